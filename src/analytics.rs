@@ -0,0 +1,74 @@
+//! Aggregate usage counters for each guild, shown to admins with `/analytics`. Only
+//! totals are kept, never who did what.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A guild's running totals since the bot started counting
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Analytics {
+    /// How many times each slash command was used, by command name
+    commands: BTreeMap<String, u64>,
+
+    /// Elections that ran until their winners were announced
+    pub elections: u64,
+
+    /// Votes counted across those elections
+    pub votes: u64,
+
+    /// Members who took part in each of those elections, added up
+    pub participants: u64,
+}
+
+impl Analytics {
+    /// Counts one use of a slash command.
+    pub fn record_command(&mut self, name: &str) {
+        *self.commands.entry(name.to_string()).or_default() += 1;
+    }
+
+    /// Adds an election whose winners were just announced.
+    pub fn record_election(&mut self, votes: usize, participants: usize) {
+        self.elections += 1;
+        self.votes += votes as u64;
+        self.participants += participants as u64;
+    }
+
+    /// Total number of slash commands used.
+    pub fn command_total(&self) -> u64 {
+        self.commands.values().sum()
+    }
+
+    /// Each command with its number of uses, most used first.
+    pub fn top_commands(&self) -> Vec<(&str, u64)> {
+        let mut commands = self.commands.iter().map(|(name, uses)| (name.as_str(), *uses)).collect::<Vec<(&str, u64)>>();
+        commands.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_add_up_across_a_session_and_survive_a_restart() {
+        let mut analytics = Analytics::default();
+        for name in ["start", "prop", "prop", "vote", "vote", "vote", "points", "stop"] {
+            analytics.record_command(name);
+        }
+        analytics.record_election(12, 3);
+        for name in ["start", "vote"] {
+            analytics.record_command(name);
+        }
+        analytics.record_election(5, 2);
+
+        assert_eq!(analytics.command_total(), 10);
+        assert_eq!(analytics.top_commands(), vec![("vote", 4), ("prop", 2), ("start", 2), ("points", 1), ("stop", 1)]);
+        assert_eq!((analytics.elections, analytics.votes, analytics.participants), (2, 17, 5));
+
+        let restored: Analytics = serde_json::from_str(&serde_json::to_string(&analytics).unwrap()).unwrap();
+        assert_eq!(restored.top_commands(), analytics.top_commands());
+        assert_eq!((restored.elections, restored.votes, restored.participants), (2, 17, 5));
+    }
+}
@@ -0,0 +1,107 @@
+//! An append-only record of actions that move credits around, reshape the ballot or
+//! export vote data, so admins can review them after the fact. Each entry is one JSON
+//! object per line.
+
+use serde::Serialize;
+use serenity::all::{GuildId, UserId};
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Where the audit log is written when no path is configured
+pub const DEFAULT_AUDIT_FILE: &str = "data/audit.jsonl";
+
+/// What was done
+#[derive(Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum AuditAction {
+    /// Credits given to another member with /delegate
+    Delegate { to: UserId, credits: usize },
+
+    /// A proposal folded into another with /merge
+    Merge { kept: usize, dropped: usize, dropped_text: String },
+
+    /// A proposal removed by /merge, brought back with /restore
+    Restore { restored: usize, text: String },
+
+    /// The pseudonymous vote matrix exported with /export-matrix
+    ExportMatrix { voters: usize },
+}
+
+/// A single line of the audit log
+#[derive(Serialize)]
+pub struct AuditEntry {
+    /// Unix timestamp (in seconds)
+    pub at: i64,
+    pub guild_id: GuildId,
+    pub actor: UserId,
+    #[serde(flatten)]
+    pub action: AuditAction,
+}
+
+/// The audit log file
+pub struct AuditLog {
+    path: PathBuf,
+
+    // Serializes writers so two entries never interleave on disk
+    write_lock: Mutex<()>,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_AUDIT_FILE)
+    }
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Appends an entry. The action has already happened, so failures are only logged.
+    pub async fn record(&self, entry: AuditEntry) {
+        if let Err(e) = self.append(&entry).await {
+            eprintln!("Failed to write audit log {}: {}", self.path.display(), e);
+        }
+    }
+
+    async fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize entry: {}", e))?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| e.to_string())?;
+        file.write_all(line.as_bytes()).await.map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_one_json_object_tagged_with_the_action() {
+        let entry = AuditEntry {
+            at: 1_700_000_000,
+            guild_id: GuildId::new(1),
+            actor: UserId::new(2),
+            action: AuditAction::Delegate { to: UserId::new(3), credits: 30 },
+        };
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"at":1700000000,"guild_id":"1","actor":"2","action":"delegate","to":"3","credits":30}"#
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! New-proposal announcements held back during a rush, so the proposals made within
+//! the guild's batch window go out as one message. Set with `/config proposal-batch`.
+
+use crate::text::{truncate_lines, DISCORD_MESSAGE_LIMIT};
+
+/// A proposal waiting to be announced
+pub struct QueuedAnnouncement {
+    /// The announcement the proposal gets when it goes out on its own
+    pub full: String,
+
+    /// Its line in a batched announcement, e.g. "#4: Park cleanup (by Ana)"
+    pub line: String,
+
+    /// Whether it has a description or link to read with `/details`
+    pub has_details: bool,
+
+    /// Whether it joined an open vote, so it can be voted for right away
+    pub votable: bool,
+}
+
+/// The message announcing a batch: a lone proposal keeps its own announcement, and
+/// several get one line each. `None` when nothing is queued.
+pub fn render(ballot: &str, mut batch: Vec<QueuedAnnouncement>) -> Option<String> {
+    if batch.len() <= 1 {
+        return batch.pop().map(|proposal| proposal.full);
+    }
+
+    let mut footer = String::new();
+    if batch.iter().any(|proposal| proposal.has_details) {
+        footer.push_str("\nRead descriptions and links with `/details <id>`");
+    }
+    if batch.iter().any(|proposal| proposal.votable) {
+        footer.push_str("\nVote for them with `/vote <votes> <id>`");
+    }
+
+    let prefix = format!("{} {} new candidates proposed:\n", ballot, batch.len());
+    let lines = batch.into_iter().map(|proposal| proposal.line).collect::<Vec<String>>();
+    Some(truncate_lines(&prefix, &lines, DISCORD_MESSAGE_LIMIT - footer.chars().count()) + &footer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued(id: usize, has_details: bool, votable: bool) -> QueuedAnnouncement {
+        QueuedAnnouncement {
+            full: format!("🗳️ New candidate proposed: #{}: Idea {}", id, id),
+            line: format!("#{}: Idea {}", id, id),
+            has_details,
+            votable,
+        }
+    }
+
+    #[test]
+    fn a_lone_proposal_keeps_its_own_announcement() {
+        assert_eq!(render("🗳️", Vec::new()), None);
+        assert_eq!(render("🗳️", vec![queued(1, true, true)]).as_deref(), Some("🗳️ New candidate proposed: #1: Idea 1"));
+    }
+
+    #[test]
+    fn several_proposals_get_a_line_each_and_the_footers_they_need() {
+        assert_eq!(
+            render("🗳️", vec![queued(1, false, false), queued(2, false, false)]).as_deref(),
+            Some("🗳️ 2 new candidates proposed:\n#1: Idea 1\n#2: Idea 2")
+        );
+        assert_eq!(
+            render("🗳️", vec![queued(1, true, false), queued(2, false, true)]).as_deref(),
+            Some(
+                "🗳️ 2 new candidates proposed:\n#1: Idea 1\n#2: Idea 2\nRead descriptions and links with `/details <id>`\nVote for them with `/vote <votes> <id>`"
+            )
+        );
+    }
+
+    #[test]
+    fn a_huge_batch_fits_in_one_message() {
+        let batch = (1..=500).map(|id| queued(id, true, true)).collect();
+        let announcement = render("🗳️", batch).unwrap();
+        assert!(announcement.chars().count() <= DISCORD_MESSAGE_LIMIT);
+        assert!(announcement.starts_with("🗳️ 500 new candidates proposed:\n#1: Idea 1\n"));
+        assert!(announcement.ends_with("Vote for them with `/vote <votes> <id>`"));
+    }
+}
@@ -0,0 +1,72 @@
+//! The tracked announcement whose live section the bot keeps editing.
+//!
+//! The static text (which includes the admin's prompt) and the live section are
+//! posted as two messages, so updates only ever edit the live one and each gets
+//! the full message length to itself.
+
+use crate::text::{truncate_lines, DISCORD_MESSAGE_LIMIT};
+use serenity::all::Message;
+
+/// How a board message is laid out
+pub struct BoardLayout {
+    /// Static text at the top, including the election prompt
+    pub intro: String,
+
+    /// The "Time remaining" line, rewritten by the countdown task
+    pub time_line: String,
+
+    /// Heading of the live section, e.g. "**Results so Far:**"
+    pub heading: &'static str,
+
+    /// Shown in the live section until there is something to list
+    pub placeholder: &'static str,
+
+    /// Lines of the live section
+    pub body: Vec<String>,
+}
+
+impl BoardLayout {
+    pub fn new(intro: String, time_line: String, heading: &'static str, placeholder: &'static str) -> Self {
+        Self { intro, time_line, heading, placeholder, body: Vec::new() }
+    }
+
+    /// Renders the live message, dropping lines that would not fit in one message.
+    pub fn render_live(&self) -> String {
+        let prefix = format!("{}\n\n{}\n", self.time_line, self.heading);
+        if self.body.is_empty() {
+            format!("{}{}", prefix, self.placeholder)
+        } else {
+            truncate_lines(&prefix, &self.body, DISCORD_MESSAGE_LIMIT)
+        }
+    }
+
+    /// Renders both messages as one, for previews.
+    pub fn render(&self) -> String {
+        format!("{}\n\n{}", self.intro, self.render_live())
+    }
+}
+
+/// A posted board: the static message and the live message below it, with their layout
+pub struct Board {
+    pub intro: Message,
+    pub live: Message,
+    pub layout: BoardLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompts_containing_the_section_headings_never_reach_the_live_message() {
+        let prompt = "Vote below!\n**Results so Far:**\n1. Fake — 99 votes\n**Suggestions so Far:**";
+        let mut layout = BoardLayout::new(prompt.to_string(), "Time remaining: 24h".to_string(), "**Results so Far:**", "No votes yet");
+        assert_eq!(layout.render_live(), "Time remaining: 24h\n\n**Results so Far:**\nNo votes yet");
+
+        layout.body = vec!["1. Parks — 4 votes".to_string()];
+        layout.time_line = "Time remaining: 3h".to_string();
+        assert_eq!(layout.render_live(), "Time remaining: 3h\n\n**Results so Far:**\n1. Parks — 4 votes");
+        assert_eq!(layout.intro, prompt);
+        assert_eq!(layout.render(), format!("{}\n\n{}", prompt, layout.render_live()));
+    }
+}
@@ -0,0 +1,351 @@
+//! Per-guild election settings that admins can change at runtime with `/config`.
+
+use serde::{Deserialize, Serialize};
+use serenity::all::ChannelId;
+
+use crate::cooldown::{CommandKind, Cooldowns};
+use crate::locale::Locale;
+use crate::restart::RestartPolicy;
+use crate::results::ResultsFormat;
+use crate::scope::ElectionScope;
+use crate::snapshot::SnapshotMode;
+use crate::theme::{Slot, Theme};
+use crate::tiebreak::TieBreak;
+use crate::voting::{CarryOver, DepartedPolicy, OverspendPolicy};
+
+/// Default minimum number of proposals needed before voting can start
+pub const DEFAULT_MIN_CANDIDATES: usize = 2;
+
+/// Default limit on the votes a member can cast for one candidate, enough to spend
+/// the default 100 credits on it
+pub const DEFAULT_MAX_VOTES: usize = 10;
+
+/// Default number of minutes between countdown refreshes of the announcement
+pub const DEFAULT_COUNTDOWN_INTERVAL_MINS: u64 = 10;
+
+/// Most roles `/config election-roles` accepts
+pub const MAX_ELECTION_ROLES: usize = 10;
+
+/// What members' credits are called in replies unless the guild names them
+pub const DEFAULT_CURRENCY_NAME: &str = "points";
+
+/// Longest name `/config currency-name` accepts, in characters
+pub const MAX_CURRENCY_NAME_LEN: usize = 24;
+
+/// Placeholders a `/config winners-message` template can use
+pub const WINNERS_PLACEHOLDERS: [&str; 3] = ["winners", "prompt", "participants"];
+
+/// Longest template `/config winners-message` accepts, in characters
+pub const MAX_WINNERS_MESSAGE_LEN: usize = 1000;
+
+/// Largest limit `/config max-candidates-per-voter` accepts
+pub const MAX_CANDIDATES_PER_VOTER: usize = 50;
+
+/// Longest time in the server `/config min-membership` can require before voting (90 days)
+pub const MAX_MIN_MEMBERSHIP_SECS: u64 = 90 * 24 * 60 * 60;
+
+/// Longest delay `/config ping-delay` accepts before the @everyone ping of a new election
+pub const MAX_PING_DELAY_SECS: u64 = 600;
+
+/// Longest window `/config proposal-batch` accepts for collecting proposal announcements
+pub const MAX_PROPOSAL_BATCH_SECS: u64 = 300;
+
+/// Settings for a single guild's elections. Settings missing from an older
+/// state file fall back to their defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildConfig {
+    /// Fewest proposals the suggestion phase must collect before `/stop` opens voting
+    pub min_candidates: usize,
+
+    /// Minutes between refreshes of the "Time remaining" line; 0 turns the countdown off
+    pub countdown_interval_mins: u64,
+
+    /// Hours between automatic credit resets; 0 resets credits only when an election ends
+    pub reset_interval_hours: u64,
+
+    /// What happens to unused credits at each automatic reset
+    pub credit_carryover: CarryOver,
+
+    /// Credits each member gets per election; `None` uses the bot-wide default
+    pub starting_credits: Option<usize>,
+
+    /// Most votes a member can cast for a single candidate
+    pub max_votes: usize,
+
+    /// How a tie for the last winning place is settled
+    pub tie_break: TieBreak,
+
+    /// Seconds a member must have been in the server before they can vote; 0 lets
+    /// anyone vote
+    pub min_membership_secs: u64,
+
+    /// What happens to a member's votes when they leave the server
+    pub departed_members: DepartedPolicy,
+
+    /// Whether proposers are DMed their candidate IDs when voting opens
+    pub notify_proposers: bool,
+
+    /// Whether the winners announcement mentions the members who proposed the winners
+    pub ping_winners: bool,
+
+    /// Minutes before the voting deadline during which votes can't be changed; 0 turns it off
+    pub freeze_window_mins: u64,
+
+    /// Whether members can still vote for candidates they haven't voted for during the freeze
+    pub freeze_allows_new_votes: bool,
+
+    /// Whether elections read members' starting credits from a snapshot file, and what
+    /// members missing from it get
+    pub credit_snapshot: SnapshotMode,
+
+    /// What `/vote` does with votes a member can't afford
+    pub overspend_policy: OverspendPolicy,
+
+    /// Most credits a member can receive from others with `/delegate` per election; `None` is unlimited
+    pub delegation_cap: Option<usize>,
+
+    /// Credits a member pays for each proposal, refunded if a moderator rejects it; 0 is free
+    pub proposal_cost: usize,
+
+    /// Largest share of the starting budget, in percent, that a member's votes for one
+    /// candidate may cost; `None` is unlimited
+    pub max_concentration: Option<usize>,
+
+    /// Most different candidates a member can vote for; `None` is unlimited
+    pub max_candidates_per_voter: Option<usize>,
+
+    /// How much detail the live results on the announcement show
+    pub results_format: ResultsFormat,
+
+    /// Whether the standings are shown while voting is open; when off, only the number
+    /// of votes cast is, until the winners are announced
+    pub live_results: bool,
+
+    /// Whether members' activity is tallied for `/leaderboard`; off unless the guild opts in
+    pub leaderboard: bool,
+
+    /// Whether admins may export the pseudonymous vote matrix with `/export-matrix`;
+    /// off unless the guild opts in
+    pub research_export: bool,
+
+    /// Whether the guild runs one election, or an independent one in each channel
+    pub election_scope: ElectionScope,
+
+    /// What `/start` does when an election is already running
+    pub on_restart: RestartPolicy,
+
+    /// Roles, by name or ID, whose members can run elections besides the owner and administrators
+    pub election_roles: Vec<String>,
+
+    /// Roles, by name or ID, whose members can see the full results with `/results private`
+    /// while live results are hidden, without being able to run elections
+    pub spectator_roles: Vec<String>,
+
+    /// Minutes members have to ratify the winners with ✅ or ❌ reactions once they're
+    /// announced; 0 skips ratification
+    pub ratify_window_mins: u64,
+
+    /// Whether a public notice is posted when `/stop` can't open voting yet
+    pub blocked_notice: bool,
+
+    /// Seconds between posting a new election's announcement and pinging @everyone about it,
+    /// giving admins time to `/cancel` a mistake; 0 pings with the announcement itself
+    pub ping_delay_secs: u64,
+
+    /// Seconds new proposals are collected before being announced together; 0 announces
+    /// each one as it's made
+    pub proposal_batch_secs: u64,
+
+    /// Whether `/stop` asks for confirmation before it ends the vote and announces the winners
+    pub confirm_stop: bool,
+
+    /// Channel where proposals wait for a moderator's approval; `None` announces them right away
+    pub moderation_channel: Option<ChannelId>,
+
+    /// Seconds members wait between commands of each kind, set with `/config cooldown`
+    pub cooldowns: Cooldowns,
+
+    /// Emoji used in the guild's announcements, set with `/config emoji`
+    pub theme: Theme,
+
+    /// Language of the guild's announcements, set with `/config locale`
+    pub locale: Locale,
+
+    /// What the guild calls its credits, e.g. "voice credits"; `None` keeps the usual wording
+    pub currency_name: Option<String>,
+
+    /// Wording of the winners announcement, with `WINNERS_PLACEHOLDERS` filled in;
+    /// `None` uses the locale's announcement
+    pub winners_message: Option<String>,
+}
+
+impl Default for GuildConfig {
+    fn default() -> Self {
+        Self {
+            min_candidates: DEFAULT_MIN_CANDIDATES,
+            countdown_interval_mins: DEFAULT_COUNTDOWN_INTERVAL_MINS,
+            reset_interval_hours: 0,
+            credit_carryover: CarryOver::default(),
+            starting_credits: None,
+            max_votes: DEFAULT_MAX_VOTES,
+            tie_break: TieBreak::default(),
+            min_membership_secs: 0,
+            departed_members: DepartedPolicy::default(),
+            notify_proposers: false,
+            ping_winners: false,
+            freeze_window_mins: 0,
+            freeze_allows_new_votes: true,
+            overspend_policy: OverspendPolicy::default(),
+            delegation_cap: None,
+            proposal_cost: 0,
+            credit_snapshot: SnapshotMode::default(),
+            max_concentration: None,
+            max_candidates_per_voter: None,
+            results_format: ResultsFormat::default(),
+            live_results: true,
+            leaderboard: false,
+            research_export: false,
+            election_scope: ElectionScope::default(),
+            on_restart: RestartPolicy::default(),
+            election_roles: vec![crate::BOT_ROLE.to_string()],
+            spectator_roles: Vec::new(),
+            ratify_window_mins: 0,
+            blocked_notice: false,
+            ping_delay_secs: 0,
+            proposal_batch_secs: 0,
+            confirm_stop: true,
+            moderation_channel: None,
+            cooldowns: Cooldowns::default(),
+            theme: Theme::default(),
+            locale: Locale::default(),
+            currency_name: None,
+            winners_message: None,
+        }
+    }
+}
+
+impl GuildConfig {
+    /// What members' credits are called in replies to them.
+    pub fn currency(&self) -> &str {
+        self.currency_name.as_deref().unwrap_or(DEFAULT_CURRENCY_NAME)
+    }
+
+    /// Every setting with its current value, named after the `/config` subcommand
+    /// that changes it, for `/config show`.
+    pub fn settings(&self) -> Vec<(&'static str, String)> {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" }.to_string();
+        let or_off = |value: u64, unit: &str| if value == 0 { "off".to_string() } else { format!("{} {}", value, unit) };
+
+        vec![
+            ("min-candidates", self.min_candidates.to_string()),
+            ("countdown-interval", or_off(self.countdown_interval_mins, "minutes")),
+            ("reset-interval", or_off(self.reset_interval_hours, "hours")),
+            ("credit-carryover", match self.credit_carryover {
+                CarryOver::Reset => "reset".to_string(),
+                CarryOver::Carry => "carry".to_string(),
+                CarryOver::Decay { percent } => format!("decay, keeping {}%", percent),
+            }),
+            ("starting-credits", self.starting_credits.unwrap_or(crate::STARTING_POINTS).to_string()),
+            ("max-votes", self.max_votes.to_string()),
+            ("tie-break", self.tie_break.name().to_string()),
+            ("freeze-window", match self.freeze_window_mins {
+                0 => "off".to_string(),
+                mins if self.freeze_allows_new_votes => format!("{} minutes, new votes allowed", mins),
+                mins => format!("{} minutes, new votes blocked", mins),
+            }),
+            ("leaderboard", on_off(self.leaderboard)),
+            ("research-export", on_off(self.research_export)),
+            ("locale", self.locale.label().to_string()),
+            ("currency-name", self.currency().to_string()),
+            ("overspend-policy", self.overspend_policy.name().to_string()),
+            ("results-format", self.results_format.name().to_string()),
+            ("live-results", on_off(self.live_results)),
+            ("election-scope", self.election_scope.name().to_string()),
+            ("on-restart", self.on_restart.name().to_string()),
+            ("ping-delay", or_off(self.ping_delay_secs, "seconds")),
+            ("proposal-batch", or_off(self.proposal_batch_secs, "seconds")),
+            ("stop-confirmation", on_off(self.confirm_stop)),
+            ("ratification", or_off(self.ratify_window_mins, "minutes")),
+            ("blocked-notice", on_off(self.blocked_notice)),
+            ("proposer-dms", on_off(self.notify_proposers)),
+            ("ping-winners", on_off(self.ping_winners)),
+            ("winners-message", self.winners_message.clone().unwrap_or_else(|| "default".to_string())),
+            ("min-membership", match self.min_membership_secs {
+                0 => "off".to_string(),
+                secs => crate::schedule::format_duration(secs),
+            }),
+            ("departed-members", self.departed_members.name().to_string()),
+            ("delegation-cap", self.delegation_cap.map_or_else(|| "no limit".to_string(), |cap| format!("{} credits", cap))),
+            ("proposal-cost", match self.proposal_cost {
+                0 => "free".to_string(),
+                cost => format!("{} credits", cost),
+            }),
+            ("credit-snapshot", self.credit_snapshot.name().to_string()),
+            ("max-concentration", self.max_concentration.map_or_else(|| "no limit".to_string(), |percent| format!("{}%", percent))),
+            ("max-candidates-per-voter", self.max_candidates_per_voter.map_or_else(|| "no limit".to_string(), |max| max.to_string())),
+            ("moderation", self.moderation_channel.map_or_else(|| "off".to_string(), |channel_id| format!("<#{}>", channel_id))),
+            ("cooldown", CommandKind::ALL
+                .iter()
+                .map(|kind| format!("{} {}s", kind.name(), self.cooldowns.get(*kind)))
+                .collect::<Vec<String>>()
+                .join(", ")),
+            ("emoji", Slot::ALL
+                .iter()
+                .map(|slot| format!("{} {}", slot.name(), self.theme.get(*slot)))
+                .collect::<Vec<String>>()
+                .join(", ")),
+            ("election-roles", if self.election_roles.is_empty() {
+                "none".to_string()
+            } else {
+                self.election_roles.join(", ")
+            }),
+            ("spectator-roles", if self.spectator_roles.is_empty() {
+                "none".to_string()
+            } else {
+                self.spectator_roles.join(", ")
+            }),
+        ]
+    }
+
+    /// One line per setting for `/config show`, marking those left at their default.
+    pub fn describe_settings(&self) -> Vec<String> {
+        self.settings()
+            .into_iter()
+            .zip(GuildConfig::default().settings())
+            .map(|((name, value), (_, default))| if value == default {
+                format!("**{}:** {} *(default)*", name, value)
+            } else {
+                format!("**{}:** {}", name, value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The line `/config show` lists for a setting.
+    fn shown(config: &GuildConfig, setting: &str) -> String {
+        let prefix = format!("**{}:** ", setting);
+        config.describe_settings().into_iter().find(|line| line.starts_with(&prefix)).expect("setting listed")
+    }
+
+    #[test]
+    fn config_show_reflects_changed_settings() {
+        let mut config = GuildConfig::default();
+        assert!(config.describe_settings().iter().all(|line| line.ends_with("*(default)*")));
+        assert_eq!(shown(&config, "max-votes"), format!("**max-votes:** {} *(default)*", config.max_votes));
+
+        config.max_votes = 7;
+        config.ping_winners = true;
+        config.currency_name = Some("tokens".to_string());
+        config.credit_carryover = CarryOver::Decay { percent: 40 };
+        assert_eq!(shown(&config, "max-votes"), "**max-votes:** 7");
+        assert_eq!(shown(&config, "ping-winners"), "**ping-winners:** on");
+        assert_eq!(shown(&config, "currency-name"), "**currency-name:** tokens");
+        assert_eq!(shown(&config, "credit-carryover"), "**credit-carryover:** decay, keeping 40%");
+        assert_eq!(config.describe_settings().iter().filter(|line| !line.ends_with("*(default)*")).count(), 4);
+    }
+}
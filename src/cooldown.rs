@@ -0,0 +1,116 @@
+//! How long members wait between commands. Each kind of command has its own
+//! cooldown, set per guild with `/config cooldown`, so cheap reads aren't throttled
+//! like votes are.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest cooldown accepted by `/config cooldown`, in seconds
+pub const MAX_COOLDOWN_SECS: u64 = 60 * 60;
+
+/// The groups of commands that share a cooldown
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CommandKind {
+    /// /prop and /editprop
+    Propose,
+
+    /// /vote, /votemulti, /unvote and the retract buttons of /myvotes
+    Vote,
+
+    /// /delegate
+    Delegate,
+
+    /// Read-only commands such as /points, /list and /status
+    Read,
+}
+
+impl CommandKind {
+    /// Every kind, as accepted by `/config cooldown`
+    pub const ALL: [CommandKind; 4] = [CommandKind::Propose, CommandKind::Vote, CommandKind::Delegate, CommandKind::Read];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CommandKind::Propose => "propose",
+            CommandKind::Vote => "vote",
+            CommandKind::Delegate => "delegate",
+            CommandKind::Read => "read",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// A guild's cooldowns in seconds; 0 turns a cooldown off. Cooldowns missing from
+/// an older state file fall back to the defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Cooldowns {
+    pub propose: u64,
+    pub vote: u64,
+    pub delegate: u64,
+    pub read: u64,
+}
+
+impl Default for Cooldowns {
+    fn default() -> Self {
+        Self {
+            propose: 2,
+            vote: 2,
+            delegate: 2,
+            read: 0,
+        }
+    }
+}
+
+impl Cooldowns {
+    pub fn get(&self, kind: CommandKind) -> u64 {
+        match kind {
+            CommandKind::Propose => self.propose,
+            CommandKind::Vote => self.vote,
+            CommandKind::Delegate => self.delegate,
+            CommandKind::Read => self.read,
+        }
+    }
+
+    pub fn set(&mut self, kind: CommandKind, secs: u64) {
+        match kind {
+            CommandKind::Propose => self.propose = secs,
+            CommandKind::Vote => self.vote = secs,
+            CommandKind::Delegate => self.delegate = secs,
+            CommandKind::Read => self.read = secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_parse_from_their_names() {
+        for kind in CommandKind::ALL {
+            assert_eq!(CommandKind::parse(kind.name()), Some(kind));
+        }
+        assert_eq!(CommandKind::parse("Vote"), None);
+        assert_eq!(CommandKind::parse(""), None);
+    }
+
+    #[test]
+    fn each_kind_has_its_own_cooldown() {
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.set(CommandKind::Vote, 30);
+        assert_eq!(cooldowns.get(CommandKind::Vote), 30);
+        assert_eq!(cooldowns.get(CommandKind::Propose), 2);
+        assert_eq!(cooldowns.get(CommandKind::Delegate), 2);
+        assert_eq!(cooldowns.get(CommandKind::Read), 0);
+    }
+
+    #[test]
+    fn cooldowns_missing_from_the_state_file_use_the_defaults() {
+        let cooldowns: Cooldowns = serde_json::from_str(r#"{"vote":10}"#).unwrap();
+        assert_eq!(cooldowns.get(CommandKind::Vote), 10);
+        assert_eq!(cooldowns.get(CommandKind::Propose), 2);
+        assert_eq!(cooldowns.get(CommandKind::Read), 0);
+    }
+}
@@ -0,0 +1,497 @@
+//! Why a slash command was refused, and the message shown to the user for it.
+
+use std::fmt;
+
+use crate::theme;
+
+/// The outcome of a slash command: a success message, or why it was refused
+pub type CommandResult = Result<String, CommandError>;
+
+/// A refused slash command. Handlers return these instead of message strings so
+/// callers can tell outcomes apart; `Display` renders the user-facing message.
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    /// Used outside of a server, e.g. in DMs
+    NotInGuild,
+
+    /// The server has no election state, e.g. it isn't approved
+    NotConfigured,
+
+    /// A command reserved for whoever owns the bot's application
+    NotBotOwner,
+
+    /// The server isn't in the cache, so permissions can't be checked
+    CacheUnavailable,
+
+    /// The user's last command of the same kind was within the guild's cooldown
+    RateLimited { wait_secs: u64 },
+
+    /// The user isn't an admin; `action` completes "You need one of the following to ..."
+    MissingPermission { action: &'static str },
+
+    /// `/results private` used by someone who is neither a spectator nor a server admin
+    NotSpectator,
+
+    /// Something that can't happen during voting; `action` is e.g. "Candidates cannot be proposed"
+    VoteInProgress { action: &'static str },
+
+    /// A vote command outside of the voting phase
+    NoVoteInProgress,
+
+    /// The vote was finalized while the command was running
+    VotingEnded,
+
+    /// No election is running
+    NoElection,
+
+    /// The running election is voted on with reactions instead of commands
+    ReactionVoting,
+
+    /// A reaction voting election already has a candidate for every number reaction
+    ReactionLimit { max: usize },
+
+    /// Voting can't open with fewer proposals than the guild's minimum
+    NotEnoughCandidates { needed: usize, have: usize },
+
+    NoSuchCandidate(usize),
+
+    /// Candidate IDs are handed out starting from 1
+    CandidateIdZero,
+
+    NoSuchProposal(usize),
+
+    /// Only a proposal's author or an admin can edit it
+    NotProposalAuthor,
+
+    DuplicateProposal(String),
+
+    /// /restore with no removed proposal left to bring back
+    NothingToRestore,
+
+    /// The moderation channel couldn't be posted to, so the proposal wasn't queued
+    ModerationUnavailable,
+
+    /// `what` is e.g. "Proposal ideas"
+    TooLong { what: &'static str, max: usize },
+
+    /// `what` is e.g. "Number of votes"; `unit` is appended to the maximum
+    OutOfRange { what: &'static str, min: i64, max: i64, unit: &'static str },
+
+    /// More votes for one candidate than the guild allows
+    OverVoteLimit { candidate_id: usize, votes: usize, max: usize },
+
+    InsufficientCredits { cost: usize, available: usize },
+
+    /// The member can't pay what proposing costs on this server
+    ProposalUnaffordable { cost: usize, available: usize },
+
+    /// Votes for one candidate cost more than the guild's share of the starting budget
+    OverConcentration { candidate_id: usize, cost: usize, cap: usize, percent: usize },
+
+    /// A vote for a new candidate when the member already backs as many as the guild allows
+    TooManyCandidates { max: usize },
+
+    /// /delegate to oneself
+    SelfDelegation,
+
+    /// The recipient can't receive more delegated credits this election
+    DelegationCapReached { cap: usize, room: usize },
+
+    /// The member joined the server too recently to vote; `eligible_at` is a unix timestamp
+    MembershipTooNew { eligible_at: i64 },
+
+    /// An admin closed the election to new proposals before voting
+    ProposalsLocked,
+
+    /// The user has nothing to retract on this candidate
+    NoVotesFor(usize),
+
+    /// The vote is inside its freeze window; `changes_existing_vote` tells which rule applied
+    VotesFrozen { minutes: u64, changes_existing_vote: bool },
+
+    /// A /votemulti batch that was refused as a whole
+    BatchRejected(Box<CommandError>),
+
+    /// `/vote-all` can't add a vote for every candidate: credits or per-candidate limits ran out
+    NothingToSpread,
+
+    /// The guild hasn't opted in to `/leaderboard`
+    LeaderboardDisabled,
+
+    /// The guild hasn't opted in to `/export-matrix`
+    ResearchExportDisabled,
+
+    NoSuchTemplate(String),
+
+    /// /template save with a name that's already taken
+    DuplicateTemplate(String),
+
+    /// The guild already has as many templates as it can keep
+    TemplateLimit { max: usize },
+
+    /// Another /start for the guild hasn't finished yet
+    ElectionStarting,
+
+    /// An election is already running and `/config on-restart` is set to reject
+    ElectionRunning,
+
+    /// /nextround with no finished election to follow up on
+    NoFinishedElection,
+
+    /// The guild's credit snapshot is missing or invalid, so the election can't start
+    CreditSnapshot { problems: Vec<String> },
+
+    /// An election is already queued with /schedule
+    AlreadyScheduled { start_at: i64 },
+
+    NothingScheduled,
+
+    /// The announcements channel is missing
+    NoAnnouncementChannel { channel: &'static str },
+
+    /// Malformed input, described by the message
+    InvalidInput(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::NotInGuild => write!(f, "{} This command can only be used in a server!", theme::ERROR),
+            CommandError::NotConfigured => write!(f, "{} Server not configured for voting. Contact an administrator.", theme::ERROR),
+            CommandError::NotBotOwner => write!(f, "{} Only the bot's owner can use this command.", theme::ERROR),
+            CommandError::CacheUnavailable => write!(f, "{} Unable to access server information right now, so permissions can't be checked. Please try again shortly.", theme::ERROR),
+            CommandError::RateLimited { wait_secs: 1 } => write!(f, "{} Please wait 1 more second before using this command again!", theme::WAITING),
+            CommandError::RateLimited { wait_secs } => write!(f, "{} Please wait {} more seconds before using this command again!", theme::WAITING, wait_secs),
+            CommandError::NotSpectator => write!(
+                f,
+                "{} Only the Server Owner, members with the Administrator permission and this server's spectator roles can see the private results.",
+                theme::ERROR
+            ),
+            CommandError::MissingPermission { action } => write!(
+                f,
+                "{} You need one of the following to {}:\n• Server Owner\n• Administrator permission\n• One of this server's election roles (see `/whoami`)",
+                theme::ERROR,
+                action
+            ),
+            CommandError::VoteInProgress { action } => write!(f, "{} {} while the vote is ongoing!", theme::ERROR, action),
+            CommandError::ProposalsLocked => write!(f, "{} Proposals are closed for this election while they're reviewed. Voting opens soon!", theme::ERROR),
+            CommandError::NoVoteInProgress => write!(f, "{} There's no vote in progress right now.", theme::ERROR),
+            CommandError::VotingEnded => write!(f, "{} Voting has ended!", theme::ERROR),
+            CommandError::NoElection => write!(f, "{} There's no election running right now. An admin can start one with `/start`.", theme::ERROR),
+            CommandError::ReactionVoting => write!(f, "{} This election uses reaction voting. React with a candidate's number on the announcement instead!", theme::ERROR),
+            CommandError::ReactionLimit { max } => write!(f, "{} This election uses reaction voting, which allows at most {} candidates!", theme::ERROR, max),
+            CommandError::NotEnoughCandidates { needed, have } => write!(
+                f,
+                "{} At least {} proposals are needed before voting can start (currently {}). Collect more with `/prop` or lower the minimum with `/config min-candidates`.",
+                theme::ERROR,
+                needed, have
+            ),
+            CommandError::NoSuchCandidate(id) => write!(f, "{} Candidate #{} does not exist!", theme::ERROR, id),
+            CommandError::CandidateIdZero => write!(f, "{} Candidate IDs start from 1. Use `/vote <votes> <candidate_id>` where candidate_id ≥ 1", theme::ERROR),
+            CommandError::NoSuchProposal(id) => write!(f, "{} Proposal #{} does not exist!", theme::ERROR, id),
+            CommandError::NotProposalAuthor => write!(f, "{} Only the author of a proposal or an admin can edit it!", theme::ERROR),
+            CommandError::DuplicateProposal(idea) => write!(f, "{} The proposal '{}' already exists!", theme::ERROR, idea),
+            CommandError::NothingToRestore => write!(f, "{} There's no removed proposal to restore. Proposals can only be restored until voting starts.", theme::ERROR),
+            CommandError::ModerationUnavailable => write!(
+                f,
+                "{} Your proposal couldn't be sent to the moderators. Please ask an admin to check the moderation channel.",
+                theme::ERROR
+            ),
+            CommandError::TooLong { what, max } => write!(f, "{} {} must be {} characters or less!", theme::ERROR, what, max),
+            CommandError::OutOfRange { what, min, max, unit } => write!(f, "{} {} must be between {} and {}{}!", theme::ERROR, what, min, max, unit),
+            CommandError::OverVoteLimit { candidate_id, votes, max } => write!(
+                f,
+                "{} {} votes for candidate #{} is over this server's limit of {} per candidate.",
+                theme::ERROR,
+                votes, candidate_id, max
+            ),
+            CommandError::InsufficientCredits { cost, available } => write!(
+                f,
+                "{} Insufficient points! These votes cost {} points, but you can only spend {}.",
+                theme::ERROR,
+                cost, available
+            ),
+            CommandError::ProposalUnaffordable { cost, available } => write!(
+                f,
+                "{} Proposing costs {} points on this server, but you only have {} left.",
+                theme::ERROR,
+                cost, available
+            ),
+            CommandError::OverConcentration { candidate_id, cost, cap, percent } => write!(
+                f,
+                "{} Votes for one candidate can cost at most {} credits ({}% of the starting budget), but these votes for candidate #{} cost {}. Spread your votes over more candidates!",
+                theme::ERROR,
+                cap, percent, candidate_id, cost
+            ),
+            CommandError::TooManyCandidates { max } => write!(
+                f,
+                "{} You can vote for at most {} different candidates. You can still change your votes for the ones you've backed, or retract one with `/unvote` to back another.",
+                theme::ERROR,
+                max
+            ),
+            CommandError::SelfDelegation => write!(f, "{} You can't delegate credits to yourself!", theme::ERROR),
+            CommandError::DelegationCapReached { cap, room } => write!(
+                f,
+                "{} Members can receive at most {} delegated credits per election; this one can take {} more.",
+                theme::ERROR,
+                cap,
+                room
+            ),
+            CommandError::MembershipTooNew { eligible_at } => write!(
+                f,
+                "{} You joined this server too recently to vote in its elections. You'll be able to vote <t:{}:R>.",
+                theme::WAITING,
+                eligible_at
+            ),
+            CommandError::NoVotesFor(id) => write!(f, "{} You have no votes for candidate #{}.", theme::ERROR, id),
+            CommandError::VotesFrozen { minutes, changes_existing_vote: true } => write!(
+                f,
+                "{} Voting is frozen for the final {} minutes: existing votes can no longer be changed or retracted.",
+                theme::FROZEN,
+                minutes
+            ),
+            CommandError::VotesFrozen { minutes, changes_existing_vote: false } => write!(
+                f,
+                "{} Voting is frozen for the final {} minutes: no more votes can be cast.",
+                theme::FROZEN,
+                minutes
+            ),
+            CommandError::BatchRejected(inner) => write!(f, "{} No votes were cast.", inner),
+            CommandError::NothingToSpread => write!(
+                f,
+                "{} There's nothing left to spread: your remaining points can't buy another vote for each candidate, or they all have as many of your votes as this server allows.",
+                theme::ERROR
+            ),
+            CommandError::LeaderboardDisabled => write!(
+                f,
+                "{} The leaderboard is turned off on this server. An admin can turn it on with `/config leaderboard`.",
+                theme::ERROR
+            ),
+            CommandError::ResearchExportDisabled => write!(
+                f,
+                "{} Exporting vote data is turned off on this server. An admin can allow it with `/config research-export`.",
+                theme::ERROR
+            ),
+            CommandError::NoSuchTemplate(name) => write!(f, "{} There's no template named `{}`. See the saved ones with `/template list`.", theme::ERROR, name),
+            CommandError::DuplicateTemplate(name) => write!(
+                f,
+                "{} A template named `{}` already exists. Delete it first with `/template delete` or pick another name.",
+                theme::ERROR,
+                name
+            ),
+            CommandError::TemplateLimit { max } => write!(f, "{} This server already has {} templates. Delete one with `/template delete` first.", theme::ERROR, max),
+            CommandError::ElectionStarting => write!(f, "{} An election is already being started. Check the announcements channel in a moment.", theme::ERROR),
+            CommandError::NoFinishedElection => write!(f, "{} There's no finished election to follow up on. `/nextround` works once winners were announced, until another election starts.", theme::ERROR),
+            CommandError::ElectionRunning => write!(f, "{} An election is already running. End it with `/stop` or `/cancel` first, or change what `/start` does with `/config on-restart`.", theme::ERROR),
+            CommandError::CreditSnapshot { problems } => write!(
+                f,
+                "{} The credit snapshot couldn't be used, so the election wasn't started:\n{}\nFix the file or turn snapshots off with `/config credit-snapshot`.",
+                theme::ERROR,
+                crate::snapshot::describe_problems(problems)
+            ),
+            CommandError::AlreadyScheduled { start_at } => write!(
+                f,
+                "{} An election is already scheduled for <t:{}:F>. Cancel it first with `/schedule cancel`.",
+                theme::ERROR,
+                start_at
+            ),
+            CommandError::NothingScheduled => write!(f, "{} There is no scheduled election to cancel!", theme::ERROR),
+            CommandError::NoAnnouncementChannel { channel } => write!(f, "{} Announcement channel '{}' not found. Please create it first.", theme::ERROR, channel),
+            CommandError::InvalidInput(message) => write!(f, "{} {}", theme::ERROR, message),
+        }
+    }
+}
+
+/// Renders a command's outcome as the message shown to the user.
+pub fn render(result: CommandResult) -> String {
+    result.unwrap_or_else(|why| why.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limits_of_one_second_use_the_singular() {
+        assert_eq!(
+            CommandError::RateLimited { wait_secs: 1 }.to_string(),
+            "⏱️ Please wait 1 more second before using this command again!"
+        );
+        assert_eq!(
+            CommandError::RateLimited { wait_secs: 5 }.to_string(),
+            "⏱️ Please wait 5 more seconds before using this command again!"
+        );
+    }
+
+    #[test]
+    fn rejected_batches_wrap_the_reason_of_the_refused_pair() {
+        let why = CommandError::BatchRejected(Box::new(CommandError::NoSuchCandidate(7)));
+        assert_eq!(why.to_string(), "❌ Candidate #7 does not exist! No votes were cast.");
+
+        let why = CommandError::BatchRejected(Box::new(CommandError::InsufficientCredits { cost: 125, available: 100 }));
+        assert_eq!(
+            why.to_string(),
+            "❌ Insufficient points! These votes cost 125 points, but you can only spend 100. No votes were cast."
+        );
+    }
+
+    #[test]
+    fn render_shows_successes_as_they_are() {
+        assert_eq!(render(Ok("✅ Done".to_string())), "✅ Done");
+        assert_eq!(render(Err(CommandError::NoVotesFor(3))), "❌ You have no votes for candidate #3.");
+    }
+
+    #[test]
+    fn every_variant_renders_its_message() {
+        let cases = [
+            (CommandError::NotInGuild, "❌ This command can only be used in a server!"),
+            (CommandError::NotConfigured, "❌ Server not configured for voting. Contact an administrator."),
+            (CommandError::NotBotOwner, "❌ Only the bot's owner can use this command."),
+            (
+                CommandError::CacheUnavailable,
+                "❌ Unable to access server information right now, so permissions can't be checked. Please try again shortly.",
+            ),
+            (
+                CommandError::MissingPermission { action: "start elections" },
+                "❌ You need one of the following to start elections:\n• Server Owner\n• Administrator permission\n• One of this server's election roles (see `/whoami`)",
+            ),
+            (
+                CommandError::NotSpectator,
+                "❌ Only the Server Owner, members with the Administrator permission and this server's spectator roles can see the private results.",
+            ),
+            (
+                CommandError::VoteInProgress { action: "Candidates cannot be proposed" },
+                "❌ Candidates cannot be proposed while the vote is ongoing!",
+            ),
+            (CommandError::NoVoteInProgress, "❌ There's no vote in progress right now."),
+            (CommandError::VotingEnded, "❌ Voting has ended!"),
+            (CommandError::NoElection, "❌ There's no election running right now. An admin can start one with `/start`."),
+            (
+                CommandError::ReactionVoting,
+                "❌ This election uses reaction voting. React with a candidate's number on the announcement instead!",
+            ),
+            (CommandError::ReactionLimit { max: 10 }, "❌ This election uses reaction voting, which allows at most 10 candidates!"),
+            (
+                CommandError::NotEnoughCandidates { needed: 3, have: 1 },
+                "❌ At least 3 proposals are needed before voting can start (currently 1). Collect more with `/prop` or lower the minimum with `/config min-candidates`.",
+            ),
+            (CommandError::NoSuchCandidate(4), "❌ Candidate #4 does not exist!"),
+            (
+                CommandError::CandidateIdZero,
+                "❌ Candidate IDs start from 1. Use `/vote <votes> <candidate_id>` where candidate_id ≥ 1",
+            ),
+            (CommandError::NoSuchProposal(4), "❌ Proposal #4 does not exist!"),
+            (CommandError::NotProposalAuthor, "❌ Only the author of a proposal or an admin can edit it!"),
+            (CommandError::DuplicateProposal("Bike racks".to_string()), "❌ The proposal 'Bike racks' already exists!"),
+            (
+                CommandError::NothingToRestore,
+                "❌ There's no removed proposal to restore. Proposals can only be restored until voting starts.",
+            ),
+            (
+                CommandError::ModerationUnavailable,
+                "❌ Your proposal couldn't be sent to the moderators. Please ask an admin to check the moderation channel.",
+            ),
+            (CommandError::TooLong { what: "Proposal ideas", max: 200 }, "❌ Proposal ideas must be 200 characters or less!"),
+            (
+                CommandError::OutOfRange { what: "Duration", min: 1, max: 720, unit: " hours" },
+                "❌ Duration must be between 1 and 720 hours!",
+            ),
+            (
+                CommandError::OverVoteLimit { candidate_id: 2, votes: 12, max: 10 },
+                "❌ 12 votes for candidate #2 is over this server's limit of 10 per candidate.",
+            ),
+            (
+                CommandError::InsufficientCredits { cost: 121, available: 100 },
+                "❌ Insufficient points! These votes cost 121 points, but you can only spend 100.",
+            ),
+            (
+                CommandError::ProposalUnaffordable { cost: 10, available: 4 },
+                "❌ Proposing costs 10 points on this server, but you only have 4 left.",
+            ),
+            (
+                CommandError::OverConcentration { candidate_id: 1, cost: 64, cap: 50, percent: 50 },
+                "❌ Votes for one candidate can cost at most 50 credits (50% of the starting budget), but these votes for candidate #1 cost 64. Spread your votes over more candidates!",
+            ),
+            (
+                CommandError::TooManyCandidates { max: 3 },
+                "❌ You can vote for at most 3 different candidates. You can still change your votes for the ones you've backed, or retract one with `/unvote` to back another.",
+            ),
+            (CommandError::SelfDelegation, "❌ You can't delegate credits to yourself!"),
+            (
+                CommandError::DelegationCapReached { cap: 50, room: 20 },
+                "❌ Members can receive at most 50 delegated credits per election; this one can take 20 more.",
+            ),
+            (
+                CommandError::MembershipTooNew { eligible_at: 1_700_000_000 },
+                "⏱️ You joined this server too recently to vote in its elections. You'll be able to vote <t:1700000000:R>.",
+            ),
+            (
+                CommandError::ProposalsLocked,
+                "❌ Proposals are closed for this election while they're reviewed. Voting opens soon!",
+            ),
+            (CommandError::NoVotesFor(3), "❌ You have no votes for candidate #3."),
+            (
+                CommandError::VotesFrozen { minutes: 10, changes_existing_vote: true },
+                "🧊 Voting is frozen for the final 10 minutes: existing votes can no longer be changed or retracted.",
+            ),
+            (
+                CommandError::VotesFrozen { minutes: 10, changes_existing_vote: false },
+                "🧊 Voting is frozen for the final 10 minutes: no more votes can be cast.",
+            ),
+            (
+                CommandError::NothingToSpread,
+                "❌ There's nothing left to spread: your remaining points can't buy another vote for each candidate, or they all have as many of your votes as this server allows.",
+            ),
+            (
+                CommandError::LeaderboardDisabled,
+                "❌ The leaderboard is turned off on this server. An admin can turn it on with `/config leaderboard`.",
+            ),
+            (
+                CommandError::ResearchExportDisabled,
+                "❌ Exporting vote data is turned off on this server. An admin can allow it with `/config research-export`.",
+            ),
+            (
+                CommandError::NoSuchTemplate("weekly".to_string()),
+                "❌ There's no template named `weekly`. See the saved ones with `/template list`.",
+            ),
+            (
+                CommandError::DuplicateTemplate("weekly".to_string()),
+                "❌ A template named `weekly` already exists. Delete it first with `/template delete` or pick another name.",
+            ),
+            (
+                CommandError::TemplateLimit { max: 25 },
+                "❌ This server already has 25 templates. Delete one with `/template delete` first.",
+            ),
+            (
+                CommandError::ElectionStarting,
+                "❌ An election is already being started. Check the announcements channel in a moment.",
+            ),
+            (
+                CommandError::ElectionRunning,
+                "❌ An election is already running. End it with `/stop` or `/cancel` first, or change what `/start` does with `/config on-restart`.",
+            ),
+            (
+                CommandError::NoFinishedElection,
+                "❌ There's no finished election to follow up on. `/nextround` works once winners were announced, until another election starts.",
+            ),
+            (
+                CommandError::CreditSnapshot { problems: vec!["line 2: not a number".to_string()] },
+                "❌ The credit snapshot couldn't be used, so the election wasn't started:\n• line 2: not a number\nFix the file or turn snapshots off with `/config credit-snapshot`.",
+            ),
+            (
+                CommandError::AlreadyScheduled { start_at: 1_700_000_000 },
+                "❌ An election is already scheduled for <t:1700000000:F>. Cancel it first with `/schedule cancel`.",
+            ),
+            (CommandError::NothingScheduled, "❌ There is no scheduled election to cancel!"),
+            (
+                CommandError::NoAnnouncementChannel { channel: "announcements" },
+                "❌ Announcement channel 'announcements' not found. Please create it first.",
+            ),
+            (CommandError::InvalidInput("Unknown option.".to_string()), "❌ Unknown option."),
+        ];
+
+        for (why, message) in cases {
+            assert_eq!(why.to_string(), message, "{:?}", why);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! A tiny HTTP server. `GET /healthz` answers 200 while the Discord gateway is
+//! connected and 503 otherwise, so a container orchestrator's probe can restart the
+//! bot when the gateway drops for good. With an API key configured, it also takes
+//! proposals on `POST /guild/<id>/proposal` (see [`crate::intake`]).
+
+use crate::intake::{ExternalProposal, Intake};
+use serenity::all::GuildId;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// How long a client has to send its request before the connection is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest request accepted, headers and body together; a proposal is small
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+/// Serves the endpoints on the given address until the process exits.
+pub async fn serve(addr: String, ready: Arc<AtomicBool>, intake: Option<Arc<Intake>>) {
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to bind health endpoint on {}: {}", addr, e);
+            return;
+        }
+    };
+    println!("Serving health checks on http://{}/healthz", addr);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let ready = ready.clone();
+                let intake = intake.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = respond(stream, &ready, intake.as_deref()).await {
+                        eprintln!("Failed to answer health check: {}", e);
+                    }
+                });
+            },
+            Err(e) => eprintln!("Failed to accept health check connection: {}", e),
+        }
+    }
+}
+
+async fn respond(mut stream: TcpStream, ready: &AtomicBool, intake: Option<&Intake>) -> std::io::Result<()> {
+    let Ok(request) = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await else {
+        return Ok(());
+    };
+    let Some(request) = request? else {
+        return write_response(&mut stream, "413 Payload Too Large", "request too large", false).await;
+    };
+
+    // e.g. "GET /healthz HTTP/1.1"
+    let mut request_line = request.head.lines().next().unwrap_or("").split_whitespace();
+    let (method, path) = (request_line.next(), request_line.next());
+    let proposal_guild = path
+        .and_then(|path| path.strip_prefix("/guild/"))
+        .and_then(|rest| rest.strip_suffix("/proposal"))
+        .and_then(|id| id.parse::<u64>().ok())
+        .filter(|&id| id != 0)
+        .map(GuildId::new);
+
+    let (status, body) = match (method, path, proposal_guild, intake) {
+        (Some("GET" | "HEAD"), Some("/healthz"), _, _) if ready.load(Ordering::Relaxed) => ("200 OK", "ok".to_string()),
+        (Some("GET" | "HEAD"), Some("/healthz"), _, _) => ("503 Service Unavailable", "gateway not connected".to_string()),
+        (Some("POST"), _, Some(guild_id), Some(intake)) => {
+            if !intake.authorized(request.header("authorization")) {
+                ("401 Unauthorized", "invalid API key".to_string())
+            } else {
+                match serde_json::from_slice::<ExternalProposal>(&request.body) {
+                    Ok(proposal) => {
+                        let reply = intake.submit(guild_id, proposal).await;
+                        (reply.status(), reply.body().to_string())
+                    },
+                    Err(e) => ("400 Bad Request", format!("invalid proposal: {}", e)),
+                }
+            }
+        },
+        _ => ("404 Not Found", "not found".to_string()),
+    };
+
+    // HEAD gets the same headers, without the body
+    write_response(&mut stream, status, &body, method == Some("HEAD")).await
+}
+
+/// A request's line and headers, and its body
+struct Request {
+    head: String,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of a header, matched without regard to case.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.head
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    }
+}
+
+/// Reads a request, with as much body as its `Content-Length` announces. Returns
+/// `None` when it's larger than [`MAX_REQUEST_LEN`].
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Request>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let head_len = loop {
+        if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if buf.len() > MAX_REQUEST_LEN {
+            return Ok(None);
+        }
+        let len = stream.read(&mut chunk).await?;
+        if len == 0 {
+            // The client stopped sending; make do with what arrived
+            break buf.len();
+        }
+        buf.extend_from_slice(&chunk[..len]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_len]).into_owned();
+    let mut request = Request { head, body: buf.split_off(head_len) };
+    let content_len = request.header("content-length").and_then(|len| len.parse::<usize>().ok()).unwrap_or(0);
+    if head_len + content_len > MAX_REQUEST_LEN {
+        return Ok(None);
+    }
+    while request.body.len() < content_len {
+        let len = stream.read(&mut chunk).await?;
+        if len == 0 {
+            break;
+        }
+        request.body.extend_from_slice(&chunk[..len]);
+    }
+    request.body.truncate(content_len);
+    Ok(Some(request))
+}
+
+async fn write_response(stream: &mut TcpStream, status: &str, body: &str, head_only: bool) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        if head_only { "" } else { body }
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sends `request` over a loopback connection and reads it back on the server side.
+    async fn read(request: &'static [u8]) -> Option<Request> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+        client.write_all(request).await.unwrap();
+        client.shutdown().await.unwrap();
+        read_request(&mut server).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_are_read_up_to_their_content_length() {
+        let request = read(b"POST /guild/1/proposal HTTP/1.1\r\nAuthorization: Bearer key\r\nContent-Length: 17\r\n\r\n{\"idea\":\"Trees\"}\nextra")
+            .await
+            .expect("a small request");
+        assert!(request.head.starts_with("POST /guild/1/proposal"));
+        assert_eq!(request.header("AUTHORIZATION"), Some("Bearer key"));
+        assert_eq!(request.body, b"{\"idea\":\"Trees\"}\n");
+    }
+
+    #[tokio::test]
+    async fn requests_announcing_too_long_a_body_are_refused() {
+        assert!(read(b"POST /guild/1/proposal HTTP/1.1\r\nContent-Length: 8192\r\n\r\n{}").await.is_none());
+        assert!(read(b"POST /guild/1/proposal HTTP/1.1\r\nContent-Length: 8000\r\n\r\n{}").await.is_some());
+    }
+}
@@ -0,0 +1,104 @@
+//! Elections that have ended, kept so members can look back at them with `/history`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// How many elections each page of `/history` lists
+pub const HISTORY_PAGE_SIZE: usize = 5;
+
+/// Most elections kept per guild; the oldest are forgotten first so the state
+/// file doesn't grow forever
+pub const MAX_ARCHIVED_ELECTIONS: usize = 100;
+
+/// An election that has ended
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedElection {
+    /// `None` when voting was run without `/start`
+    #[serde(default)]
+    pub prompt: Option<String>,
+
+    /// Unix timestamp of when the winners were announced
+    pub ended_at: i64,
+
+    /// Names of the winners, best first
+    #[serde(default)]
+    pub winners: Vec<String>,
+}
+
+/// A guild's past elections, oldest first
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    elections: VecDeque<ArchivedElection>,
+}
+
+impl History {
+    /// Remembers an election that just ended.
+    pub fn record(&mut self, election: ArchivedElection) {
+        self.elections.push_back(election);
+        while self.elections.len() > MAX_ARCHIVED_ELECTIONS {
+            self.elections.pop_front();
+        }
+    }
+
+    /// Number of pages `/history` has, at least one.
+    pub fn page_count(&self) -> usize {
+        self.elections.len().div_ceil(HISTORY_PAGE_SIZE).max(1)
+    }
+
+    /// The elections on a page, newest first. Pages start at 1.
+    pub fn page(&self, page: usize) -> Vec<&ArchivedElection> {
+        self.elections
+            .iter()
+            .rev()
+            .skip(page.saturating_sub(1) * HISTORY_PAGE_SIZE)
+            .take(HISTORY_PAGE_SIZE)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elections.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ended(n: usize) -> ArchivedElection {
+        ArchivedElection { prompt: Some(format!("Election {}", n)), ended_at: n as i64, winners: Vec::new() }
+    }
+
+    fn end_times(page: Vec<&ArchivedElection>) -> Vec<i64> {
+        page.iter().map(|election| election.ended_at).collect()
+    }
+
+    #[test]
+    fn pages_list_the_newest_elections_first() {
+        let mut history = History::default();
+        assert!(history.is_empty());
+        assert_eq!(history.page_count(), 1);
+        assert!(history.page(1).is_empty());
+
+        for n in 1..=7 {
+            history.record(ended(n));
+        }
+        assert_eq!(history.page_count(), 2);
+        assert_eq!(end_times(history.page(1)), vec![7, 6, 5, 4, 3]);
+        assert_eq!(end_times(history.page(2)), vec![2, 1]);
+        assert!(history.page(3).is_empty());
+        // Page 0 is read as the first page
+        assert_eq!(end_times(history.page(0)), vec![7, 6, 5, 4, 3]);
+    }
+
+    #[test]
+    fn the_oldest_elections_are_forgotten_past_the_limit() {
+        let mut history = History::default();
+        for n in 1..=MAX_ARCHIVED_ELECTIONS + 2 {
+            history.record(ended(n));
+        }
+        assert_eq!(history.page_count(), MAX_ARCHIVED_ELECTIONS / HISTORY_PAGE_SIZE);
+        let last = history.page(history.page_count());
+        assert_eq!(last.last().map(|election| election.ended_at), Some(3));
+    }
+}
@@ -0,0 +1,157 @@
+//! Proposals submitted from outside Discord, such as a community's web form, with
+//! `POST /guild/<id>/proposal` on the HTTP server. Requests must carry the API key
+//! as `Authorization: Bearer <key>`, and go through the same checks as `/prop`.
+
+use serde::Deserialize;
+use serenity::all::{ChannelId, GuildId, UserId};
+use tokio::sync::{mpsc, oneshot};
+
+/// Environment variable holding the API key external proposals must present
+pub const PROPOSAL_API_KEY: &str = "PROPOSAL_API_KEY";
+
+/// Environment variable naming who proposals without an author are credited to
+pub const PROPOSAL_AUTHOR_KEY: &str = "PROPOSAL_API_AUTHOR";
+
+/// Who proposals without an author are credited to when none is configured
+pub const DEFAULT_AUTHOR_NAME: &str = "Web form";
+
+/// Proposals waiting for the bot to pick them up before new ones are turned away
+const QUEUE_LEN: usize = 32;
+
+/// The JSON body of a proposal request
+#[derive(Debug, Deserialize)]
+pub struct ExternalProposal {
+    pub idea: String,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Image or web page shown with the candidate, http or https
+    #[serde(default)]
+    pub link: Option<String>,
+
+    /// Member credited with the proposal; without one, it's credited to the web form
+    #[serde(default)]
+    pub author: Option<UserId>,
+
+    /// Channel whose election gets the proposal, for guilds with per-channel elections
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+}
+
+/// A proposal handed to the bot, with where to send the outcome
+pub struct ProposalRequest {
+    pub guild_id: GuildId,
+    pub proposal: ExternalProposal,
+    pub reply: oneshot::Sender<Reply>,
+}
+
+/// How the bot dealt with a proposal
+pub enum Reply {
+    /// Added to the election, or queued for moderation
+    Accepted(String),
+
+    /// Refused by the same checks as `/prop`
+    Rejected(String),
+
+    /// The bot doesn't serve this guild
+    UnknownGuild,
+
+    /// The bot isn't connected to Discord yet
+    NotReady,
+}
+
+impl Reply {
+    /// The HTTP status line for this outcome.
+    pub fn status(&self) -> &'static str {
+        match self {
+            Reply::Accepted(_) => "201 Created",
+            Reply::Rejected(_) => "422 Unprocessable Entity",
+            Reply::UnknownGuild => "404 Not Found",
+            Reply::NotReady => "503 Service Unavailable",
+        }
+    }
+
+    /// The plain text response body.
+    pub fn body(&self) -> &str {
+        match self {
+            Reply::Accepted(message) | Reply::Rejected(message) => message,
+            Reply::UnknownGuild => "unknown guild",
+            Reply::NotReady => "bot not ready",
+        }
+    }
+}
+
+/// Who proposals without an author are credited to, as configured.
+pub fn default_author_name() -> String {
+    std::env::var(PROPOSAL_AUTHOR_KEY)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_AUTHOR_NAME.to_string())
+}
+
+/// The HTTP server's side of the intake: checks the key and passes proposals on
+pub struct Intake {
+    api_key: String,
+    sender: mpsc::Sender<ProposalRequest>,
+}
+
+impl Intake {
+    /// Reads the API key from the environment. Returns `None` when none is configured,
+    /// or the intake with the receiving end the bot takes proposals from.
+    pub fn from_env() -> Option<(Self, mpsc::Receiver<ProposalRequest>)> {
+        let api_key = std::env::var(PROPOSAL_API_KEY).ok().filter(|key| !key.trim().is_empty())?;
+        let (sender, receiver) = mpsc::channel(QUEUE_LEN);
+        Some((Self { api_key, sender }, receiver))
+    }
+
+    /// Whether an `Authorization` header value carries the API key. The comparison
+    /// takes as long for a near miss as for a wild guess.
+    pub fn authorized(&self, authorization: Option<&str>) -> bool {
+        let Some(key) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+            return false;
+        };
+        let (key, expected) = (key.trim().as_bytes(), self.api_key.as_bytes());
+        key.len() == expected.len() && key.iter().zip(expected).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    /// Hands a proposal to the bot and waits for the outcome.
+    pub async fn submit(&self, guild_id: GuildId, proposal: ExternalProposal) -> Reply {
+        let (reply, outcome) = oneshot::channel();
+        if self.sender.send(ProposalRequest { guild_id, proposal, reply }).await.is_err() {
+            return Reply::NotReady;
+        }
+        outcome.await.unwrap_or(Reply::NotReady)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intake() -> Intake {
+        let (sender, _) = mpsc::channel(1);
+        Intake { api_key: "s3cret-key".to_string(), sender }
+    }
+
+    #[test]
+    fn only_the_exact_api_key_is_authorized() {
+        let intake = intake();
+        assert!(intake.authorized(Some("Bearer s3cret-key")));
+        assert!(!intake.authorized(None));
+        assert!(!intake.authorized(Some("")));
+        assert!(!intake.authorized(Some("s3cret-key")));
+        assert!(!intake.authorized(Some("Bearer wrong-key!")));
+        assert!(!intake.authorized(Some("Bearer s3cret")));
+        assert!(!intake.authorized(Some("Bearer s3cret-key-and-more")));
+        assert!(!intake.authorized(Some("Bearer ")));
+    }
+
+    #[tokio::test]
+    async fn proposals_are_refused_once_the_bot_stops_taking_them() {
+        // The receiving end was dropped with the bot
+        let proposal = ExternalProposal { idea: "Bike racks".to_string(), description: None, link: None, author: None, channel: None };
+        assert!(matches!(intake().submit(GuildId::new(1), proposal).await, Reply::NotReady));
+    }
+}
@@ -0,0 +1,71 @@
+//! Running totals of how active each member has been across a guild's elections,
+//! shown with `/leaderboard` when the guild opts in.
+
+use serde::{Deserialize, Serialize};
+use serenity::all::UserId;
+use std::collections::HashMap;
+
+/// How many members each `/leaderboard` ranking shows
+pub const LEADERBOARD_SIZE: usize = 5;
+
+/// A single member's totals
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemberActivity {
+    /// Proposals that made it onto a ballot
+    pub proposals: usize,
+
+    /// Credits spent on votes that were counted when an election ended
+    pub credits_spent: usize,
+}
+
+/// Every member's totals in one guild
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    members: HashMap<UserId, MemberActivity>,
+}
+
+impl Leaderboard {
+    /// Counts the proposals of an election that just ended, given each candidate's author.
+    pub fn record_proposals<'a>(&mut self, authors: impl IntoIterator<Item = &'a UserId>) {
+        for author in authors {
+            self.members.entry(*author).or_default().proposals += 1;
+        }
+    }
+
+    /// Adds the credits each member spent in a round of voting that just closed.
+    pub fn record_spending(&mut self, spending: &HashMap<UserId, usize>) {
+        for (user_id, credits) in spending {
+            self.members.entry(*user_id).or_default().credits_spent += credits;
+        }
+    }
+
+    /// The members with the most proposals, most first.
+    pub fn top_proposers(&self) -> Vec<(UserId, usize)> {
+        self.top(|activity| activity.proposals)
+    }
+
+    /// The members who spent the most credits, most first.
+    pub fn top_voters(&self) -> Vec<(UserId, usize)> {
+        self.top(|activity| activity.credits_spent)
+    }
+
+    /// Forgets every member's totals.
+    pub fn clear(&mut self) {
+        self.members.clear();
+    }
+
+    fn top(&self, score: impl Fn(&MemberActivity) -> usize) -> Vec<(UserId, usize)> {
+        let mut ranked = self
+            .members
+            .iter()
+            .map(|(user_id, activity)| (*user_id, score(activity)))
+            .filter(|(_, score)| *score > 0)
+            .collect::<Vec<(UserId, usize)>>();
+        // Ties are listed by user ID so the order doesn't change between calls
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(LEADERBOARD_SIZE);
+        ranked
+    }
+}
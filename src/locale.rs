@@ -0,0 +1,193 @@
+//! The language of the public announcements, set per guild with `/config locale`.
+//! Replies to commands are shown to a single member and stay in English.
+
+use serde::{Deserialize, Serialize};
+
+/// A language the announcements can be written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+    Es,
+}
+
+impl Locale {
+    /// Every locale, as accepted by `/config locale`
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::Fr, Locale::Es];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+            Locale::Es => "es",
+        }
+    }
+
+    /// The language's own name, for `/config locale` choices and replies
+    pub fn label(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Fr => "Français",
+            Locale::Es => "Español",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|locale| locale.name() == name)
+    }
+
+    pub fn messages(self) -> &'static Messages {
+        match self {
+            Locale::En => &EN,
+            Locale::Fr => &FR,
+            Locale::Es => &ES,
+        }
+    }
+}
+
+/// The text of the announcements in one language. Placeholders such as `{n}` are
+/// replaced with numbers by `fill`.
+pub struct Messages {
+    pub election_started: &'static str,
+    pub suggest_hint: &'static str,
+    pub open_voting_hint: &'static str,
+    pub suggestions_heading: &'static str,
+    pub no_suggestions: &'static str,
+    pub results_heading: &'static str,
+    pub no_candidates: &'static str,
+    pub no_votes: &'static str,
+    pub votes_hidden: &'static str,
+    pub vote_hint: &'static str,
+    pub reaction_vote_hint: &'static str,
+    pub time_remaining: &'static str,
+    pub one_seat: &'static str,
+    pub seats: &'static str,
+    pub vote_costs: &'static str,
+    pub vote_costs_in: &'static str,
+    pub candidates_selected: &'static str,
+    pub runoff: &'static str,
+    pub election_over: &'static str,
+    pub one_participant: &'static str,
+    pub participants: &'static str,
+    pub one_abstention: &'static str,
+    pub abstentions: &'static str,
+    pub winners: &'static str,
+    pub elected: &'static str,
+    pub final_tally: &'static str,
+    pub voting_blocked: &'static str,
+    pub ratify_hint: &'static str,
+    pub ratified: &'static str,
+    pub not_ratified: &'static str,
+    pub winning_proposers: &'static str,
+}
+
+/// Replaces each `{name}` placeholder in a message with its value.
+pub fn fill(message: &str, values: &[(&str, usize)]) -> String {
+    values.iter().fold(message.to_string(), |message, (name, value)| {
+        message.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+const EN: Messages = Messages {
+    election_started: "An election has started:",
+    suggest_hint: "Suggest candidates with `/prop <idea>`",
+    open_voting_hint: "Suggest candidates with `/prop <idea>` and vote on them right away with `/vote <votes> <candidate_number>`",
+    suggestions_heading: "**Suggestions so Far:**",
+    no_suggestions: "No suggestions yet",
+    results_heading: "**Results so Far:**",
+    no_candidates: "No candidates yet",
+    no_votes: "No votes cast yet!",
+    votes_hidden: "{n} votes cast so far. The standings will be revealed when the election ends.",
+    vote_hint: "Vote with `/vote <votes> <candidate_number>`",
+    reaction_vote_hint: "Vote by reacting to this message with a candidate's number (one vote each)",
+    time_remaining: "⏰ Time remaining:",
+    one_seat: "**1 seat** will be filled.",
+    seats: "**{n} seats** will be filled.",
+    vote_costs: "Votes → credits:",
+    vote_costs_in: "Votes → {currency}:",
+    candidates_selected: "Candidates selected:",
+    runoff: "Tie for the last winning place! Runoff between:",
+    election_over: "The election is over!",
+    one_participant: "1 member participated.",
+    participants: "{n} members participated.",
+    one_abstention: "1 of them abstained.",
+    abstentions: "{n} of them abstained.",
+    winners: "Winners:",
+    elected: "Elected ({n} of {seats} seats):",
+    final_tally: "Final tally:",
+    voting_blocked: "Voting hasn't started yet: at least {needed} candidates are needed, and there are currently {have}. Keep suggesting with `/prop`!",
+    ratify_hint: "React with ✅ to ratify this outcome or ❌ to reject it. Ratification closes <t:{closes}:R>.",
+    ratified: "The outcome was ratified: {for} for, {against} against.",
+    not_ratified: "The outcome was not ratified: {for} for, {against} against.",
+    winning_proposers: "Congratulations to the proposers of the winners:",
+};
+
+const FR: Messages = Messages {
+    election_started: "Une élection a commencé :",
+    suggest_hint: "Proposez des candidats avec `/prop <idée>`",
+    open_voting_hint: "Proposez des candidats avec `/prop <idée>` et votez pour eux tout de suite avec `/vote <votes> <numéro_du_candidat>`",
+    suggestions_heading: "**Suggestions jusqu'ici :**",
+    no_suggestions: "Aucune suggestion pour l'instant",
+    results_heading: "**Résultats provisoires :**",
+    no_candidates: "Aucun candidat pour l'instant",
+    no_votes: "Aucun vote pour l'instant !",
+    votes_hidden: "{n} votes exprimés jusqu'ici. Le classement sera révélé à la fin de l'élection.",
+    vote_hint: "Votez avec `/vote <votes> <numéro_du_candidat>`",
+    reaction_vote_hint: "Votez en réagissant à ce message avec le numéro d'un candidat (un vote chacun)",
+    time_remaining: "⏰ Temps restant :",
+    one_seat: "**1 siège** sera pourvu.",
+    seats: "**{n} sièges** seront pourvus.",
+    vote_costs: "Votes → crédits :",
+    vote_costs_in: "Votes → {currency} :",
+    candidates_selected: "Candidats retenus :",
+    runoff: "Égalité pour la dernière place gagnante ! Second tour entre :",
+    election_over: "L'élection est terminée !",
+    one_participant: "1 membre a participé.",
+    participants: "{n} membres ont participé.",
+    one_abstention: "1 d'entre eux s'est abstenu.",
+    abstentions: "{n} d'entre eux se sont abstenus.",
+    winners: "Gagnants :",
+    elected: "Élus ({n} sièges sur {seats}) :",
+    final_tally: "Décompte final :",
+    voting_blocked: "Le vote n'a pas encore commencé : il faut au moins {needed} candidats, et il y en a actuellement {have}. Continuez à proposer avec `/prop` !",
+    ratify_hint: "Réagissez avec ✅ pour ratifier ce résultat ou ❌ pour le rejeter. La ratification se termine <t:{closes}:R>.",
+    ratified: "Le résultat a été ratifié : {for} pour, {against} contre.",
+    not_ratified: "Le résultat n'a pas été ratifié : {for} pour, {against} contre.",
+    winning_proposers: "Félicitations aux auteurs des propositions gagnantes :",
+};
+
+const ES: Messages = Messages {
+    election_started: "Ha comenzado una elección:",
+    suggest_hint: "Propón candidatos con `/prop <idea>`",
+    open_voting_hint: "Propón candidatos con `/prop <idea>` y vota por ellos de inmediato con `/vote <votos> <número_de_candidato>`",
+    suggestions_heading: "**Sugerencias hasta ahora:**",
+    no_suggestions: "Aún no hay sugerencias",
+    results_heading: "**Resultados hasta ahora:**",
+    no_candidates: "Aún no hay candidatos",
+    no_votes: "¡Aún no hay votos!",
+    votes_hidden: "{n} votos emitidos hasta ahora. La clasificación se revelará cuando termine la elección.",
+    vote_hint: "Vota con `/vote <votos> <número_de_candidato>`",
+    reaction_vote_hint: "Vota reaccionando a este mensaje con el número de un candidato (un voto cada uno)",
+    time_remaining: "⏰ Tiempo restante:",
+    one_seat: "Se cubrirá **1 puesto**.",
+    seats: "Se cubrirán **{n} puestos**.",
+    vote_costs: "Votos → créditos:",
+    vote_costs_in: "Votos → {currency}:",
+    candidates_selected: "Candidatos seleccionados:",
+    runoff: "¡Empate por el último puesto ganador! Segunda vuelta entre:",
+    election_over: "¡La elección ha terminado!",
+    one_participant: "Participó 1 miembro.",
+    participants: "Participaron {n} miembros.",
+    one_abstention: "1 de ellos se abstuvo.",
+    abstentions: "{n} de ellos se abstuvieron.",
+    winners: "Ganadores:",
+    elected: "Elegidos ({n} de {seats} puestos):",
+    final_tally: "Recuento final:",
+    voting_blocked: "La votación aún no ha comenzado: se necesitan al menos {needed} candidatos y ahora hay {have}. ¡Sigue proponiendo con `/prop`!",
+    ratify_hint: "Reacciona con ✅ para ratificar este resultado o ❌ para rechazarlo. La ratificación termina <t:{closes}:R>.",
+    ratified: "El resultado fue ratificado: {for} a favor, {against} en contra.",
+    not_ratified: "El resultado no fue ratificado: {for} a favor, {against} en contra.",
+    winning_proposers: "Felicidades a quienes propusieron a los ganadores:",
+};
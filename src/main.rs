@@ -1,29 +1,96 @@
 #[macro_use]
 extern crate const_format;
 
+mod analytics;
+mod audit;
+mod batch;
+mod board;
+mod config;
+mod cooldown;
+mod error;
+mod health;
+mod history;
+mod intake;
+mod leaderboard;
+mod locale;
+mod matrix;
+mod pacer;
+mod ratify;
+mod restart;
+mod retry;
+mod schedule;
+mod scope;
+mod snapshot;
+mod sort;
+mod results;
+mod store;
+mod template;
+mod text;
+mod theme;
+mod tiebreak;
+mod voting;
+mod webhook;
+
+use analytics::Analytics;
+use audit::{AuditAction, AuditEntry, AuditLog};
+use batch::QueuedAnnouncement;
+use board::{Board, BoardLayout};
+use config::GuildConfig;
+use cooldown::CommandKind;
+use error::{CommandError, CommandResult};
+use history::{ArchivedElection, History};
+use intake::{ExternalProposal, Intake, ProposalRequest, Reply};
+use leaderboard::Leaderboard;
+use locale::{Locale, Messages};
+use matrix::{MatrixFormat, VoteMatrix};
+use pacer::ChannelPacer;
+use restart::RestartPolicy;
+use retry::RetryPolicy;
+use results::ResultsFormat;
+use schedule::ScheduledStart;
+use scope::{ElectionKey, ElectionScope};
+use sort::ListSort;
+use snapshot::{CreditSnapshot, SnapshotMode};
+use store::{PersistedGuild, Snapshot, Store};
+use template::ElectionTemplate;
+use theme::{Slot, Theme};
+use text::{capitalize, excerpt, fill_placeholders, placeholders, split_message, truncate_lines, DISCORD_MESSAGE_LIMIT};
+use tiebreak::{Outcome, Ranked, TieBreak};
+use voting::{rank_candidates, voter_counts, CandidateVotes, CarryOver, DepartedPolicy, FreezeWindow, OverspendPolicy, Phase, VotingState};
+use webhook::{ElectionResults, RankedCandidate, ResultsWebhook};
+
 use dotenv::dotenv;
+use serde::{Deserialize, Serialize};
 use serenity::async_trait;
 use serenity::client::{Client, Context, EventHandler};
 use serenity::all::{
-    GatewayIntents, Interaction, Message, GuildId, UserId, Ready,
+    GatewayIntents, Interaction, Guild, GuildId, UserId, RoleId, Message, MessageId, ChannelId, ChannelType, Ready, UnavailableGuild,
+    Reaction, ReactionType, User, Member, Timestamp,
     CreateCommand, CreateCommandOption, CreateInteractionResponse,
-    CreateInteractionResponseMessage, CreateInteractionResponseFollowup, EditMessage,
-    CommandOptionType, CommandInteraction,
+    CreateInteractionResponseMessage, CreateInteractionResponseFollowup, EditInteractionResponse, EditMessage,
+    CommandOptionType, CommandInteraction, CommandDataOption, CommandDataOptionValue, ComponentInteraction, CreateActionRow, CreateButton,
+    ButtonStyle, CreateMessage, CreateAllowedMentions, ConnectionStage, ShardStageUpdateEvent, ResumedEvent, GatewayError,
+    CreateModal, CreateInputText, InputTextStyle, ModalInteraction, ActionRowComponent, CreateEmbed, CreateEmbedFooter, CreateAttachment,
+    CreateAutocompleteResponse,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::{DefaultHasher, Entry}, BTreeMap, HashMap, HashSet};
 use std::env;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 use std::sync::{
-    atomic::{AtomicUsize, Ordering},
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     Arc,
 };
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::AbortHandle;
 
 /// Where the discord api key should be stored in the process or .env environment
 /// variables
 const SECRET_KEY: &str = "DISCORD_SECRET";
 
-/// Role name identifying users with the ability to start and stop elections.
+/// Role name identifying users with the ability to start and stop elections, unless
+/// the guild picks its own with `/config election-roles`.
 const BOT_ROLE: &str = "voting";
 
 /// The channel where the bot should post announcements
@@ -32,1002 +99,8402 @@ const BOT_CHANNEL: &str = "announcements";
 /// The number of winners that should be displayed for convenience purposes
 const CONVENIENT_WINNERS: usize = 5;
 
-/// Everyone starts out with 100 points, and they reset on the below interval:
+/// Most winners named per election in /history
+const HISTORY_WINNERS_SHOWN: usize = 3;
+
+/// Shortest time between two public notices that /stop couldn't open voting
+const BLOCKED_NOTICE_DEBOUNCE: Duration = Duration::from_secs(10 * 60);
+
+/// Shortest time between two /standings posts for one election, so a busy channel isn't flooded
+const STANDINGS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rows of the vote cost reference shown in the announcement before it skips to the limit
+const COST_TABLE_ROWS: usize = 5;
+
+/// Most seats an election can fill with `/start seats:`
+const MAX_SEATS: usize = 25;
+
+/// Everyone starts out with 100 points. They reset when an election ends, and
+/// optionally on the interval set with /config reset-interval
 const STARTING_POINTS: usize = 100;
 
+/// Most credits an election can hand out to each member
+const MAX_STARTING_CREDITS: usize = 10_000;
+
 /// The number of hours that people can suggest ideas for
 const SUGG_INTERVAL: u64 = 48;
 
 /// The number of hours that a vote should last
 const VOTE_INTERVAL: u64 = 24;
 
-/// The bot uses slash commands exclusively
+/// Longest suggestion phase /start and /schedule accept, in hours (30 days), so a
+/// typo can't leave a timer running for years
+const MAX_DURATION_HOURS: u64 = 30 * 24;
+
+/// The longest election prompt accepted by /start and /schedule, so the announcement always fits
+const MAX_PROMPT_LEN: usize = 500;
+
+/// The longest interval accepted by /config reset-interval (one year)
+const MAX_RESET_INTERVAL_HOURS: i64 = 365 * 24;
+
+/// The highest per-candidate vote limit /config max-votes accepts, which spends
+/// the largest possible credit budget on a single candidate
+const MAX_VOTES_CEILING: usize = 100;
+
+/// The most candidates that can be voted on in a single /votemulti or /simulate
+const MAX_BATCH_VOTES: usize = 25;
+
+/// The longest proposal text accepted by /prop and /editprop
+const MAX_PROPOSAL_LEN: usize = 100;
+
+/// The longest description accepted by the /prop form, in characters
+const MAX_DESCRIPTION_LEN: usize = 1000;
+
+/// The longest image or link a proposal can carry
+const MAX_LINK_LEN: usize = 512;
+
+/// Most characters of a description /list shows under its candidate
+const DESCRIPTION_PREVIEW_LEN: usize = 80;
+
+/// Most candidates /nextround carries into a follow-up election
+const MAX_NEXT_ROUND: usize = 25;
+
+/// Most proposals removed with /merge that /restore can bring back, newest kept
+const MAX_REMOVED_PROPOSALS: usize = 5;
+
+/// Custom IDs of the form /prop opens when run without an idea, and of its fields
+const PROPOSAL_FORM: &str = "prop:form";
+const PROPOSAL_FORM_TITLE: &str = "title";
+const PROPOSAL_FORM_DESCRIPTION: &str = "description";
+const PROPOSAL_FORM_LINK: &str = "link";
+
+/// Custom ID prefix for the retract buttons attached to /myvotes
+const RETRACT_BUTTON_PREFIX: &str = "retract:";
+
+/// Custom ID prefix for the page buttons attached to /list, followed by
+/// `<page>:<unix time the list was first shown>`
+const LIST_PAGE_PREFIX: &str = "list:";
+
+/// Most suggestions Discord accepts for an autocompleted option
+const MAX_AUTOCOMPLETE_CHOICES: usize = 25;
+
+/// Longest name Discord accepts for an autocomplete suggestion, in characters
+const MAX_CHOICE_NAME_LEN: usize = 100;
+
+/// Candidates shown per page of /list
+const LIST_PAGE_SIZE: usize = 20;
+
+/// How long the page buttons of a /list keep working, in seconds
+const LIST_PAGE_TTL_SECS: i64 = 15 * 60;
+
+/// Pause between the DMs telling proposers that voting has opened, to stay well
+/// clear of Discord's rate limits
+const PROPOSER_DM_DELAY: Duration = Duration::from_secs(1);
+
+/// Cooldown entries kept before the expired ones are swept out of the rate limiter
+const COOLDOWN_PRUNE_THRESHOLD: usize = 10_000;
+
+/// Least time between two sweeps of the rate limiter, so a map full of live entries
+/// isn't scanned on every command
+const COOLDOWN_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Most commands `/analytics` lists by number of uses
+const MAX_ANALYTICS_COMMANDS: usize = 10;
+
+/// Custom ID prefixes for the buttons on proposals waiting for moderation
+const APPROVE_PROPOSAL_PREFIX: &str = "proposal:approve:";
+const REJECT_PROPOSAL_PREFIX: &str = "proposal:reject:";
+
+/// Custom IDs of the confirmation buttons attached to /reset
+const RESET_CONFIRM_BUTTON: &str = "reset:confirm";
+const RESET_CANCEL_BUTTON: &str = "reset:cancel";
+
+/// Custom IDs of the confirmation buttons attached to a /stop that would end the vote
+const STOP_CONFIRM_BUTTON: &str = "stop:confirm";
+const STOP_CANCEL_BUTTON: &str = "stop:cancel";
+
+/// Discord allows at most 5 buttons per row and 5 rows per message
+const MAX_BUTTONS_PER_ROW: usize = 5;
+const MAX_BUTTON_ROWS: usize = 5;
+
+/// Reactions used to approve candidates in reaction voting, which caps such an
+/// election at 10 candidates
+const NUMBER_EMOJI: [&str; 10] = ["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣", "🔟"];
 
 /// Environment variable name for approved servers list
 const APPROVED_SERVERS_KEY: &str = "APPROVED_SERVERS";
 
-// Make an announcement in the bot channel with comprehensive error handling
-macro_rules! announce {
-    ($context:expr,$guild_id:expr,$content:expr) => {{
-        async {
-            // Quick cache access with timeout protection
-            let channel_id = match tokio::time::timeout(
-                std::time::Duration::from_secs(2),
-                async {
-                    $context.cache.guild($guild_id)
-                        .and_then(|guild| guild.channels.iter().find(|(_, ch)| ch.name == BOT_CHANNEL).map(|(id, _)| *id))
-                }
-            ).await {
-                Ok(Some(id)) => id,
-                Ok(None) => {
-                    eprintln!("Announcement channel '{}' not found in guild {}", BOT_CHANNEL, $guild_id);
-                    return None;
-                },
-                Err(_) => {
-                    eprintln!("Timeout accessing guild cache for announcement in {}", $guild_id);
-                    return None;
-                }
-            };
+/// Environment variable name for the file that persists state across restarts
+const STATE_FILE_KEY: &str = "STATE_FILE";
 
-            // Send message with timeout and retry logic
-            let mut attempts = 0;
-            const MAX_ATTEMPTS: u8 = 2;
-
-            while attempts < MAX_ATTEMPTS {
-                match tokio::time::timeout(
-                    std::time::Duration::from_secs(8),
-                    channel_id.say($context, $content)
-                ).await {
-                    Ok(Ok(message)) => {
-                        println!("Successfully sent announcement to guild {}", $guild_id);
-                        return Some(message);
-                    },
-                    Ok(Err(e)) => {
-                        eprintln!("Discord API error in announce (attempt {}): {}", attempts + 1, e);
-                        if attempts + 1 >= MAX_ATTEMPTS {
-                            return None;
-                        }
-                    },
-                    Err(_) => {
-                        eprintln!("Announce timeout (attempt {}) - Discord API took too long", attempts + 1);
-                        if attempts + 1 >= MAX_ATTEMPTS {
-                            return None;
-                        }
-                    }
-                }
-                attempts += 1;
-                // Brief delay before retry
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-            }
-            None
-        }.await
-    }};
-}
+/// Environment variable name for the file audited actions are appended to
+const AUDIT_FILE_KEY: &str = "AUDIT_LOG_FILE";
+
+/// Environment variable name for the directory holding per-guild credit snapshots
+const CREDIT_SNAPSHOT_DIR_KEY: &str = "CREDIT_SNAPSHOT_DIR";
+
+/// How many times in a row the client is restarted after the gateway fails, before giving up
+const MAX_START_RETRIES: u32 = 8;
+
+/// Wait before the first restart, doubled after each further failure up to the maximum
+const START_RETRY_BASE_DELAY: Duration = Duration::from_secs(5);
+const START_RETRY_MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Address the health endpoint listens on, e.g. 0.0.0.0:8080; unset turns it off
+const HEALTH_ADDR_KEY: &str = "HEALTH_ADDR";
 
 // Enhanced permission checking for admin commands
 macro_rules! check_admin_permission {
-    ($context:expr,$guild_id:expr,$user_id:expr) => {{
-        match $context.cache.guild($guild_id) {
-            Some(guild) => {
-                // Check if user is guild owner (always has permission)
-                if guild.owner_id == $user_id.id {
-                    true
-                } else if let Some(member) = guild.members.get(&$user_id.id) {
-                    // Check for Administrator permission
-                    if let Ok(permissions) = member.permissions($context) {
-                        if permissions.administrator() {
-                            true
-                        } else {
-                            // Check for the specific voting role
-                            guild.role_by_name(BOT_ROLE)
-                                .map(|role| member.roles.contains(&role.id))
-                                .unwrap_or(false)
-                        }
-                    } else {
-                        // Fallback to role check only
-                        guild.role_by_name(BOT_ROLE)
-                            .map(|role| member.roles.contains(&role.id))
-                            .unwrap_or(false)
-                    }
-                } else {
-                    false
-                }
-            },
-            None => false,
-        }
+    ($handler:expr,$context:expr,$guild_id:expr,$user_id:expr) => {{
+        $handler.admin_permissions($context, $guild_id, $user_id.id)
+            .await
+            .map(|permissions| permissions.allowed())
+            .unwrap_or(false)
     }};
 }
 
-/// Possible slash commands for the quadratic voting bot:
-/// /prop <topic>: Adds a topic to the upcoming election
-/// /vote <votes> <candidate_id>: Cast votes for the selected candidate
-/// /points: Get the sender's remaining points in the election
-/// /start <prompt>: Starts a new vote (can only be called by users with admin permissions)
-/// /stop: Stops the segment of the voting process (can only be called by users with admin permissions)
-#[derive(Default)]
-struct Handler {
-    // Suggested topics for the upcoming election
-    upcoming_topics: HashMap<GuildId, Arc<RwLock<Vec<String>>>>,
+/// What grants (or would grant) a member the right to run elections
+struct AdminPermissions {
+    owner: bool,
+    administrator: bool,
 
-    // Users cannot have less than 0 points, but they may have different
-    // balances per-guild
-    points: HashMap<GuildId, Arc<RwLock<HashMap<UserId, AtomicUsize>>>>,
+    // Each role checked, as configured, with whether the member has it, or None when
+    // the guild has no such role. These are the election roles for admin checks, and
+    // the spectator roles when checking who may see private results
+    roles: Vec<(String, Option<bool>)>,
 
-    // The bot automatically updates results of the election as it progresses
-    results: Arc<RwLock<HashMap<GuildId, Message>>>,
+    // Whether the member's roles could be read, from the cache or from Discord
+    member_found: bool,
+}
 
-    // Total votes per idea, and votes cast per idea per user
-    votes: HashMap<
-        GuildId,
-        Arc<RwLock<HashMap<usize, (String, AtomicUsize, HashMap<UserId, AtomicUsize>)>>>,
-    >,
+impl AdminPermissions {
+    fn allowed(&self) -> bool {
+        self.owner || self.administrator || self.roles.iter().any(|(_, has_role)| *has_role == Some(true))
+    }
+}
 
-    // Rate limiting: track last command usage per user per guild
-    last_command_time: Arc<RwLock<HashMap<(GuildId, UserId), Instant>>>,
+/// Works out the member's admin-relevant permissions from the cached guild, using
+/// `fetched` when the member isn't cached. Roles are given by name or ID. Returns
+/// None when the guild is not cached.
+fn evaluate_admin_permissions(context: &Context, guild_id: GuildId, user_id: UserId, roles: &[String], fetched: Option<&Member>) -> Option<AdminPermissions> {
+    let guild = context.cache.guild(guild_id)?;
+    Some(guild_admin_permissions(&guild, user_id, roles, fetched))
 }
 
-#[async_trait]
-impl EventHandler for Handler {
-    async fn ready(&self, ctx: Context, ready: Ready) {
-        println!("Bot logged in as {}", ready.user.name);
+/// Works out the member's admin-relevant permissions in the guild, as for
+/// [`evaluate_admin_permissions`].
+fn guild_admin_permissions(guild: &Guild, user_id: UserId, roles: &[String], fetched: Option<&Member>) -> AdminPermissions {
+    let member = guild.members.get(&user_id).or(fetched);
+
+    AdminPermissions {
+        // The guild owner always has permission
+        owner: guild.owner_id == user_id,
+        administrator: member
+            .map(|member| guild.member_permissions(member).administrator())
+            .unwrap_or(false),
+        // Check for any of the roles
+        roles: roles
+            .iter()
+            .map(|entry| {
+                let role = match entry.parse::<u64>() {
+                    Ok(id) if id != 0 => guild.roles.get(&RoleId::new(id)),
+                    _ => guild.role_by_name(entry),
+                };
+                let has_role = role.map(|role| member.is_some_and(|member| member.roles.contains(&role.id)));
+                (role.map_or_else(|| entry.clone(), |role| role.name.clone()), has_role)
+            })
+            .collect(),
+        member_found: member.is_some(),
+    }
+}
 
-        // Create modern slash commands with proper builders
-        let commands = vec![
-            CreateCommand::new("prop")
-                .description("Propose a candidate for the election")
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::String,
-                        "idea",
-                        "Your proposal"
-                    )
-                    .required(true)
-                ),
-            CreateCommand::new("vote")
-                .description("Cast votes for a candidate")
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::Integer,
-                        "n",
-                        "Votes to cast (1-10)"
-                    )
-                    .required(true)
-                    .min_int_value(1)
-                    .max_int_value(10)
-                )
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::Integer,
-                        "id",
-                        "Candidate ID"
-                    )
-                    .required(true)
-                    .min_int_value(0)
-                ),
-            CreateCommand::new("points")
-                .description("Check your remaining voice credits"),
-            CreateCommand::new("start")
-                .description("Start a new election (requires voting role)")
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::String,
-                        "prompt",
-                        "Election topic/question"
-                    )
-                    .required(true)
-                ),
-            CreateCommand::new("stop")
-                .description("Stop the current election phase (requires voting role)"),
-        ];
+/// Finds the channel the guild's announcements are posted in. Returns None when
+/// the guild isn't cached or has no such channel.
+fn resolve_announcement_channel(context: &Context, guild_id: GuildId) -> Option<ChannelId> {
+    let guild = context.cache.guild(guild_id)?;
+    find_announcement_channel(guild.channels.values().map(|channel| (channel.id, channel.name.as_str())))
+}
 
-        // Register commands globally for all guilds
-        match ctx.http.create_global_commands(&commands).await {
-            Ok(_) => println!("Successfully registered {} global slash commands", commands.len()),
-            Err(why) => println!("Failed to register global commands: {:?}", why),
-        }
-    }
+/// Finds the channel an election is announced in: its own channel, or the guild's
+/// announcement channel for the guild-wide election.
+fn election_channel(context: &Context, key: ElectionKey) -> Option<ChannelId> {
+    key.channel_id.or_else(|| resolve_announcement_channel(context, key.guild_id))
+}
 
-    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            println!("Received slash command: {} from user: {}", command.data.name, command.user.id);
+/// The part of a moderation button's custom ID after its prefix: the ticket, then the
+/// election's channel for channel elections, e.g. `7` or `7:123456789`.
+fn moderation_target(ticket: usize, key: ElectionKey) -> String {
+    match key.channel_id {
+        Some(channel_id) => format!("{}:{}", ticket, channel_id),
+        None => ticket.to_string(),
+    }
+}
 
-            // Handle commands with appropriate response patterns
-            match command.data.name.as_str() {
-                "prop" => {
-                    self.handle_prop_command(&ctx, &command).await;
-                },
-                "vote" => {
-                    self.handle_vote_command(&ctx, &command).await;
-                },
-                "points" => {
-                    self.handle_points_command(&ctx, &command).await;
-                },
-                "start" => {
-                    self.handle_start_command(&ctx, &command).await;
-                },
-                "stop" => {
-                    self.handle_stop_command(&ctx, &command).await;
-                },
-                _ => {
-                    self.send_ephemeral_response(&ctx, &command, "❌ Unknown command. Please try again.").await;
-                },
-            }
-        }
+/// Reads back what `moderation_target` wrote.
+fn parse_moderation_target(target: &str) -> Option<(usize, Option<ChannelId>)> {
+    match target.split_once(':') {
+        Some((ticket, channel)) => {
+            let channel_id = channel.parse::<u64>().ok().filter(|&id| id != 0)?;
+            Some((ticket.parse().ok()?, Some(ChannelId::new(channel_id))))
+        },
+        None => Some((target.parse().ok()?, None)),
     }
 }
 
-impl Handler {
-    /// Creates buckets for all of the pre-specified servers the bot belongs to.
-    fn register_servers(mut self, approved_servers: Vec<GuildId>) -> Self {
-        for g in approved_servers {
-            self.upcoming_topics
-                .insert(g, Arc::new(RwLock::new(Vec::new())));
-            self.points.insert(g, Arc::new(RwLock::new(HashMap::new())));
-            self.votes.insert(g, Arc::new(RwLock::new(HashMap::new())));
-        }
+/// Picks the announcement channel out of a guild's channels, given as (ID, name) pairs.
+fn find_announcement_channel<'a>(channels: impl IntoIterator<Item = (ChannelId, &'a str)>) -> Option<ChannelId> {
+    channels.into_iter().find(|(_, name)| *name == BOT_CHANNEL).map(|(id, _)| id)
+}
 
-        self
-    }
+/// A suggested topic, along with the member who proposed it. The ID is assigned
+/// once at /prop time and never reused within an election, so it stays valid
+/// when other proposals are edited or removed.
+struct Proposal {
+    id: usize,
+    idea: String,
+    author: UserId,
+}
 
-    /// Check if user is rate limited (max 1 command per 2 seconds)
-    async fn check_rate_limit(&self, guild_id: GuildId, user_id: UserId) -> bool {
-        let key = (guild_id, user_id);
-        let now = Instant::now();
-        let cooldown = Duration::from_secs(2);
+/// A proposal removed during the suggestion phase, kept so /restore can bring it
+/// back with its ID and description
+struct RemovedProposal {
+    proposal: Proposal,
+    description: Option<String>,
+    link: Option<String>,
+}
 
-        let mut times = self.last_command_time.write().await;
-        if let Some(last_time) = times.get(&key) {
-            if now.duration_since(*last_time) < cooldown {
-                return true; // Rate limited
-            }
+/// A proposal waiting for a moderator. Its ticket number identifies it in the
+/// moderation buttons; it only gets a candidate ID once approved.
+struct PendingProposal {
+    ticket: usize,
+    idea: String,
+    description: Option<String>,
+    link: Option<String>,
+    author: UserId,
+    author_name: String,
+
+    /// Credits the author paid to propose it, given back if it's rejected
+    cost: usize,
+}
+
+/// What a member submitted with /prop: the idea, shown as the candidate's title,
+/// and the longer description the /prop form also asks for
+struct Submission {
+    idea: String,
+    description: Option<String>,
+    link: Option<String>,
+}
+
+impl Submission {
+    /// Checks the lengths of the idea and description, and that the link is a web address.
+    fn check(&self) -> Result<(), CommandError> {
+        if self.idea.len() > MAX_PROPOSAL_LEN {
+            return Err(CommandError::TooLong { what: "Proposal ideas", max: MAX_PROPOSAL_LEN });
+        }
+        if self.description.as_ref().is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LEN) {
+            return Err(CommandError::TooLong { what: "Proposal descriptions", max: MAX_DESCRIPTION_LEN });
+        }
+        if let Some(link) = &self.link {
+            check_link(link)?;
         }
-        times.insert(key, now);
-        false // Not rate limited
+        Ok(())
     }
+}
 
+/// How a proposal went. The outcome may be posted in the channel, but what the
+/// proposal cost shows the proposer's balance, so only they get to see it.
+#[derive(Debug, PartialEq)]
+struct ProposalReply {
+    outcome: String,
+    cost: Option<String>,
+}
 
+/// The election currently running in a guild
+#[derive(Default)]
+struct ElectionInfo {
+    prompt: String,
 
+    // Unix timestamp (in seconds) of the /start
+    started_at: i64,
 
+    // Credits granted to each member for this election only, set by /start
+    credits: Option<usize>,
 
+    // Members approve candidates by reacting instead of using /vote
+    reaction_voting: bool,
 
-    /// Get a list of the candidates that are winning so far, sorted by their
-    /// number of votes.
-    async fn winners(&self, g: &GuildId) -> Vec<String> {
-        // Sort the candidates, and take the first CONVENIENT_WINNERS ones
-        let mut candidates = self
-            .votes
-            .get(g)
-            .unwrap()
-            .read()
-            .await
-            .values()
-            .map(|(c, votes, _)| (c.clone(), votes.load(Ordering::Relaxed)))
-            .collect::<Vec<(String, usize)>>();
-        candidates.sort_by(|b, a| a.1.partial_cmp(&b.1).unwrap());
-        candidates
-            .iter()
-            .map(|w| format!("{}: {}", w.0, w.1))
-            .take(CONVENIENT_WINNERS)
-            .collect::<Vec<String>>()
-    }
+    // Proposers are hidden from everyone but admins
+    anonymous_proposals: bool,
 
-    /// Updates the most recent announcement in the given guild with the latest suggestions.
-    async fn poll_suggestions(&self, context: &Context, g: &GuildId) {
-        let suggestions = self
-            .upcoming_topics
-            .get(g)
-            .unwrap()
-            .read()
-            .await
-            .iter()
-            .map(|s| format!("• {}", s))
-            .collect::<Vec<String>>();
+    // Proposals become candidates right away, with no separate suggestion phase
+    open_voting: bool,
 
-        let cts = self
-            .results
-            .read()
-            .await
-            .get(g)
-            .unwrap()
-            .content
-            .split_inclusive("**Suggestions so Far:**")
-            .map(|s| s.to_owned())
-            .next()
-            .unwrap_or_default();
+    // Candidates elected when it ends, if set by /start
+    seats: Option<usize>,
 
-        self.results
-            .write()
-            .await
-            .get_mut(g)
-            .unwrap()
-            .edit(context, EditMessage::new().content(format!("{}\n{}", cts, suggestions.join("\n"))))
-            .await
-            .expect("discord API error");
-    }
+    // Everyone who proposed or voted in this election, counted once each
+    participants: HashSet<UserId>,
 
-    /// Safe version of poll_suggestions with proper error handling
-    async fn poll_suggestions_safe(&self, context: &Context, g: &GuildId) -> Result<(), String> {
-        let Some(topics_lock) = self.upcoming_topics.get(g) else {
-            return Err("Guild not found in topics".to_string());
-        };
+    // Participants who chose to abstain with /abstain; they count toward turnout but hold no votes
+    abstentions: HashSet<UserId>,
+}
 
-        // Quick check if there's an active election to update
-        {
-            let results_read = self.results.read().await;
-            if results_read.get(g).is_none() {
-                return Err("No active election to update".to_string());
-            }
-        }
+/// Settings chosen for a single election with the `/start` options, also saved in templates
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ElectionOptions {
+    // Length of the suggestion phase, when not SUGG_INTERVAL
+    duration_hours: Option<u64>,
 
-        let suggestions = topics_lock
-            .read()
-            .await
-            .iter()
-            .enumerate()
-            .map(|(i, s)| format!("#{}: {}", i + 1, s))
-            .collect::<Vec<String>>();
+    // Credits for each member, overriding the guild default
+    credits: Option<usize>,
 
-        let base_content = {
-            let results_read = self.results.read().await;
-            let Some(result_msg) = results_read.get(g) else {
-                return Err("No active announcement message".to_string());
-            };
-            let content_parts: Vec<&str> = result_msg.content.split("**Suggestions so Far:**").collect();
-            content_parts.get(0).unwrap_or(&"").to_string()
-        };
+    // Number of candidates elected when it ends; None just reports the top CONVENIENT_WINNERS
+    seats: Option<usize>,
 
-        let mut results_write = self.results.write().await;
-        if let Some(message) = results_write.get_mut(g) {
-            let new_content = if suggestions.is_empty() {
-                format!("{}**Suggestions so Far:**\nNo suggestions yet", &base_content)
-            } else {
-                format!("{}**Suggestions so Far:**\n{}", &base_content, suggestions.join("\n"))
-            };
+    reaction_voting: bool,
+    anonymous_proposals: bool,
+    open_voting: bool,
+}
 
-            // Edit message with timeout protection
-            let edit_result = tokio::time::timeout(
-                std::time::Duration::from_secs(5),
-                message.edit(context, EditMessage::new().content(new_content))
-            ).await;
+/// Candidates an admin listed with `/start candidates:`, entered before anyone can /prop
+struct SeededCandidates {
+    ideas: Vec<String>,
 
-            match edit_result {
-                Ok(Ok(_)) => {}, // Success
-                Ok(Err(e)) => return Err(format!("Failed to edit message: {}", e)),
-                Err(_) => return Err("Timeout editing message".to_string()),
-            }
-        }
+    // Whether voting opens on them right away, skipping the suggestion phase
+    vote_now: bool,
 
-        Ok(())
-    }
+    // The admin who listed them, recorded as their proposer
+    author: UserId,
+}
 
-    /// Updates the most recent poll announcement in the given guild with the latest polling
-    /// numbers.
-    async fn poll_votes(&self, context: Context, g: &GuildId) {
-        let winners = self.winners(g).await.join("\n");
+/// How the last election ended, kept so /nextround can start a follow-up round
+struct FinishedElection {
+    // `None` when voting was run without /start
+    prompt: Option<String>,
 
-        if !self.results.read().await.contains_key(g) {
-            return;
-        }
+    // Candidate names, winners first, then the rest of the final tally
+    ranking: Vec<String>,
+}
 
-        // Edit the results section in the new poll message to have the winning candidates
-        let cts = self
-            .results
-            .read()
-            .await
-            .get(g)
-            .unwrap()
-            .content
-            .split_inclusive("**Results so Far:**")
-            .map(|s| s.to_owned())
-            .next()
-            .unwrap_or_default();
+/// Election state for a single guild. Buckets are created for approved servers at
+/// startup and whenever the bot joins a new guild. Guilds running one election per
+/// channel get another bucket for each channel, sharing the guild's settings; only
+/// the guild's own bucket keeps the schedule, leaderboard, templates and history.
+#[derive(Default)]
+struct GuildState {
+    // The running election's prompt, set by /start
+    election: RwLock<Option<ElectionInfo>>,
 
-        // Acquire mutable access to the stored message, build the new content,
-        // and edit it with timeout + proper error handling.
-        let mut results_write = self.results.write().await;
-        if let Some(message) = results_write.get_mut(g) {
-            let new_content = format!("{}\n{}", cts, winners);
+    // Votes are only accepted while this is Phase::Voting
+    phase: RwLock<Phase>,
 
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(8),
-                message.edit(&context, EditMessage::new().content(new_content))
-            ).await {
-                Ok(Ok(_)) => {
-                    println!("Successfully updated vote results for guild {}", g);
-                },
-                Ok(Err(e)) => {
-                    eprintln!("Discord API error updating vote results: {}", e);
-                },
-                Err(_) => {
-                    eprintln!("Timeout updating vote results for guild {}", g);
-                }
-            }
-        }
-    }
+    // Credits each member received from others with /delegate since credits last reset
+    delegated: RwLock<HashMap<UserId, usize>>,
 
-    /// Checks whether the vote is currently in the suggestion period.
-    async fn in_suggestion_period(&self, guild_id: &GuildId) -> bool {
-        !self
-            .upcoming_topics
-            .get(guild_id)
-            .unwrap()
-            .read()
-            .await
-            .is_empty()
-    }
+    // Unused credits each member kept through the last carry-over refill
+    carried: RwLock<HashMap<UserId, usize>>,
 
-    /// Checks whether the vote is currently in the voting period.
-    /// If no votes are cast, it is not in the voting period.
-    async fn in_vote_period(&self, guild_id: &GuildId) -> bool {
-        !self.votes.get(guild_id).unwrap().read().await.is_empty()
-    }
+    // Suggested topics for the upcoming election
+    upcoming_topics: RwLock<Vec<Proposal>>,
 
-    // ===== INTERACTION RESPONSE HELPERS =====
+    // Last candidate ID handed out; IDs count up from 1 for each election
+    last_candidate_id: AtomicUsize,
 
-    /// Send an immediate response to the user
-    async fn send_response(&self, ctx: &Context, command: &CommandInteraction, content: &str) {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new().content(content)
-        );
+    // Users cannot have less than 0 points, but they may have different
+    // balances per-guild
+    points: RwLock<HashMap<UserId, AtomicUsize>>,
 
-        if let Err(why) = command.create_response(&ctx.http, response).await {
-            eprintln!("Failed to respond to command '{}': {}", command.data.name, why);
-        }
-    }
+    // Total votes per idea, and votes cast per idea per user, keyed by candidate ID
+    votes: RwLock<HashMap<usize, CandidateVotes>>,
 
-    /// Send an ephemeral (private) response to the user
-    async fn send_ephemeral_response(&self, ctx: &Context, command: &CommandInteraction, content: &str) {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content(content)
-                .ephemeral(true)
-        );
+    // Settings changed by admins with /config, shared with the guild's channel elections
+    config: Arc<RwLock<GuildConfig>>,
 
-        if let Err(why) = command.create_response(&ctx.http, response).await {
-            eprintln!("Failed to send ephemeral response: {}", why);
-        }
+    // Election queued with /schedule, and the timer task that will start it
+    scheduled_start: RwLock<Option<ScheduledStart>>,
+    schedule_timer: RwLock<Option<AbortHandle>>,
+
+    // Task resetting everyone's credits on the configured interval
+    reset_timer: RwLock<Option<AbortHandle>>,
+
+    // When the current phase is due to end (unix seconds), and the task counting down to it
+    phase_deadline: RwLock<Option<i64>>,
+    countdown_timer: RwLock<Option<AbortHandle>>,
+
+    // Task that pings @everyone about a new election once the configured delay passes
+    ping_timer: RwLock<Option<AbortHandle>>,
+
+    // New-proposal announcements held for the batch window, and the task that posts them
+    announcement_batch: RwLock<Vec<QueuedAnnouncement>>,
+    batch_timer: RwLock<Option<AbortHandle>>,
+
+    // In reaction voting, the message carrying the number reactions and the
+    // candidate ID behind each of them, in NUMBER_EMOJI order
+    reaction_board: RwLock<Option<(MessageId, Vec<usize>)>>,
+
+    // During a tie-break runoff, the winners already decided by the first round
+    runoff_decided: RwLock<Option<Vec<Ranked>>>,
+
+    // Who proposed each candidate, kept through the voting phase for moderation
+    candidate_authors: RwLock<HashMap<usize, UserId>>,
+
+    // Descriptions submitted with the /prop form, by candidate ID, for /details
+    descriptions: RwLock<HashMap<usize, String>>,
+
+    // Images or links proposals were made with, by candidate ID, for /details
+    links: RwLock<HashMap<usize, String>>,
+
+    // Proposals removed during the suggestion phase, oldest first, until the phase ends
+    removed_proposals: RwLock<Vec<RemovedProposal>>,
+
+    // Proposals waiting for a moderator's approval, and the last ticket number handed out
+    pending_proposals: RwLock<Vec<PendingProposal>>,
+    last_ticket: AtomicUsize,
+
+    // Proposals and credits spent per member across elections, kept while the guild opts in
+    leaderboard: RwLock<Leaderboard>,
+
+    // Elections saved with /template save, by name
+    templates: RwLock<BTreeMap<String, ElectionTemplate>>,
+
+    // Elections that have ended, for /history
+    history: RwLock<History>,
+
+    // Aggregate usage counters, for /analytics
+    analytics: RwLock<Analytics>,
+
+    // Set while an election is being started, so a concurrent /start is refused
+    starting: AtomicBool,
+
+    // When the last notice that /stop couldn't open voting was posted, to debounce repeats
+    last_blocked_notice: RwLock<Option<Instant>>,
+
+    // When /standings last reposted the results, to space out reposts
+    last_standings: RwLock<Option<Instant>>,
+
+    // Members' starting credits for the running election, if it uses a snapshot
+    credit_snapshot: RwLock<Option<CreditSnapshot>>,
+
+    // The election that ended last, until another one starts
+    last_outcome: RwLock<Option<FinishedElection>>,
+}
+
+/// Clears a guild's `starting` flag once its start finishes, including when the
+/// start is dropped after timing out.
+struct StartingGuard<'a>(&'a AtomicBool);
+
+impl<'a> StartingGuard<'a> {
+    /// Sets the flag, unless another start already holds it.
+    fn acquire(starting: &'a AtomicBool) -> Option<Self> {
+        // Built lazily: a refused guard would clear the other start's flag when dropped
+        (!starting.swap(true, Ordering::AcqRel)).then(|| StartingGuard(starting))
     }
+}
 
-    /// Defer the response for long-running operations
-    async fn defer_response(&self, ctx: &Context, command: &CommandInteraction, ephemeral: bool) -> bool {
-        let response = if ephemeral {
-            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true))
-        } else {
-            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new())
+impl Drop for StartingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl GuildState {
+    /// A bucket for one of the guild's channel elections.
+    fn for_channel(guild: &GuildState) -> Self {
+        Self { config: guild.config.clone(), ..Self::default() }
+    }
+
+    /// Applies a change to the tally, credit balances and phase under their write
+    /// locks, taken in the usual order, so the change is atomic with any checks it makes.
+    async fn update_votes<T>(&self, change: impl FnOnce(&mut VotingState) -> T) -> T {
+        let starting_credits = starting_credits(self).await;
+        let (max_votes, delegation_cap, max_concentration, max_candidates, overspend) = {
+            let config = self.config.read().await;
+            (config.max_votes, config.delegation_cap, config.max_concentration, config.max_candidates_per_voter, config.overspend_policy)
         };
+        let freeze = vote_freeze(self).await;
+        let snapshot = self.credit_snapshot.read().await;
+
+        let mut votes_map = self.votes.write().await;
+        let mut points_map = self.points.write().await;
+        let mut phase = self.phase.write().await;
+        let mut delegated = self.delegated.write().await;
+        let mut carried = self.carried.write().await;
+        change(&mut VotingState {
+            phase: &mut phase,
+            candidates: &mut votes_map,
+            points: &mut points_map,
+            delegated: &mut delegated,
+            carried: &mut carried,
+            starting_credits,
+            snapshot: snapshot.as_ref(),
+            max_votes,
+            freeze,
+            delegation_cap,
+            max_concentration,
+            max_candidates,
+            overspend,
+        })
+    }
 
-        match command.create_response(&ctx.http, response).await {
-            Ok(()) => true,
-            Err(why) => {
-                eprintln!("Failed to defer response: {}", why);
-                false
+    /// Takes the final tally and closes the vote in a single `update_votes`, so no vote
+    /// can land between the results being computed and the ballot being cleared. A tie
+    /// sent to a runoff keeps just the tied candidates on the ballot instead.
+    async fn finalize_votes(&self, places: usize, tie_break: TieBreak, seed: u64) -> (Vec<Ranked>, HashMap<UserId, usize>, Outcome) {
+        self.update_votes(|voting| {
+            let standings = voting.standings();
+            let spending = voting.spending();
+            let outcome = tiebreak::select_winners(&standings, places, tie_break, seed);
+            match &outcome {
+                Outcome::Runoff { tied, .. } => voting.start_runoff(tied),
+                Outcome::Decided(_) => voting.close(),
             }
+            (standings, spending, outcome)
+        })
+        .await
+    }
+
+    /// Records that a notice that /stop couldn't open voting is being posted. Returns
+    /// false if one went out within the debounce period, so this one should be skipped.
+    async fn claim_blocked_notice(&self) -> bool {
+        let mut last_notice = self.last_blocked_notice.write().await;
+        if last_notice.is_some_and(|posted| posted.elapsed() < BLOCKED_NOTICE_DEBOUNCE) {
+            return false;
         }
+        *last_notice = Some(Instant::now());
+        true
     }
 
-    /// Send a follow-up message after deferring with timeout protection
-    async fn send_followup(&self, ctx: &Context, command: &CommandInteraction, content: &str) {
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(10),
-            command.create_followup(ctx, CreateInteractionResponseFollowup::new().content(content))
-        ).await {
-            Ok(Ok(_)) => {
-                println!("Successfully sent follow-up response to user: {}", command.user.id);
-            },
-            Ok(Err(why)) => {
-                eprintln!("Discord API error in follow-up: {}", why);
-            },
-            Err(_) => {
-                eprintln!("Follow-up response timeout - Discord API took too long");
+    /// The prompt and candidates of a follow-up round between the top `count`
+    /// candidates of the election that ended last.
+    async fn next_round(&self, count: usize) -> Result<(String, Vec<String>), CommandError> {
+        if *self.phase.read().await != Phase::Idle {
+            return Err(CommandError::ElectionRunning);
+        }
+
+        let outcome = self.last_outcome.read().await;
+        let Some(outcome) = outcome.as_ref() else {
+            return Err(CommandError::NoFinishedElection);
+        };
+        if outcome.ranking.len() < count {
+            return Err(CommandError::InvalidInput(format!(
+                "The last election only had {} candidates, so its top {} can't go through!",
+                outcome.ranking.len(), count
+            )));
+        }
+        Ok((next_round_prompt(outcome.prompt.as_deref()), outcome.ranking[..count].to_vec()))
+    }
+
+    /// Adds the candidates listed with `/start candidates:` to the proposals. Proposals
+    /// left over from before /start keep their place ahead of the list.
+    async fn seed_proposals(&self, seeds: SeededCandidates) {
+        let mut topics = self.upcoming_topics.write().await;
+        for idea in seeds.ideas {
+            if !topics.iter().any(|p| p.idea == idea) {
+                let id = self.last_candidate_id.fetch_add(1, Ordering::Relaxed) + 1;
+                topics.push(Proposal { id, idea, author: seeds.author });
             }
         }
     }
 
-    /// Send a follow-up message with guaranteed delivery (fallback to error message)
-    async fn send_followup_guaranteed(&self, ctx: &Context, command: &CommandInteraction, content: &str) {
-        let fallback_msg = "⚠️ Operation completed but response delivery failed. Please check the announcements channel.";
+    /// Keeps a removed proposal for /restore, forgetting the oldest beyond the last few.
+    async fn remember_removed(&self, removed: RemovedProposal) {
+        let mut recent = self.removed_proposals.write().await;
+        recent.push(removed);
+        if recent.len() > MAX_REMOVED_PROPOSALS {
+            recent.remove(0);
+        }
+    }
 
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(8),
-            command.create_followup(ctx, CreateInteractionResponseFollowup::new().content(content))
-        ).await {
-            Ok(Ok(_)) => {
-                println!("Successfully sent follow-up response to user: {}", command.user.id);
-                return;
-            },
-            Ok(Err(why)) => {
-                eprintln!("Discord API error in follow-up, trying fallback: {}", why);
+    /// Puts the most recently removed proposal back, with its ID, description and link.
+    /// Returns its ID and text.
+    async fn restore_removed(&self) -> Result<(usize, String), CommandError> {
+        let mut topics = self.upcoming_topics.write().await;
+        let mut removed = self.removed_proposals.write().await;
+        let Some(last) = removed.last() else {
+            return Err(CommandError::NothingToRestore);
+        };
+        // Someone may have proposed the same idea again in the meantime
+        if topics.iter().any(|p| p.idea == last.proposal.idea) {
+            return Err(CommandError::DuplicateProposal(last.proposal.idea.clone()));
+        }
+        let Some(RemovedProposal { proposal, description, link }) = removed.pop() else {
+            return Err(CommandError::NothingToRestore);
+        };
+
+        // Back in its old place, since IDs only ever count up
+        let restored = (proposal.id, proposal.idea.clone());
+        let index = topics.partition_point(|p| p.id < proposal.id);
+        topics.insert(index, proposal);
+        if let Some(description) = description {
+            self.descriptions.write().await.insert(restored.0, description);
+        }
+        if let Some(link) = link {
+            self.links.write().await.insert(restored.0, link);
+        }
+        Ok(restored)
+    }
+
+    /// Turns the proposals into the ballot, remembering who proposed each candidate.
+    /// Returns the candidates and whether the election votes with reactions. Refuses
+    /// to open a degenerate vote, leaving the suggestion phase running.
+    async fn start_voting_phase(&self) -> Result<(Vec<(usize, String)>, bool), CommandError> {
+        let all_candidates: Vec<(usize, String)> = self.upcoming_topics.read().await.iter().map(|p| (p.id, p.idea.clone())).collect();
+        let authors: HashMap<usize, UserId> = self.upcoming_topics.read().await.iter().map(|p| (p.id, p.author)).collect();
+
+        let min_candidates = self.config.read().await.min_candidates;
+        if all_candidates.len() < min_candidates {
+            return Err(CommandError::NotEnoughCandidates { needed: min_candidates, have: all_candidates.len() });
+        }
+
+        // Proposals left over from before /start can exceed the reaction limit,
+        // in which case the election falls back to /vote
+        let reaction_voting = match self.election.write().await.as_mut() {
+            Some(election) if election.reaction_voting => {
+                election.reaction_voting = all_candidates.len() <= NUMBER_EMOJI.len();
+                election.reaction_voting
             },
-            Err(_) => {
-                eprintln!("Follow-up response timeout, trying fallback message");
+            _ => false,
+        };
+
+        self.update_votes(|voting| voting.open(all_candidates.iter().cloned())).await;
+
+        // Descriptions and links are kept by candidate ID, so they carry over as is
+        self.upcoming_topics.write().await.clear();
+        self.removed_proposals.write().await.clear();
+        *self.candidate_authors.write().await = authors;
+        Ok((all_candidates, reaction_voting))
+    }
+
+    /// Forgets the election's votes, credits, candidates and proposals without
+    /// tallying anything, as /cancel does.
+    async fn discard(&self) {
+        self.update_votes(|voting| voting.reset()).await;
+        self.upcoming_topics.write().await.clear();
+        *self.election.write().await = None;
+        *self.reaction_board.write().await = None;
+        *self.runoff_decided.write().await = None;
+        self.candidate_authors.write().await.clear();
+        self.descriptions.write().await.clear();
+        self.links.write().await.clear();
+        self.removed_proposals.write().await.clear();
+        self.pending_proposals.write().await.clear();
+        self.last_candidate_id.store(0, Ordering::Relaxed);
+    }
+
+    /// Holds a new-proposal announcement for the batch window. Returns true for the
+    /// first one of a batch, whose caller starts the timer that posts it.
+    async fn queue_announcement(&self, proposal: QueuedAnnouncement) -> bool {
+        let mut batch = self.announcement_batch.write().await;
+        batch.push(proposal);
+        batch.len() == 1
+    }
+
+    /// Takes the announcements held for the batch window, so the next proposal starts
+    /// a new batch.
+    async fn take_announcement_batch(&self) -> Vec<QueuedAnnouncement> {
+        self.batch_timer.write().await.take();
+        std::mem::take(&mut *self.announcement_batch.write().await)
+    }
+
+    /// The guild's announcement emoji.
+    async fn theme(&self) -> Theme {
+        self.config.read().await.theme.clone()
+    }
+
+    /// What the guild calls its credits, for replies to members.
+    async fn currency(&self) -> String {
+        self.config.read().await.currency().to_string()
+    }
+
+    /// The guild's announcement text, in its configured locale.
+    async fn messages(&self) -> &'static Messages {
+        self.config.read().await.locale.messages()
+    }
+
+    /// Sets the template for the winners announcement from `/config winners-message`,
+    /// or goes back to the usual wording for `default`.
+    async fn set_winners_message(&self, input: &str) -> CommandResult {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(CommandError::InvalidInput("Please provide a template, or `default` to restore the usual announcement!".to_string()));
+        }
+        if input.eq_ignore_ascii_case("default") {
+            self.config.write().await.winners_message = None;
+            return Ok(format!("{} The winners announcement is back to its usual wording.", theme::SUCCESS));
+        }
+
+        // Typed as \n, since slash command options are a single line
+        let template = input.replace("\\n", "\n");
+        if template.chars().count() > config::MAX_WINNERS_MESSAGE_LEN {
+            return Err(CommandError::TooLong { what: "The winners message", max: config::MAX_WINNERS_MESSAGE_LEN });
+        }
+        if let Some(unknown) = placeholders(&template).into_iter().find(|name| !config::WINNERS_PLACEHOLDERS.contains(name)) {
+            return Err(CommandError::InvalidInput(format!(
+                "`{{{}}}` isn't a placeholder the winners message can use. Use {}.",
+                unknown,
+                config::WINNERS_PLACEHOLDERS.iter().map(|name| format!("`{{{}}}`", name)).collect::<Vec<String>>().join(", ")
+            )));
+        }
+        if !placeholders(&template).contains(&"winners") {
+            return Err(CommandError::InvalidInput("The winners message needs `{winners}`, or nobody will know who won!".to_string()));
+        }
+
+        self.config.write().await.winners_message = Some(template);
+        Ok(format!("{} Winners will now be announced with your message. `/config winners-message default` restores the usual one.", theme::SUCCESS))
+    }
+
+    /// Counts a member as having taken part in the running election.
+    async fn record_participant(&self, user_id: UserId) {
+        if let Some(election) = self.election.write().await.as_mut() {
+            election.participants.insert(user_id);
+        }
+    }
+
+    /// Records a member who voted, which takes back any earlier abstention.
+    async fn record_voter(&self, user_id: UserId) {
+        if let Some(election) = self.election.write().await.as_mut() {
+            election.participants.insert(user_id);
+            election.abstentions.remove(&user_id);
+        }
+    }
+
+    /// Records a member who abstained, counting them toward turnout without any votes.
+    /// Returns false when they had already abstained.
+    async fn abstain(&self, user_id: UserId) -> Result<bool, CommandError> {
+        self.phase.read().await.check_vote()?;
+
+        // Abstaining means backing no one, so votes already cast have to be retracted first
+        let has_votes = self.votes.read().await.values().any(|(_, _, voters)| {
+            voters.get(&user_id).is_some_and(|votes| votes.load(Ordering::Relaxed) > 0)
+        });
+        if has_votes {
+            return Err(CommandError::InvalidInput(
+                "You've already voted in this election! Retract your votes with `/unvote` or `/myvotes` to abstain instead.".to_string(),
+            ));
+        }
+
+        let mut election = self.election.write().await;
+        let Some(election) = election.as_mut() else {
+            return Err(CommandError::NoElection);
+        };
+        if !election.abstentions.insert(user_id) {
+            return Ok(false);
+        }
+        election.participants.insert(user_id);
+        Ok(true)
+    }
+
+    /// How many members have taken part in the running election.
+    async fn participant_count(&self) -> usize {
+        self.election.read().await.as_ref().map_or(0, |e| e.participants.len())
+    }
+
+    /// How many of those participants abstained.
+    async fn abstention_count(&self) -> usize {
+        self.election.read().await.as_ref().map_or(0, |e| e.abstentions.len())
+    }
+}
+
+/// Who ran which kind of command where, for rate limiting
+type CooldownKey = (GuildId, UserId, CommandKind);
+
+/// When each member last ran each kind of command, for rate limiting
+#[derive(Default)]
+struct CommandTimes {
+    last_used: HashMap<CooldownKey, Instant>,
+
+    // When expired entries were last swept out
+    last_swept: Option<Instant>,
+}
+
+impl CommandTimes {
+    /// Records a command run at `now`, unless its cooldown since the last one is still
+    /// running. Every member who ever ran a command would otherwise stay in the map, so
+    /// once it's large, expired entries are swept out every COOLDOWN_SWEEP_INTERVAL.
+    fn check(&mut self, key: CooldownKey, cooldown: Duration, now: Instant) -> Result<(), CommandError> {
+        if let Some(last_time) = self.last_used.get(&key) {
+            let elapsed = now.duration_since(*last_time);
+            if elapsed < cooldown {
+                // Round up, so the wait is never shown as 0 seconds
+                let wait_secs = (cooldown - elapsed).as_millis().div_ceil(1000) as u64;
+                return Err(CommandError::RateLimited { wait_secs });
             }
         }
 
-        // Fallback attempt with error message
-        if let Err(why) = tokio::time::timeout(
-            std::time::Duration::from_secs(3),
-            command.create_followup(ctx, CreateInteractionResponseFollowup::new().content(fallback_msg))
-        ).await {
-            eprintln!("Critical: Failed to send any follow-up response: {:?}", why);
+        let sweep_due = self.last_swept.is_none_or(|swept| now.duration_since(swept) >= COOLDOWN_SWEEP_INTERVAL);
+        if self.last_used.len() >= COOLDOWN_PRUNE_THRESHOLD && sweep_due {
+            // No guild's cooldown is longer than the maximum, so older entries can't block anyone
+            let longest = Duration::from_secs(cooldown::MAX_COOLDOWN_SECS);
+            self.last_used.retain(|_, last_time| now.duration_since(*last_time) < longest);
+            self.last_swept = Some(now);
+        }
+        self.last_used.insert(key, now);
+        Ok(())
+    }
+}
+
+/// The bot uses slash commands exclusively. Possible slash commands for the quadratic voting bot:
+/// /prop <topic>: Adds a topic to the upcoming election
+/// /editprop <id> <text>: Edits a proposal's text (by its author or an admin) before voting starts
+/// /merge <keep> <drop>: Folds a duplicate proposal into another before voting starts (can only be called by users with admin permissions)
+/// /restore: Brings back the proposal /merge removed last, during the suggestion phase (can only be called by users with admin permissions)
+/// /lock-proposals: Closes the election to new proposals before voting opens, for a review window (can only be called by users with admin permissions)
+/// /vote <votes> <candidate_id>: Cast votes for the selected candidate
+/// /whoami: Shows which election permissions the sender has
+/// /export-matrix [format]: Exports the pseudonymous vote matrix for research, once the guild opted in (can only be called by users with admin permissions)
+/// /debug: Dumps the guild's election state for troubleshooting (can only be called by the bot's owner)
+/// /analytics: Shows the guild's aggregate usage counters (can only be called by users with admin permissions)
+/// /list [sort]: Show the current candidates and their IDs, by number, name or votes
+/// /details <candidate_id>: Show the description a candidate was proposed with
+/// /leaderboard: Show the members who proposed and voted the most, if the guild opted in
+/// /standings: Reposts the current results in the channel, for when the live results scrolled away
+/// /results private: Shows the full standings privately, even while live results are hidden (can only be called by spectators and server admins)
+/// /status: Show the running election's phase, deadline and number of participants
+/// /history [page]: List past elections and their winners, newest first
+/// /votemulti <id:votes ...>: Cast votes for several candidates at once
+/// /vote-all: Spread the remaining credits evenly over every candidate
+/// /simulate <votes ...>: Works out what a set of vote counts would cost, without voting
+/// /abstain: Count the sender toward turnout without voting for any candidate
+/// /unvote <candidate_id>: Retract the sender's votes for the selected candidate
+/// /myvotes: List the sender's own votes with buttons to retract them
+/// /points: Get the sender's remaining points in the election
+/// /delegate <user> <credits>: Give some of the sender's credits to another member
+/// /start <prompt>: Starts a new vote (can only be called by users with admin permissions)
+/// /stop: Stops the segment of the voting process (can only be called by users with admin permissions)
+/// /cancel: Abandons the running election without announcing winners (can only be called by users with admin permissions)
+/// /nextround <n>: Starts a follow-up election between the top n candidates of the last one (can only be called by users with admin permissions)
+/// /config <setting> <value>: Changes a per-guild election setting (can only be called by users with admin permissions)
+/// /schedule create <prompt> <time> [duration]: Queues an election to start later (can only be called by users with admin permissions)
+/// /schedule cancel: Cancels the queued election (can only be called by users with admin permissions)
+/// /template save|start|delete|list: Saves elections by name and starts them again (can only be called by users with admin permissions)
+///
+/// With `/config election-scope channel`, each channel runs its own election and
+/// commands apply to the election of the channel they're used in.
+///
+/// The handler is cheap to clone, so background timer tasks can hold their own copy.
+#[derive(Clone, Default)]
+struct Handler {
+    // Servers the bot may operate in. When empty, every guild is accepted.
+    approved_servers: HashSet<GuildId>,
+
+    // Per-guild election state, added and removed as the bot joins and leaves guilds
+    guilds: Arc<RwLock<HashMap<GuildId, Arc<GuildState>>>>,
+
+    // Elections of guilds that run one per channel, created as channels first use them
+    channel_elections: Arc<RwLock<HashMap<ElectionKey, Arc<GuildState>>>>,
+
+    // Persists the state that must survive a restart
+    store: Arc<Store>,
+
+    // Set once persisted timers have been restarted, since ready fires on every reconnect
+    timers_resumed: Arc<AtomicBool>,
+
+    // Fingerprint of the slash commands last registered with Discord, 0 until they are
+    registered_commands: Arc<AtomicU64>,
+
+    // Whether the gateway is connected, as reported by the health endpoint
+    gateway_ready: Arc<AtomicBool>,
+
+    // The latest gateway context, for proposals arriving over HTTP rather than from Discord
+    context: Arc<RwLock<Option<Context>>>,
+
+    // Set once operators were warned that admin checks fall back to fetching members
+    members_intent_warned: Arc<AtomicBool>,
+
+    // Where final results are POSTed, if configured
+    results_webhook: Option<Arc<ResultsWebhook>>,
+
+    // Records credit transfers for admins to review
+    audit: Arc<AuditLog>,
+
+    // Where guilds' credit snapshots are read from
+    snapshot_dir: Arc<PathBuf>,
+
+    // The bot automatically updates results of the election as it progresses
+    results: Arc<RwLock<HashMap<ElectionKey, Board>>>,
+
+    // Paces sends and edits per channel, shared by announcements and board refreshes
+    pacer: Arc<ChannelPacer>,
+
+    // How often and how patiently announcements are retried
+    announce_retry: RetryPolicy,
+
+    // Rate limiting: track last command usage per user per guild, for each kind of command
+    last_command_time: Arc<RwLock<CommandTimes>>,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        println!("Bot logged in as {}", ready.user.name);
+        self.gateway_ready.store(true, Ordering::Relaxed);
+        *self.context.write().await = Some(ctx.clone());
+
+        if !self.timers_resumed.swap(true, Ordering::Relaxed) {
+            self.resume_timers(&ctx).await;
+        }
+
+        // Create modern slash commands with proper builders
+        let commands = vec![
+            CreateCommand::new("prop")
+                .description("Propose a candidate for the election")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "idea",
+                        "Your proposal (leave out to open a form with room for a description)"
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "link",
+                        "An image or web page showing the candidate, e.g. for logo votes (http or https)"
+                    )
+                    .max_length(MAX_LINK_LEN as u16)
+                ),
+            CreateCommand::new("editprop")
+                .description("Fix the text of your proposal before voting starts")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "id",
+                        "Proposal number"
+                    )
+                    .required(true)
+                    .min_int_value(1)
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "text",
+                        "The corrected proposal"
+                    )
+                    .required(true)
+                ),
+            CreateCommand::new("merge")
+                .description("Fold a duplicate proposal into another before voting starts")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "keep",
+                        "Proposal number whose text is kept"
+                    )
+                    .required(true)
+                    .min_int_value(1)
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "drop",
+                        "Proposal number to remove"
+                    )
+                    .required(true)
+                    .min_int_value(1)
+                ),
+            CreateCommand::new("restore")
+                .description("Bring back the proposal /merge removed last"),
+            CreateCommand::new("lock-proposals")
+                .description("Close the election to new proposals before voting opens"),
+            CreateCommand::new("vote")
+                .description("Cast votes for a candidate")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "n",
+                        "Votes to cast (costs n² credits)"
+                    )
+                    .required(true)
+                    .min_int_value(1)
+                    // The server's own limit, set with /config max-votes, is checked when voting
+                    .max_int_value(MAX_VOTES_CEILING as u64)
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "id",
+                        "Candidate ID"
+                    )
+                    .required(true)
+                    .min_int_value(0)
+                    .set_autocomplete(true)
+                ),
+            CreateCommand::new("votemulti")
+                .description("Cast votes for several candidates at once")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "votes",
+                        "Pairs of candidate ID and votes, e.g. 1:3 4:2 7:1"
+                    )
+                    .required(true)
+                ),
+            CreateCommand::new("vote-all")
+                .description("Spread your remaining credits evenly over every candidate"),
+            CreateCommand::new("simulate")
+                .description("Work out what some votes would cost, without casting them")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "votes",
+                        "Votes per candidate, e.g. 3 3 2"
+                    )
+                    .required(true)
+                ),
+            CreateCommand::new("list")
+                .description("Show the current candidates and their IDs")
+                .add_option(
+                    ListSort::ALL.iter().fold(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "sort",
+                            "Order by number (default), by name, or by votes once voting has started"
+                        ),
+                        |option, sort| option.add_string_choice(sort.name(), sort.name()),
+                    )
+                ),
+            CreateCommand::new("details")
+                .description("Read the description a candidate was proposed with")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "id",
+                        "Candidate ID"
+                    )
+                    .required(true)
+                    .min_int_value(1)
+                ),
+            CreateCommand::new("leaderboard")
+                .description("Show the most active proposers and voters"),
+            CreateCommand::new("standings")
+                .description("Repost the current results in this channel"),
+            CreateCommand::new("results")
+                .description("See results that aren't shown publicly (spectators only)")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "private",
+                        "Show the full current standings, even while live results are hidden"
+                    )
+                ),
+            CreateCommand::new("status")
+                .description("Show the running election's phase and turnout"),
+            CreateCommand::new("history")
+                .description("List past elections and their winners")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "page",
+                        "Page to show, newest elections first (default 1)"
+                    )
+                    .min_int_value(1)
+                ),
+            CreateCommand::new("export-matrix")
+                .description("Export the anonymized vote matrix for research (admins only, needs opt-in)")
+                .add_option(
+                    MatrixFormat::ALL.iter().fold(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "format",
+                            "File format (default csv)"
+                        ),
+                        |option, format| option.add_string_choice(format.name(), format.name()),
+                    )
+                ),
+            CreateCommand::new("debug")
+                .description("Dump this server's election state for troubleshooting (bot owner only)"),
+            CreateCommand::new("analytics")
+                .description("See how much the server has used the bot (admins only)"),
+            CreateCommand::new("whoami")
+                .description("Check which election permissions you have"),
+            CreateCommand::new("abstain")
+                .description("Take part in the vote without backing any candidate"),
+            CreateCommand::new("unvote")
+                .description("Retract your votes for a candidate")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "id",
+                        "Candidate ID"
+                    )
+                    .required(true)
+                    .min_int_value(1)
+                    .set_autocomplete(true)
+                ),
+            CreateCommand::new("myvotes")
+                .description("Review and retract your own votes"),
+            CreateCommand::new("points")
+                .description("Check your remaining voice credits"),
+            CreateCommand::new("delegate")
+                .description("Give some of your voice credits to a member you trust")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::User,
+                        "user",
+                        "Who gets the credits"
+                    )
+                    .required(true)
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "credits",
+                        "How many credits to give"
+                    )
+                    .required(true)
+                    .min_int_value(1)
+                    .max_int_value(MAX_STARTING_CREDITS as u64)
+                ),
+            CreateCommand::new("start")
+                .description("Start a new election (requires voting role)")
+                .set_options(election_command_options())
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "candidates",
+                        "Candidates to start with, separated by ; (e.g. Park; Library; Bike lanes)"
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Boolean,
+                        "vote-now",
+                        "Open voting on the listed candidates right away, with no suggestion phase"
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Boolean,
+                        "preview",
+                        "Only show me the announcement, without starting the election"
+                    )
+                ),
+            CreateCommand::new("schedule")
+                .description("Queue an election to start later (requires voting role)")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "create",
+                        "Schedule an election"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "prompt",
+                            "Election topic/question"
+                        )
+                        .required(true)
+                        .max_length(MAX_PROMPT_LEN as u16)
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "time",
+                            "When to start: ISO 8601 (2025-06-01T18:00:00Z) or relative (2h, 1d)"
+                        )
+                        .required(true)
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "duration",
+                            "Suggestion phase length, e.g. 48h or 2d"
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "cancel",
+                        "Cancel the scheduled election"
+                    )
+                ),
+            CreateCommand::new("template")
+                .description("Save elections and start them again later (requires voting role)")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "save",
+                        "Save an election's prompt and options under a name"
+                    )
+                    // Set in one go, since required options have to come before optional ones
+                    .set_sub_options(std::iter::once(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "name",
+                            "Template name, e.g. weekly-topic"
+                        )
+                        .required(true)
+                        .max_length(template::MAX_TEMPLATE_NAME_LEN as u16)
+                    ).chain(election_command_options()))
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "start",
+                        "Start an election from a saved template"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "name",
+                            "Template name"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "delete",
+                        "Delete a saved template"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "name",
+                            "Template name"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "list",
+                        "Show the saved templates"
+                    )
+                ),
+            CreateCommand::new("stop")
+                .description("Stop the current election phase (requires voting role)"),
+            CreateCommand::new("cancel")
+                .description("Abandon the running election without announcing winners"),
+            CreateCommand::new("nextround")
+                .description("Start a follow-up election between the top candidates of the last one")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "n",
+                        "How many of the top candidates go through"
+                    )
+                    .required(true)
+                    .min_int_value(2)
+                    .max_int_value(MAX_NEXT_ROUND as u64)
+                ),
+            CreateCommand::new("reset")
+                .description("Wipe this server's election, votes and credits (requires voting role)"),
+            CreateCommand::new("config")
+                .description("Change election settings for this server (requires voting role)")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "show",
+                        "List every setting and its current value"
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "min-candidates",
+                        "Minimum number of proposals before voting can start"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "n",
+                            "Minimum number of proposals"
+                        )
+                        .required(true)
+                        .min_int_value(1)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "countdown-interval",
+                        "How often the announcement's time remaining is refreshed"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "minutes",
+                            "Minutes between refreshes (0 turns the countdown off)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(24 * 60)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "reset-interval",
+                        "Reset everyone's credits periodically, independent of elections"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "hours",
+                            "Hours between resets (0 = only when an election ends)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(MAX_RESET_INTERVAL_HOURS as u64)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "credit-carryover",
+                        "Choose what happens to unused credits at each periodic reset"
+                    )
+                    .add_sub_option(
+                        CarryOver::NAMES.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "policy",
+                                "reset loses them; carry keeps them all; decay keeps a percentage"
+                            )
+                            .required(true),
+                            |option, name| option.add_string_choice(*name, *name),
+                        )
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "percent",
+                            "Percentage of unused credits decay keeps"
+                        )
+                        .min_int_value(1)
+                        .max_int_value(99)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "starting-credits",
+                        "Credits each member gets per election"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "credits",
+                            "Credits per member"
+                        )
+                        .required(true)
+                        .min_int_value(1)
+                        .max_int_value(MAX_STARTING_CREDITS as u64)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "max-votes",
+                        "Most votes a member can cast for a single candidate"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "n",
+                            "Votes per candidate"
+                        )
+                        .required(true)
+                        .min_int_value(1)
+                        .max_int_value(MAX_VOTES_CEILING as u64)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "tie-break",
+                        "What happens when candidates tie for the last winning place"
+                    )
+                    .add_sub_option(
+                        TieBreak::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "mode",
+                                "How the tie is settled"
+                            )
+                            .required(true),
+                            |option, mode| option.add_string_choice(mode.name(), mode.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "freeze-window",
+                        "Freeze vote changes in the final minutes of voting"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "minutes",
+                            "Length of the freeze before the voting deadline (0 turns it off)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(VOTE_INTERVAL * 60)
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "new-votes",
+                            "Still accept votes for candidates a member hasn't voted for yet (default true)"
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "research-export",
+                        "Allow admins to export the anonymized vote matrix with /export-matrix"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "enabled",
+                            "Whether this server agrees to vote data being exported for research"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "leaderboard",
+                        "Track members' proposals and spending for /leaderboard"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "enabled",
+                            "Whether to keep the leaderboard"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "locale",
+                        "Choose the language of the election announcements"
+                    )
+                    .add_sub_option(
+                        Locale::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "language",
+                                "Language of the start, candidates and winners announcements"
+                            )
+                            .required(true),
+                            |option, locale| option.add_string_choice(locale.label(), locale.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "currency-name",
+                        "Choose what members' credits are called, e.g. voice credits or tokens"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "name",
+                            "Plural name of the credits (points goes back to the default)"
+                        )
+                        .required(true)
+                        .max_length(config::MAX_CURRENCY_NAME_LEN as u16)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "overspend-policy",
+                        "Choose what /vote does with votes a member can't afford"
+                    )
+                    .add_sub_option(
+                        OverspendPolicy::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "policy",
+                                "reject refuses the vote; clamp casts as many votes as the member can afford"
+                            )
+                            .required(true),
+                            |option, policy| option.add_string_choice(policy.name(), policy.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "on-restart",
+                        "Choose what /start does when an election is already running"
+                    )
+                    .add_sub_option(
+                        RestartPolicy::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "policy",
+                                "finalize announces its winners; cancel discards it; reject refuses to start"
+                            )
+                            .required(true),
+                            |option, policy| option.add_string_choice(policy.name(), policy.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "live-results",
+                        "Show the standings while voting is open, or hide them until the end"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "enabled",
+                            "Whether members can see the standings before the winners are announced"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "results-format",
+                        "Choose how much detail the live results show"
+                    )
+                    .add_sub_option(
+                        ResultsFormat::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "format",
+                                "compact shows votes; detailed adds rank, share and voters"
+                            )
+                            .required(true),
+                            |option, format| option.add_string_choice(format.name(), format.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "election-scope",
+                        "Choose whether the server runs one election, or one per channel"
+                    )
+                    .add_sub_option(
+                        ElectionScope::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "scope",
+                                "guild runs a single election; channel runs an independent election in each channel"
+                            )
+                            .required(true),
+                            |option, scope| option.add_string_choice(scope.name(), scope.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "proposal-batch",
+                        "Announce new proposals together instead of one message each"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "seconds",
+                            "How long to collect proposals before announcing them (0 announces each right away)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(config::MAX_PROPOSAL_BATCH_SECS)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "ping-delay",
+                        "Wait before pinging @everyone about a new election"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "seconds",
+                            "Time to /cancel a mistaken /start before anyone is pinged (0 pings right away)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(config::MAX_PING_DELAY_SECS)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "stop-confirmation",
+                        "Ask for confirmation before /stop ends the vote"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "enabled",
+                            "Whether to ask first"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "ratification",
+                        "Let members ratify the winners with ✅/❌ reactions"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "minutes",
+                            "How long ratification stays open after the winners are announced (0 turns it off)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(ratify::MAX_RATIFY_WINDOW_MINS)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "blocked-notice",
+                        "Tell everyone when /stop can't open voting yet"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "enabled",
+                            "Whether to post a public notice"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "proposer-dms",
+                        "DM proposers their candidate IDs when voting opens"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "enabled",
+                            "Whether to send the DMs"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "ping-winners",
+                        "Mention the proposers of the winners in the winners announcement"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Boolean,
+                            "enabled",
+                            "Whether to mention them"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "winners-message",
+                        "Word the winners announcement yourself"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "template",
+                            "Uses {winners}, {prompt} and {participants}; \\n starts a new line; default restores it"
+                        )
+                        .required(true)
+                        .max_length(config::MAX_WINNERS_MESSAGE_LEN as u16)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "min-membership",
+                        "How long members must have been in the server before they can vote"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "duration",
+                            "e.g. 1d or 12h; off lets anyone vote"
+                        )
+                        .required(true)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "departed-members",
+                        "What happens to the votes of members who leave the server mid-election"
+                    )
+                    .add_sub_option(
+                        DepartedPolicy::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "policy",
+                                "keep: restore them if the member rejoins; purge: remove them from the tally"
+                            )
+                            .required(true),
+                            |option, policy| option.add_string_choice(policy.name(), policy.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "delegation-cap",
+                        "Limit the credits a member can receive from others with /delegate"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "credits",
+                            "Most credits a member can receive per election (0 = no limit)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(MAX_STARTING_CREDITS as u64)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "proposal-cost",
+                        "Make members pay credits for each proposal they submit"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "credits",
+                            "Credits taken from the proposer's balance (0 = free)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(MAX_STARTING_CREDITS as u64)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "credit-snapshot",
+                        "Read members' starting credits from this server's snapshot file"
+                    )
+                    .add_sub_option(
+                        SnapshotMode::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "mode",
+                                "off: everyone gets the same; starting/zero: what members missing from the snapshot get"
+                            )
+                            .required(true),
+                            |option, mode| option.add_string_choice(mode.name(), mode.name()),
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "max-concentration",
+                        "Limit how much of a member's budget can go to one candidate"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "percent",
+                            "Largest share of the starting credits one candidate can get (100 = no limit)"
+                        )
+                        .required(true)
+                        .min_int_value(1)
+                        .max_int_value(100)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "max-candidates-per-voter",
+                        "Limit how many different candidates a member can vote for"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "count",
+                            "Most candidates one member can back (0 = no limit)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(config::MAX_CANDIDATES_PER_VOTER as u64)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "moderation",
+                        "Hold proposals for a moderator's approval before they're announced"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Channel,
+                            "channel",
+                            "Where proposals wait for review (leave out to announce proposals right away)"
+                        )
+                        .channel_types(vec![ChannelType::Text])
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "cooldown",
+                        "Change how long members wait between commands of one kind"
+                    )
+                    .add_sub_option(
+                        CommandKind::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "commands",
+                                "Which kind of commands"
+                            )
+                            .required(true),
+                            |option, kind| option.add_string_choice(kind.name(), kind.name()),
+                        )
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "seconds",
+                            "Seconds between commands (0 = no cooldown)"
+                        )
+                        .required(true)
+                        .min_int_value(0)
+                        .max_int_value(cooldown::MAX_COOLDOWN_SECS)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "emoji",
+                        "Change an emoji used in announcements"
+                    )
+                    .add_sub_option(
+                        Slot::ALL.iter().fold(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "slot",
+                                "Which announcement emoji to change"
+                            )
+                            .required(true),
+                            |option, slot| option.add_string_choice(slot.name(), slot.name()),
+                        )
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "emoji",
+                            "The new emoji, e.g. 🎉 or a custom server emoji (leave out to restore the default)"
+                        )
+                        .max_length(theme::MAX_EMOJI_LEN as u16)
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "spectator-roles",
+                        "Choose the roles allowed to see hidden results with /results private"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "roles",
+                            "Comma-separated role names or IDs, e.g. observer (leave out to remove all spectator roles)"
+                        )
+                    )
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "election-roles",
+                        "Choose the roles allowed to run elections"
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "roles",
+                            "Comma-separated role names or IDs, e.g. moderator, organizer (leave out to restore the default)"
+                        )
+                    )
+                ),
+        ];
+
+        // ready fires again on every reconnect, so the commands are only pushed again
+        // when they differ from what this process already registered
+        let fingerprint = command_fingerprint(&commands);
+        if self.registered_commands.load(Ordering::Relaxed) == fingerprint {
+            println!("Slash commands are unchanged since they were registered, skipping registration");
+            return;
+        }
+
+        // Register commands globally for all guilds
+        match ctx.http.create_global_commands(&commands).await {
+            Ok(_) => {
+                self.registered_commands.store(fingerprint, Ordering::Relaxed);
+                println!("Successfully registered {} global slash commands", commands.len());
+            },
+            Err(why) => println!("Failed to register global commands: {:?}", why),
+        }
+    }
+
+    async fn resume(&self, _ctx: Context, _event: ResumedEvent) {
+        println!("Resumed the gateway session");
+        self.gateway_ready.store(true, Ordering::Relaxed);
+    }
+
+    async fn shard_stage_update(&self, _ctx: Context, event: ShardStageUpdateEvent) {
+        // Serenity reconnects on its own; the health endpoint reports unhealthy meanwhile
+        println!("Gateway connection {:?} -> {:?}", event.old, event.new);
+        self.gateway_ready.store(event.new == ConnectionStage::Connected, Ordering::Relaxed);
+    }
+
+    async fn guild_create(&self, _ctx: Context, guild: Guild, _is_new: Option<bool>) {
+        if !self.is_approved(&guild.id) {
+            println!("Ignoring guild {} ({}): not in the approved servers list", guild.id, guild.name);
+            return;
+        }
+
+        if self.add_guild(guild.id).await {
+            println!("Initialized election state for guild {} ({})", guild.id, guild.name);
+        }
+    }
+
+    async fn guild_delete(&self, _ctx: Context, incomplete: UnavailableGuild, _full: Option<Guild>) {
+        // An unavailable guild is an outage, not a removal - keep its state around
+        if incomplete.unavailable {
+            return;
+        }
+
+        let elections = self.guild_elections(incomplete.id).await;
+        let removed = self.guilds.write().await.remove(&incomplete.id);
+        if let Some(state) = removed {
+            if let Some(timer) = state.schedule_timer.write().await.take() {
+                timer.abort();
+            }
+            if let Some(timer) = state.reset_timer.write().await.take() {
+                timer.abort();
+            }
+            for (_, election) in &elections {
+                if let Some(timer) = election.countdown_timer.write().await.take() {
+                    timer.abort();
+                }
+                if let Some(timer) = election.ping_timer.write().await.take() {
+                    timer.abort();
+                }
+                if let Some(timer) = election.batch_timer.write().await.take() {
+                    timer.abort();
+                }
+            }
+            self.persist().await;
+            self.channel_elections.write().await.retain(|key, _| key.guild_id != incomplete.id);
+            self.results.write().await.retain(|key, _| key.guild_id != incomplete.id);
+            self.last_command_time.write().await.last_used.retain(|(g, _, _), _| *g != incomplete.id);
+            println!("Removed election state for guild {}", incomplete.id);
+        }
+    }
+
+    async fn guild_member_removal(&self, ctx: Context, guild_id: GuildId, user: User, _member: Option<Member>) {
+        for (key, election) in self.guild_elections(guild_id).await {
+            if member_departed(&election, user.id).await {
+                println!("Purged the votes of departed member {} in guild {}", user.id, key);
+                self.poll_votes(ctx.clone(), &key).await;
+            }
+        }
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let Some((key, state, user_id, candidate_id)) = self.reaction_vote_target(&ctx, &reaction).await else {
+            return;
+        };
+
+        let approved = match check_membership(&state, reaction.member.as_ref().and_then(|member| member.joined_at), schedule::now_unix()).await {
+            Ok(()) => state.update_votes(|voting| voting.approve(user_id, candidate_id)).await,
+            Err(why) => Err(why),
+        };
+        if let Err(why) = approved {
+            // Take the reaction back off so it doesn't look like it counted
+            println!("Rejected reaction vote from {} for candidate #{}: {}", user_id, candidate_id, why);
+            if let Err(why) = reaction.delete(&ctx).await {
+                eprintln!("Failed to remove rejected reaction: {}", why);
+            }
+            return;
+        }
+        state.record_voter(user_id).await;
+
+        self.poll_votes(ctx, &key).await;
+    }
+
+    async fn reaction_remove(&self, ctx: Context, reaction: Reaction) {
+        let Some((key, state, user_id, candidate_id)) = self.reaction_vote_target(&ctx, &reaction).await else {
+            return;
+        };
+
+        match state.update_votes(|voting| voting.retract(user_id, candidate_id)).await {
+            Ok(_) => self.poll_votes(ctx, &key).await,
+            // The reaction can't be put back for the member, so the vote stays counted
+            // and they're told why by DM
+            Err(why @ CommandError::VotesFrozen { .. }) => {
+                println!("Kept frozen reaction vote from {} for candidate #{}", user_id, candidate_id);
+                let content = format!("{} Your reaction vote for candidate #{} still counts.", why, candidate_id);
+                if let Err(why) = user_id.direct_message(&ctx, CreateMessage::new().content(content)).await {
+                    eprintln!("Failed to tell {} their reaction vote is frozen: {}", user_id, why);
+                }
+            },
+            Err(_) => {},
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::Component(component) = interaction {
+            self.handle_component(&ctx, &component).await;
+        } else if let Interaction::Modal(modal) = interaction {
+            self.handle_modal(&ctx, &modal).await;
+        } else if let Interaction::Autocomplete(autocomplete) = interaction {
+            self.handle_autocomplete(&ctx, &autocomplete).await;
+        } else if let Interaction::Command(command) = interaction {
+            println!("Received slash command: {} from user: {}", command.data.name, command.user.id);
+            // Only the totals are kept, never who ran what
+            if let Some(guild_id) = command.guild_id {
+                if let Some(state) = self.guild_state(&guild_id).await {
+                    state.analytics.write().await.record_command(&command.data.name);
+                }
+            }
+
+            // Handle commands with appropriate response patterns
+            match command.data.name.as_str() {
+                "prop" => {
+                    self.handle_prop_command(&ctx, &command).await;
+                },
+                "editprop" => {
+                    self.handle_editprop_command(&ctx, &command).await;
+                },
+                "merge" => {
+                    self.handle_merge_command(&ctx, &command).await;
+                },
+                "restore" => {
+                    self.handle_restore_command(&ctx, &command).await;
+                },
+                "lock-proposals" => {
+                    self.handle_lock_proposals_command(&ctx, &command).await;
+                },
+                "vote" => {
+                    self.handle_vote_command(&ctx, &command).await;
+                },
+                "votemulti" => {
+                    self.handle_votemulti_command(&ctx, &command).await;
+                },
+                "vote-all" => {
+                    self.handle_vote_all_command(&ctx, &command).await;
+                },
+                "simulate" => {
+                    self.handle_simulate_command(&ctx, &command).await;
+                },
+                "list" => {
+                    self.handle_list_command(&ctx, &command).await;
+                },
+                "leaderboard" => {
+                    self.handle_leaderboard_command(&ctx, &command).await;
+                },
+                "results" => {
+                    self.handle_results_command(&ctx, &command).await;
+                },
+                "standings" => {
+                    self.handle_standings_command(&ctx, &command).await;
+                },
+                "status" => {
+                    self.handle_status_command(&ctx, &command).await;
+                },
+                "history" => {
+                    self.handle_history_command(&ctx, &command).await;
+                },
+                "analytics" => {
+                    self.handle_analytics_command(&ctx, &command).await;
+                },
+                "debug" => {
+                    self.handle_debug_command(&ctx, &command).await;
+                },
+                "export-matrix" => {
+                    self.handle_export_matrix_command(&ctx, &command).await;
+                },
+                "whoami" => {
+                    self.handle_whoami_command(&ctx, &command).await;
+                },
+                "reset" => {
+                    self.handle_reset_command(&ctx, &command).await;
+                },
+                "abstain" => {
+                    self.handle_abstain_command(&ctx, &command).await;
+                },
+                "unvote" => {
+                    self.handle_unvote_command(&ctx, &command).await;
+                },
+                "details" => {
+                    self.handle_details_command(&ctx, &command).await;
+                },
+                "myvotes" => {
+                    self.handle_myvotes_command(&ctx, &command).await;
+                },
+                "points" => {
+                    self.handle_points_command(&ctx, &command).await;
+                },
+                "delegate" => {
+                    self.handle_delegate_command(&ctx, &command).await;
+                },
+                "start" => {
+                    self.handle_start_command(&ctx, &command).await;
+                },
+                "schedule" => {
+                    self.handle_schedule_command(&ctx, &command).await;
+                },
+                "template" => {
+                    self.handle_template_command(&ctx, &command).await;
+                },
+                "stop" => {
+                    self.handle_stop_command(&ctx, &command).await;
+                },
+                "cancel" => {
+                    self.handle_cancel_command(&ctx, &command).await;
+                },
+                "nextround" => {
+                    self.handle_nextround_command(&ctx, &command).await;
+                },
+                "config" => {
+                    self.handle_config_command(&ctx, &command).await;
+                },
+                _ => {
+                    self.send_ephemeral_response(&ctx, &command, &format!("{} Unknown command. Please try again.", theme::ERROR)).await;
+                },
+            }
+        }
+    }
+}
+
+impl Handler {
+    /// Creates buckets for all of the pre-specified servers the bot belongs to.
+    /// An empty list approves every guild the bot is invited to.
+    fn register_servers(mut self, approved_servers: Vec<GuildId>) -> Self {
+        let guilds = Arc::get_mut(&mut self.guilds)
+            .expect("handler state is shared before startup")
+            .get_mut();
+        for g in approved_servers {
+            guilds.insert(g, Arc::new(GuildState::default()));
+            self.approved_servers.insert(g);
+        }
+
+        self
+    }
+
+    /// Loads the persisted state from the given store, and keeps the store for later saves.
+    fn restore(mut self, store: Store) -> Self {
+        let snapshot = store.load();
+        self.store = Arc::new(store);
+
+        let guilds = Arc::get_mut(&mut self.guilds)
+            .expect("handler state is shared before startup")
+            .get_mut();
+        for (g, persisted) in snapshot.guilds {
+            if !(self.approved_servers.is_empty() || self.approved_servers.contains(&g)) {
+                continue;
+            }
+
+            let state = Arc::get_mut(guilds.entry(g).or_default())
+                .expect("guild state is shared before startup");
+            *Arc::get_mut(&mut state.config)
+                .expect("guild config is shared before startup")
+                .get_mut() = persisted.config;
+            *state.scheduled_start.get_mut() = persisted.scheduled_start;
+            *state.leaderboard.get_mut() = persisted.leaderboard;
+            *state.templates.get_mut() = persisted.templates;
+            *state.history.get_mut() = persisted.history;
+            *state.analytics.get_mut() = persisted.analytics;
+        }
+
+        self
+    }
+
+    /// Reports final results to the configured webhook, if any.
+    fn with_results_webhook(mut self, webhook: Option<ResultsWebhook>) -> Self {
+        self.results_webhook = webhook.map(Arc::new);
+        self
+    }
+
+    /// Retries announcements as the given policy says.
+    fn with_announce_retry(mut self, retry: RetryPolicy) -> Self {
+        self.announce_retry = retry;
+        self
+    }
+
+    /// Records audited actions to the given log.
+    fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Arc::new(audit);
+        self
+    }
+
+    /// Reads guilds' credit snapshots from the given directory.
+    fn with_snapshot_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshot_dir = Arc::new(dir.into());
+        self
+    }
+
+    /// Saves the state that must survive a restart.
+    async fn persist(&self) {
+        if let Err(e) = self.store.save(self.snapshot()).await {
+            eprintln!("Failed to persist state: {}", e);
+        }
+    }
+
+    /// The state that has to survive a restart, as it is now.
+    async fn snapshot(&self) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        for (g, state) in self.guilds.read().await.iter() {
+            let persisted = PersistedGuild {
+                config: state.config.read().await.clone(),
+                scheduled_start: state.scheduled_start.read().await.clone(),
+                leaderboard: state.leaderboard.read().await.clone(),
+                templates: state.templates.read().await.clone(),
+                history: state.history.read().await.clone(),
+                analytics: state.analytics.read().await.clone(),
+            };
+            snapshot.guilds.insert(*g, persisted);
+        }
+        snapshot
+    }
+
+    /// Looks up the member's admin-relevant permissions. The cache can miss members,
+    /// e.g. in large guilds or without the Server Members intent, so a member missing
+    /// from it is fetched from Discord before being denied. Returns None when the
+    /// guild is not cached.
+    async fn admin_permissions(&self, ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<AdminPermissions> {
+        let roles = self.election_roles(&guild_id).await;
+        self.role_permissions(ctx, guild_id, user_id, &roles).await
+    }
+
+    /// Whether the member may see the private results: the owner, administrators and
+    /// members with a spectator role. Election roles alone don't count.
+    async fn is_spectator(&self, ctx: &Context, guild_id: GuildId, user_id: UserId) -> bool {
+        let roles = match self.guild_state(&guild_id).await {
+            Some(state) => state.config.read().await.spectator_roles.clone(),
+            None => Vec::new(),
+        };
+        self.role_permissions(ctx, guild_id, user_id, &roles)
+            .await
+            .is_some_and(|permissions| permissions.allowed())
+    }
+
+    /// Works out the member's permissions against the given roles, fetching them from
+    /// Discord when the cache misses them.
+    async fn role_permissions(&self, ctx: &Context, guild_id: GuildId, user_id: UserId, roles: &[String]) -> Option<AdminPermissions> {
+        let cached = evaluate_admin_permissions(ctx, guild_id, user_id, roles, None)?;
+        if cached.member_found || cached.owner {
+            return Some(cached);
+        }
+
+        match guild_id.member(&ctx.http, user_id).await {
+            Ok(member) => {
+                if !self.members_intent_warned.swap(true, Ordering::Relaxed) {
+                    eprintln!("Some members aren't cached, so admin checks fetch them from Discord. Check that the Server Members intent is enabled.");
+                }
+                evaluate_admin_permissions(ctx, guild_id, user_id, roles, Some(&member))
+            },
+            Err(why) => {
+                eprintln!("Failed to fetch member {} of guild {} for an admin check: {}", user_id, guild_id, why);
+                Some(cached)
+            },
+        }
+    }
+
+    /// The roles, by name or ID, that let members run elections in a guild.
+    async fn election_roles(&self, guild_id: &GuildId) -> Vec<String> {
+        match self.guild_state(guild_id).await {
+            Some(state) => state.config.read().await.election_roles.clone(),
+            None => vec![BOT_ROLE.to_string()],
+        }
+    }
+
+    /// Checks whether the bot may operate in the given guild.
+    fn is_approved(&self, guild_id: &GuildId) -> bool {
+        self.approved_servers.is_empty() || self.approved_servers.contains(guild_id)
+    }
+
+    /// Creates the election state of a guild the bot was added to, if it's approved.
+    /// Returns whether a new bucket was created.
+    async fn add_guild(&self, guild_id: GuildId) -> bool {
+        if !self.is_approved(&guild_id) {
+            return false;
+        }
+
+        // Fired at startup and on reconnect as well, so keep any existing state
+        match self.guilds.write().await.entry(guild_id) {
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::new(GuildState::default()));
+                true
+            },
+            Entry::Occupied(_) => false,
+        }
+    }
+
+    /// Get the election state for a guild, if the bot serves it.
+    async fn guild_state(&self, guild_id: &GuildId) -> Option<Arc<GuildState>> {
+        self.guilds.read().await.get(guild_id).cloned()
+    }
+
+    /// The election a command run in the channel is about: the guild's, or the
+    /// channel's own when the guild runs one election per channel.
+    async fn election_key(&self, guild_id: GuildId, channel_id: ChannelId) -> ElectionKey {
+        let scope = match self.guild_state(&guild_id).await {
+            Some(state) => state.config.read().await.election_scope,
+            None => ElectionScope::Guild,
+        };
+        ElectionKey::resolve(guild_id, channel_id, scope)
+    }
+
+    /// Get the state of an election, if the bot serves its guild. A channel's election
+    /// is created the first time it's used.
+    async fn election_state(&self, key: &ElectionKey) -> Option<Arc<GuildState>> {
+        let guild = self.guild_state(&key.guild_id).await?;
+        if key.channel_id.is_none() {
+            return Some(guild);
+        }
+
+        let mut elections = self.channel_elections.write().await;
+        Some(elections.entry(*key).or_insert_with(|| Arc::new(GuildState::for_channel(&guild))).clone())
+    }
+
+    /// Every election of a guild: its own, then those of its channels.
+    async fn guild_elections(&self, guild_id: GuildId) -> Vec<(ElectionKey, Arc<GuildState>)> {
+        let mut elections = Vec::new();
+        if let Some(state) = self.guild_state(&guild_id).await {
+            elections.push((ElectionKey::guild(guild_id), state));
+        }
+        elections.extend(
+            self.channel_elections
+                .read()
+                .await
+                .iter()
+                .filter(|(key, _)| key.guild_id == guild_id)
+                .map(|(key, state)| (*key, state.clone())),
+        );
+        elections
+    }
+
+    /// Spawns the timer task that starts a scheduled election once its time comes.
+    fn spawn_scheduled_start(&self, ctx: &Context, guild_id: GuildId, state: Arc<GuildState>, start_at: i64) -> AbortHandle {
+        let handler = self.clone();
+        let ctx = ctx.clone();
+
+        tokio::spawn(async move {
+            let Some(scheduled) = scheduled_start_due(&state, start_at).await else {
+                return;
+            };
+            handler.persist().await;
+
+            println!("Starting scheduled election in guild {}: {}", guild_id, scheduled.prompt);
+            let result = handler.start_election(&ctx, ElectionKey::guild(guild_id), scheduled.prompt, ElectionOptions {
+                duration_hours: scheduled.duration_hours,
+                ..ElectionOptions::default()
+            }, None).await;
+            println!("Scheduled election in guild {}: {}", guild_id, error::render(result));
+        })
+        .abort_handle()
+    }
+
+    /// Restarts the timers that were running before the bot restarted: credit resets,
+    /// and scheduled elections (any whose time passed while the bot was down start right away).
+    /// Running elections aren't persisted, so there are no phase deadlines to re-arm.
+    async fn resume_timers(&self, ctx: &Context) {
+        let guilds = self.guilds.read().await.clone();
+        for (g, state) in guilds {
+            self.restart_credit_reset(g, state.clone()).await;
+
+            let Some(start_at) = state.scheduled_start.read().await.as_ref().map(|s| s.start_at) else {
+                continue;
+            };
+
+            if start_at <= schedule::now_unix() {
+                println!("Scheduled election in guild {} was due at {} while the bot was down, starting it now", g, start_at);
+            } else {
+                println!("Resuming scheduled election in guild {} (starts at {})", g, start_at);
+            }
+            let timer = self.spawn_scheduled_start(ctx, g, state.clone(), start_at);
+            *state.schedule_timer.write().await = Some(timer);
+        }
+    }
+
+    /// (Re)starts the task that resets everyone's credits on the configured interval,
+    /// in each of the guild's elections. With an interval of 0, credits only reset
+    /// when an election ends.
+    async fn restart_credit_reset(&self, guild_id: GuildId, state: Arc<GuildState>) {
+        let hours = state.config.read().await.reset_interval_hours;
+
+        let timer = (hours > 0).then(|| {
+            let handler = self.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(hours * 60 * 60));
+                // The first tick completes immediately
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    for (_, election) in handler.guild_elections(guild_id).await {
+                        refill_credits(&election).await;
+                    }
+                    println!("Reset credits for guild {} (every {}h)", guild_id, hours);
+                }
+            })
+            .abort_handle()
+        });
+
+        let previous = std::mem::replace(&mut *state.reset_timer.write().await, timer);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+
+    /// Records when the current phase ends and restarts the task that keeps the
+    /// announcement's "Time remaining" line counting down. This is display only:
+    /// phases still end when an admin runs /stop.
+    async fn start_countdown(&self, ctx: &Context, key: ElectionKey, state: Arc<GuildState>, hours: u64) {
+        let deadline = schedule::now_unix() + (hours * 60 * 60) as i64;
+        *state.phase_deadline.write().await = Some(deadline);
+
+        let handler = self.clone();
+        let ctx = ctx.clone();
+        let task_state = state.clone();
+        let timer = tokio::spawn(async move {
+            loop {
+                // Re-read the interval each time so /config changes apply to running countdowns
+                let interval = task_state.config.read().await.countdown_interval_mins;
+                if interval == 0 {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+
+                if *task_state.phase_deadline.read().await != Some(deadline) {
+                    return;
+                }
+
+                handler.update_countdown(&ctx, &key, deadline).await;
+                if schedule::now_unix() >= deadline {
+                    return;
+                }
+            }
+        })
+        .abort_handle();
+
+        if let Some(previous) = state.countdown_timer.write().await.replace(timer) {
+            previous.abort();
+        }
+    }
+
+    /// Pings @everyone about a just-posted announcement once the delay passes, replying
+    /// to it so members land on the board. Edits never notify anyone, so the ping is a
+    /// message of its own. `/cancel` and `/reset` abort it before it fires.
+    async fn schedule_ping(&self, ctx: &Context, key: ElectionKey, state: Arc<GuildState>, board: (ChannelId, MessageId), announcement: String, delay_secs: u64) {
+        let pacer = self.pacer.clone();
+        let http = ctx.http.clone();
+        let (channel_id, board_id) = board;
+        let task_state = state.clone();
+        let timer = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(delay_secs)).await;
+            task_state.ping_timer.write().await.take();
+
+            pacer.acquire(channel_id).await;
+            let message = CreateMessage::new()
+                .content(format!("@everyone {}", announcement))
+                .reference_message((channel_id, board_id))
+                .allowed_mentions(CreateAllowedMentions::new().everyone(true));
+            if let Err(why) = channel_id.send_message(&http, message).await {
+                eprintln!("Failed to ping @everyone about the election in guild {}: {}", key, why);
+            }
+        })
+        .abort_handle();
+
+        if let Some(previous) = state.ping_timer.write().await.replace(timer) {
+            previous.abort();
+        }
+    }
+
+    /// Calls off a pending @everyone ping, if any.
+    async fn cancel_ping(&self, state: &GuildState) {
+        if let Some(timer) = state.ping_timer.write().await.take() {
+            timer.abort();
+        }
+    }
+
+    /// Drops the proposal announcements still waiting for their batch window, when the
+    /// election they belong to is discarded or replaced.
+    async fn cancel_proposal_batch(&self, state: &GuildState) {
+        if let Some(timer) = state.batch_timer.write().await.take() {
+            timer.abort();
+        }
+        state.announcement_batch.write().await.clear();
+    }
+
+    /// Adds the ✅/❌ reactions to the winners announcement and spawns the task that
+    /// tallies them once the ratification window closes. The tally isn't kept across
+    /// restarts, so a restart during the window skips it.
+    async fn start_ratification(&self, ctx: &Context, guild_id: GuildId, announcement: &Message, closes_at: i64) {
+        for emoji in [ratify::RATIFY_EMOJI, ratify::REJECT_EMOJI] {
+            if let Err(why) = announcement.react(ctx, ReactionType::Unicode(emoji.to_string())).await {
+                eprintln!("Failed to add ratification reactions in guild {}: {}", guild_id, why);
+                return;
+            }
+        }
+
+        let handler = self.clone();
+        let http = ctx.http.clone();
+        let (channel_id, message_id) = (announcement.channel_id, announcement.id);
+        tokio::spawn(async move {
+            let wait = (closes_at - schedule::now_unix()).max(0) as u64;
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+
+            // The bot may have been removed from the guild in the meantime
+            let Some(state) = handler.guild_state(&guild_id).await else {
+                return;
+            };
+            let tally = match ratify::tally(&http, channel_id, message_id).await {
+                Ok(tally) => tally,
+                Err(why) => {
+                    eprintln!("Failed to tally ratification in guild {}: {}", guild_id, why);
+                    return;
+                },
+            };
+
+            let messages = state.messages().await;
+            let (emoji, outcome) = if tally.ratified() {
+                (ratify::RATIFY_EMOJI, messages.ratified)
+            } else {
+                (ratify::REJECT_EMOJI, messages.not_ratified)
+            };
+            let content = format!(
+                "{} **{}**",
+                emoji,
+                locale::fill(outcome, &[("for", tally.for_votes), ("against", tally.against_votes)])
+            );
+            handler.pacer.acquire(channel_id).await;
+            let reply = CreateMessage::new()
+                .content(content)
+                .reference_message((channel_id, message_id))
+                .allowed_mentions(CreateAllowedMentions::new());
+            if let Err(why) = channel_id.send_message(&http, reply).await {
+                eprintln!("Failed to announce ratification in guild {}: {}", guild_id, why);
+            }
+            println!("Ratification in guild {}: {} for, {} against", guild_id, tally.for_votes, tally.against_votes);
+        });
+    }
+
+    /// Stops the countdown once the phase it tracked is over.
+    async fn stop_countdown(&self, state: &GuildState) {
+        *state.phase_deadline.write().await = None;
+        if let Some(timer) = state.countdown_timer.write().await.take() {
+            timer.abort();
+        }
+    }
+
+    /// Rewrites the "Time remaining" line of the tracked announcement.
+    async fn update_countdown(&self, context: &Context, key: &ElectionKey, deadline: i64) {
+        let Some(state) = self.election_state(key).await else {
+            return;
+        };
+        let line = format!("{} {}", state.messages().await.time_remaining, format_remaining(deadline - schedule::now_unix()));
+
+        if let Err(e) = self.refresh_board(context, key, |layout| layout.time_line = line).await {
+            eprintln!("Failed to update countdown for guild {}: {}", key, e);
+        }
+    }
+
+    /// Posts a board's static message, then its live message below it.
+    async fn post_board(&self, ctx: &Context, channel_id: ChannelId, layout: BoardLayout) -> serenity::Result<Board> {
+        self.pacer.acquire(channel_id).await;
+        let intro = channel_id.say(ctx, &layout.intro).await?;
+        self.pacer.acquire(channel_id).await;
+        let live = channel_id.say(ctx, layout.render_live()).await?;
+        Ok(Board { intro, live, layout })
+    }
+
+    /// Applies a change to the tracked announcement's layout and edits its live message
+    /// to match. Edits wait their turn in the channel, and by then a single edit may
+    /// carry the changes of several queued refreshes.
+    async fn refresh_board(&self, context: &Context, key: &ElectionKey, update: impl FnOnce(&mut BoardLayout)) -> Result<(), String> {
+        let channel_id = {
+            let mut results_write = self.results.write().await;
+            let Some(board) = results_write.get_mut(key) else {
+                return Err("No active announcement message".to_string());
+            };
+
+            update(&mut board.layout);
+            if board.layout.render_live() == board.live.content {
+                return Ok(());
+            }
+            board.live.channel_id
+        };
+        self.pacer.acquire(channel_id).await;
+
+        let mut results_write = self.results.write().await;
+        let Some(board) = results_write.get_mut(key) else {
+            return Err("No active announcement message".to_string());
+        };
+        let new_content = board.layout.render_live();
+        if new_content == board.live.content {
+            return Ok(());
+        }
+
+        // Edit message with timeout protection
+        match tokio::time::timeout(
+            std::time::Duration::from_secs(8),
+            board.live.edit(context, EditMessage::new().content(new_content))
+        ).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(format!("Failed to edit message: {}", e)),
+            Err(_) => Err("Timeout editing message".to_string()),
+        }
+    }
+
+    /// Check if user is rate limited, using the guild's cooldown for this kind of command.
+    /// Each kind is timed separately, so a /points right after a /vote goes through.
+    async fn check_rate_limit(&self, guild_id: GuildId, user_id: UserId, kind: CommandKind) -> Result<(), CommandError> {
+        let secs = match self.guild_state(&guild_id).await {
+            Some(state) => state.config.read().await.cooldowns.get(kind),
+            None => 0,
+        };
+        if secs == 0 {
+            return Ok(());
+        }
+
+        self.last_command_time
+            .write()
+            .await
+            .check((guild_id, user_id, kind), Duration::from_secs(secs), Instant::now())
+    }
+
+    /// Get a list of the candidates that are winning so far, sorted by their
+    /// number of votes.
+    async fn winners(&self, key: &ElectionKey) -> Vec<Ranked> {
+        let mut standings = self.standings(key).await;
+        standings.truncate(CONVENIENT_WINNERS);
+        standings
+    }
+
+    /// Writes out the candidates that are winning so far in the guild's results format.
+    async fn live_standings(&self, key: &ElectionKey) -> Vec<String> {
+        let Some(state) = self.election_state(key).await else {
+            return Vec::new();
+        };
+
+        let (format, live_results) = {
+            let config = state.config.read().await;
+            (config.results_format, config.live_results)
+        };
+        let (voters, total_votes) = {
+            let votes = state.votes.read().await;
+            (voter_counts(&votes), votes.values().map(|(_, total, _)| total.load(Ordering::Relaxed)).sum::<usize>())
+        };
+        // Blind voting: the tallies are still kept, but only their sum is shown
+        if !live_results {
+            return match total_votes {
+                0 => Vec::new(),
+                n => vec![locale::fill(state.messages().await.votes_hidden, &[("n", n)])],
+            };
+        }
+        results::render(&self.winners(key).await, &voters, total_votes, format)
+    }
+
+    /// The standings as live results would show them, even when the guild hides them.
+    /// Only for /results private.
+    async fn private_standings(&self, key: &ElectionKey) -> Vec<String> {
+        let Some(state) = self.election_state(key).await else {
+            return Vec::new();
+        };
+
+        let format = state.config.read().await.results_format;
+        let (voters, total_votes) = {
+            let votes = state.votes.read().await;
+            (voter_counts(&votes), votes.values().map(|(_, total, _)| total.load(Ordering::Relaxed)).sum::<usize>())
+        };
+        if total_votes == 0 {
+            return Vec::new();
+        }
+        results::render(&self.winners(key).await, &voters, total_votes, format)
+    }
+
+    /// Get every candidate with its vote total, sorted by number of votes.
+    async fn standings(&self, key: &ElectionKey) -> Vec<Ranked> {
+        match self.election_state(key).await {
+            Some(state) => rank_candidates(&*state.votes.read().await),
+            None => Vec::new(),
+        }
+    }
+
+    /// Posts a message in the election's channel, retrying as configured. Returns `None`
+    /// when the channel can't be found or every attempt failed, after logging why.
+    async fn announce(&self, context: &Context, key: ElectionKey, content: String) -> Option<Message> {
+        let Some(channel_id) = election_channel(context, key) else {
+            eprintln!("Announcement channel '{}' not found in guild {}", BOT_CHANNEL, key);
+            return None;
+        };
+
+        // Queue behind the channel's other messages rather than hitting the rate limit.
+        // Waiting our turn doesn't count against the timeout of each attempt
+        self.pacer.acquire(channel_id).await;
+        let message = self.announce_retry.run("announce", || channel_id.say(context, content.clone())).await?;
+        println!("Successfully sent announcement to guild {}", key);
+        Some(message)
+    }
+
+    /// Updates the most recent announcement in the given guild with the latest suggestions.
+    async fn poll_suggestions_safe(&self, context: &Context, key: &ElectionKey) -> Result<(), String> {
+        let Some(state) = self.election_state(key).await else {
+            return Err("Guild not found in topics".to_string());
+        };
+
+        let suggestions = state
+            .upcoming_topics
+            .read()
+            .await
+            .iter()
+            .map(|p| format!("#{}: {}", p.id, p.idea))
+            .collect::<Vec<String>>();
+
+        self.refresh_board(context, key, |layout| layout.body = suggestions).await
+    }
+
+    /// Updates the most recent poll announcement in the given guild with the latest polling
+    /// numbers.
+    async fn poll_votes(&self, context: Context, key: &ElectionKey) {
+        // Edit the results section in the poll message to have the winning candidates
+        let winners = self.live_standings(key).await;
+
+        match self.refresh_board(&context, key, |layout| layout.body = winners).await {
+            Ok(()) => {
+                println!("Successfully updated vote results for guild {}", key);
+            },
+            Err(e) => {
+                eprintln!("Failed to update vote results for guild {}: {}", key, e);
+            }
+        }
+    }
+
+    /// Whether the guild's current election hides who proposed each candidate.
+    async fn anonymous_proposals(&self, key: &ElectionKey) -> bool {
+        match self.election_state(key).await {
+            Some(state) => state.election.read().await.as_ref().is_some_and(|e| e.anonymous_proposals),
+            None => false,
+        }
+    }
+
+    /// Checks whether the vote is currently in the voting period.
+    async fn in_vote_period(&self, key: &ElectionKey) -> bool {
+        match self.election_state(key).await {
+            Some(state) => *state.phase.read().await == Phase::Voting,
+            None => false,
+        }
+    }
+
+    /// Works out which candidate a reaction on the reaction voting board stands for.
+    /// Returns None for reactions elsewhere, by the bot itself or with other emoji.
+    async fn reaction_vote_target(&self, ctx: &Context, reaction: &Reaction) -> Option<(ElectionKey, Arc<GuildState>, UserId, usize)> {
+        let guild_id = reaction.guild_id?;
+        let user_id = reaction.user_id.filter(|&u| u != ctx.cache.current_user().id)?;
+        let ReactionType::Unicode(emoji) = &reaction.emoji else {
+            return None;
+        };
+        let index = NUMBER_EMOJI.iter().position(|e| e == emoji)?;
+
+        let key = self.election_key(guild_id, reaction.channel_id).await;
+        let state = self.election_state(&key).await?;
+        let candidate_id = match &*state.reaction_board.read().await {
+            Some((message_id, candidates)) if *message_id == reaction.message_id => *candidates.get(index)?,
+            _ => return None,
+        };
+
+        Some((key, state, user_id, candidate_id))
+    }
+
+    /// Builds the private summary of a user's own votes, with one retract button per candidate.
+    async fn my_votes_summary(&self, state: &GuildState, user_id: UserId) -> (String, Vec<CreateActionRow>) {
+        let mut own_votes = state
+            .votes
+            .read()
+            .await
+            .iter()
+            .filter_map(|(id, (name, _, voters))| {
+                voters.get(&user_id).map(|v| (*id, name.clone(), v.load(Ordering::Relaxed)))
+            })
+            .collect::<Vec<(usize, String, usize)>>();
+        own_votes.sort_by_key(|(id, _, _)| *id);
+
+        if own_votes.is_empty() {
+            return (format!("{} You haven't cast any votes in this election.", state.theme().await.ballot), Vec::new());
+        }
+
+        let points_left = match state.points.read().await.get(&user_id) {
+            Some(points) => points.load(Ordering::Relaxed),
+            None => member_starting_credits(state, user_id).await,
+        };
+
+        let currency = state.currency().await;
+        let lines = own_votes
+            .iter()
+            .map(|(id, name, v)| format!("#{}: {} — {} votes ({} {})", id, name, v, voting::cost(*v), currency))
+            .collect::<Vec<String>>();
+
+        let buttons = own_votes
+            .iter()
+            .take(MAX_BUTTONS_PER_ROW * MAX_BUTTON_ROWS)
+            .map(|(id, _, _)| {
+                CreateButton::new(format!("{}{}", RETRACT_BUTTON_PREFIX, id))
+                    .label(format!("Retract #{}", id))
+                    .style(ButtonStyle::Danger)
+            })
+            .collect::<Vec<CreateButton>>();
+        let rows = buttons
+            .chunks(MAX_BUTTONS_PER_ROW)
+            .map(|row| CreateActionRow::Buttons(row.to_vec()))
+            .collect();
+
+        let footer = format!("\n\n{} remaining: {}", capitalize(&currency), points_left);
+        let content = truncate_lines(&format!("{} **Your votes:**\n", state.theme().await.ballot), &lines, DISCORD_MESSAGE_LIMIT - footer.chars().count());
+        (content + &footer, rows)
+    }
+
+    // ===== INTERACTION RESPONSE HELPERS =====
+
+    /// Send an ephemeral (private) response to the user
+    async fn send_ephemeral_response(&self, ctx: &Context, command: &CommandInteraction, content: &str) {
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true)
+        );
+
+        if let Err(why) = command.create_response(&ctx.http, response).await {
+            eprintln!("Failed to send ephemeral response: {}", why);
+        }
+    }
+
+    /// Defer the response for long-running operations
+    async fn defer_response(&self, ctx: &Context, command: &CommandInteraction, ephemeral: bool) -> bool {
+        let response = if ephemeral {
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true))
+        } else {
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new())
+        };
+
+        match command.create_response(&ctx.http, response).await {
+            Ok(()) => true,
+            Err(why) => {
+                eprintln!("Failed to defer response: {}", why);
+                false
+            }
+        }
+    }
+
+    /// Send a follow-up message with guaranteed delivery (fallback to error message)
+    async fn send_followup_guaranteed(&self, ctx: &Context, command: &CommandInteraction, content: &str) {
+        let fallback_msg = format!("{} Operation completed but response delivery failed. Please check the announcements channel.", theme::WARNING);
+
+        match tokio::time::timeout(
+            std::time::Duration::from_secs(8),
+            command.create_followup(ctx, CreateInteractionResponseFollowup::new().content(content))
+        ).await {
+            Ok(Ok(_)) => {
+                println!("Successfully sent follow-up response to user: {}", command.user.id);
+                return;
+            },
+            Ok(Err(why)) => {
+                eprintln!("Discord API error in follow-up, trying fallback: {}", why);
+            },
+            Err(_) => {
+                eprintln!("Follow-up response timeout, trying fallback message");
+            }
+        }
+
+        // Fallback attempt with error message
+        if let Err(why) = tokio::time::timeout(
+            std::time::Duration::from_secs(3),
+            command.create_followup(ctx, CreateInteractionResponseFollowup::new().content(fallback_msg))
+        ).await {
+            eprintln!("Critical: Failed to send any follow-up response: {:?}", why);
+        }
+    }
+
+    // ===== COMMAND HANDLERS WITH PROPER RESPONSE PATTERNS =====
+
+    async fn handle_prop_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let option_str = |name: &str| command.data.options.iter()
+            .find(|opt| opt.name == name)
+            .and_then(|opt| opt.value.as_str());
+        let link = option_str("link").map(str::trim).filter(|link| !link.is_empty()).map(str::to_string);
+        let idea = match option_str("idea") {
+            Some(idea) if !idea.trim().is_empty() => idea.trim().to_string(),
+            None => {
+                self.open_proposal_form(ctx, command, link).await;
+                return;
+            },
+            _ => {
+                self.send_ephemeral_response(ctx, command, &format!("{} Please provide a valid proposal idea!", theme::ERROR)).await;
+                return;
+            },
+        };
+
+        let key = match command.guild_id {
+            Some(guild_id) => Some(self.election_key(guild_id, command.channel_id).await),
+            None => None,
+        };
+
+        // In anonymous elections the reply is private, since it shows who ran /prop
+        let anonymous = match &key {
+            Some(key) => self.anonymous_proposals(key).await,
+            None => false,
+        };
+
+        // Defer response since announcing and state updates might take time
+        if !self.defer_response(ctx, command, anonymous).await {
+            eprintln!("Failed to defer response for /prop command from user: {}", command.user.id);
+            return;
+        }
+
+        println!("Processing /prop command for user: {} with idea: {}", command.user.id, idea);
+
+        // Execute with timeout protection
+        let (result, cost) = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_prop(ctx, key, &command.user, Submission { idea: idea.clone(), description: None, link })
+        ).await {
+            Ok(Ok(reply)) => (reply.outcome, reply.cost),
+            Ok(Err(why)) => (why.to_string(), None),
+            Err(_) => {
+                eprintln!("Timeout processing /prop command for user: {}", command.user.id);
+                (format!("{} Operation timed out, but your proposal '{}' may have been recorded. Please check the announcements channel.", theme::WAITING, idea), None)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+        if let Some(cost) = cost {
+            let followup = CreateInteractionResponseFollowup::new().content(cost).ephemeral(true);
+            if let Err(why) = command.create_followup(ctx, followup).await {
+                eprintln!("Failed to tell user {} what their proposal cost: {}", command.user.id, why);
+            }
+        }
+        println!("Completed /prop command processing for user: {}", command.user.id);
+    }
+
+    /// Opens the /prop form, which takes a title, a longer description and a link. The
+    /// link field is filled in when /prop was given one.
+    async fn open_proposal_form(&self, ctx: &Context, command: &CommandInteraction, link: Option<String>) {
+        let title = CreateInputText::new(InputTextStyle::Short, "Title", PROPOSAL_FORM_TITLE)
+            .placeholder("Shown in the candidate list and the results")
+            .max_length(MAX_PROPOSAL_LEN as u16);
+        let description = CreateInputText::new(InputTextStyle::Paragraph, "Description", PROPOSAL_FORM_DESCRIPTION)
+            .placeholder("Shown to members who ask for details with /details")
+            .max_length(MAX_DESCRIPTION_LEN as u16);
+        let link_input = CreateInputText::new(InputTextStyle::Short, "Image or link", PROPOSAL_FORM_LINK)
+            .placeholder("https://… shown with /details, e.g. a logo for a design vote")
+            .max_length(MAX_LINK_LEN as u16)
+            .required(false);
+        let link_input = match link {
+            Some(link) => link_input.value(link),
+            None => link_input,
+        };
+        let modal = CreateModal::new(PROPOSAL_FORM, "Propose a candidate").components(vec![
+            CreateActionRow::InputText(title),
+            CreateActionRow::InputText(description),
+            CreateActionRow::InputText(link_input),
+        ]);
+
+        if let Err(why) = command.create_response(&ctx.http, CreateInteractionResponse::Modal(modal)).await {
+            eprintln!("Failed to open the /prop form for user {}: {}", command.user.id, why);
+        }
+    }
+
+    /// Suggests candidates for the `id` option of /vote and /unvote as it's typed. /unvote
+    /// only suggests candidates the member has votes for.
+    async fn handle_autocomplete(&self, ctx: &Context, autocomplete: &CommandInteraction) {
+        let Some(guild_id) = autocomplete.guild_id else {
+            return;
+        };
+        let Some(focused) = autocomplete.data.autocomplete().filter(|option| option.name == "id") else {
+            return;
+        };
+        let key = self.election_key(guild_id, autocomplete.channel_id).await;
+        let Some(state) = self.election_state(&key).await else {
+            return;
+        };
+
+        let own_only = autocomplete.data.name == "unvote";
+        let candidates = state
+            .votes
+            .read()
+            .await
+            .iter()
+            .filter(|(_, (_, _, voters))| !own_only || voters.contains_key(&autocomplete.user.id))
+            .map(|(id, (name, _, _))| (*id, name.clone()))
+            .collect::<Vec<(usize, String)>>();
+
+        let response = candidate_suggestions(candidates, focused.value)
+            .into_iter()
+            .fold(CreateAutocompleteResponse::new(), |response, (id, name)| {
+                let label = format!("#{}: {}", id, name).chars().take(MAX_CHOICE_NAME_LEN).collect::<String>();
+                response.add_int_choice(label, id as i64)
+            });
+        if let Err(why) = autocomplete.create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response)).await {
+            eprintln!("Failed to suggest candidates for /{}: {}", autocomplete.data.name, why);
+        }
+    }
+
+    async fn handle_modal(&self, ctx: &Context, modal: &ModalInteraction) {
+        match modal.data.custom_id.as_str() {
+            PROPOSAL_FORM => self.handle_proposal_form(ctx, modal).await,
+            other => eprintln!("Received unknown modal submission: {}", other),
+        }
+    }
+
+    /// Handles a submitted /prop form, replying the way /prop does.
+    async fn handle_proposal_form(&self, ctx: &Context, modal: &ModalInteraction) {
+        let field = |custom_id: &str| {
+            modal.data.components
+                .iter()
+                .flat_map(|row| &row.components)
+                .find_map(|component| match component {
+                    ActionRowComponent::InputText(input) if input.custom_id == custom_id => input.value.clone(),
+                    _ => None,
+                })
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let submission = Submission {
+            idea: field(PROPOSAL_FORM_TITLE).unwrap_or_default(),
+            description: field(PROPOSAL_FORM_DESCRIPTION),
+            link: field(PROPOSAL_FORM_LINK),
+        };
+
+        let key = match modal.guild_id {
+            Some(guild_id) => Some(self.election_key(guild_id, modal.channel_id).await),
+            None => None,
+        };
+
+        // In anonymous elections the reply is private, since it shows who proposed
+        let anonymous = match &key {
+            Some(key) => self.anonymous_proposals(key).await,
+            None => false,
+        };
+        let defer = CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(anonymous));
+        if let Err(why) = modal.create_response(&ctx.http, defer).await {
+            eprintln!("Failed to defer response to the /prop form from user {}: {}", modal.user.id, why);
+            return;
+        }
+
+        let idea = submission.idea.clone();
+        let (result, cost) = if idea.is_empty() {
+            (format!("{} Please provide a valid proposal idea!", theme::ERROR), None)
+        } else {
+            match tokio::time::timeout(
+                std::time::Duration::from_secs(12),
+                self.slash_prop(ctx, key, &modal.user, submission)
+            ).await {
+                Ok(Ok(reply)) => (reply.outcome, reply.cost),
+                Ok(Err(why)) => (why.to_string(), None),
+                Err(_) => {
+                    eprintln!("Timeout processing the /prop form for user: {}", modal.user.id);
+                    (format!("{} Operation timed out, but your proposal '{}' may have been recorded. Please check the announcements channel.", theme::WAITING, idea), None)
+                }
+            }
+        };
+
+        if let Err(why) = modal.create_followup(&ctx.http, CreateInteractionResponseFollowup::new().content(result)).await {
+            eprintln!("Failed to reply to the /prop form from user {}: {}", modal.user.id, why);
+        }
+        if let Some(cost) = cost {
+            let followup = CreateInteractionResponseFollowup::new().content(cost).ephemeral(true);
+            if let Err(why) = modal.create_followup(&ctx.http, followup).await {
+                eprintln!("Failed to tell user {} what their proposal cost: {}", modal.user.id, why);
+            }
+        }
+    }
+
+    async fn handle_editprop_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let proposal_id = command.data.options.first()
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_i64())
+            .filter(|&v| v > 0)
+            .unwrap_or(0) as usize;
+
+        let text = match command.data.options.get(1)
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_str()) {
+            Some(text) if !text.trim().is_empty() => text.trim().to_string(),
+            _ => {
+                self.send_ephemeral_response(ctx, command, &format!("{} Please provide the new proposal text!", theme::ERROR)).await;
+                return;
+            },
+        };
+
+        if proposal_id == 0 {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please provide a valid proposal number (1 or higher)!", theme::ERROR)).await;
+            return;
+        }
+
+        // Defer response since refreshing the suggestions board might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /editprop command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_editprop(ctx, command, proposal_id, text)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /editprop command for user: {}", command.user.id);
+                format!("{} Operation timed out, but your edit may have been recorded. Please check the announcements channel.", theme::WAITING)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_merge_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let proposal_id = |name: &str| command.data.options.iter()
+            .find(|opt| opt.name == name)
+            .and_then(|opt| opt.value.as_i64())
+            .filter(|&v| v > 0)
+            .unwrap_or(0) as usize;
+        let (keep, drop) = (proposal_id("keep"), proposal_id("drop"));
+
+        if keep == 0 || drop == 0 {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please provide valid proposal numbers (1 or higher)!", theme::ERROR)).await;
+            return;
+        }
+
+        // Defer response since refreshing the suggestions board might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /merge command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_merge(ctx, command, keep, drop)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /merge command for user: {}", command.user.id);
+                format!("{} Operation timed out, but the merge may have been applied. Please check the announcements channel.", theme::WAITING)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_restore_command(&self, ctx: &Context, command: &CommandInteraction) {
+        // Defer response since refreshing the suggestions board might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /restore command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_restore(ctx, command)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /restore command for user: {}", command.user.id);
+                format!("{} Operation timed out, but the proposal may have been restored. Please check the announcements channel.", theme::WAITING)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_lock_proposals_command(&self, ctx: &Context, command: &CommandInteraction) {
+        // Defer response since posting the announcement might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /lock-proposals command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_lock_proposals(ctx, command)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /lock-proposals command for user: {}", command.user.id);
+                format!("{} Operation timed out, but proposals may have been locked. Please check the announcements channel.", theme::WAITING)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_vote_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let votes = command.data.options.first()
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_i64())
+            .filter(|&v| v > 0 && v <= MAX_VOTES_CEILING as i64)
+            .unwrap_or(0) as usize;
+
+        let candidate_id = command.data.options.get(1)
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_i64())
+            .filter(|&v| v >= 0)
+            .unwrap_or(-1) as isize;
+
+        if votes == 0 {
+            self.send_ephemeral_response(ctx, command, &CommandError::OutOfRange { what: "Number of votes", min: 1, max: MAX_VOTES_CEILING as i64, unit: "" }.to_string()).await;
+            return;
+        }
+
+        if candidate_id < 0 {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please provide a valid candidate ID (0 or higher)!", theme::ERROR)).await;
+            return;
+        }
+
+        // Defer response since updating the live results might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /vote command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_vote(ctx, command, votes, candidate_id as usize)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /vote command for user: {}", command.user.id);
+                format!("{} Operation timed out, but your vote may have been cast. Please check `/myvotes`.", theme::WAITING)
+            }
+        };
+        // Private, since the confirmation includes the voter's remaining credits
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_votemulti_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let pairs = match command.data.options.first()
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_str())
+            .map(parse_vote_pairs) {
+            Some(Ok(pairs)) => pairs,
+            Some(Err(why)) => {
+                self.send_ephemeral_response(ctx, command, &why.to_string()).await;
+                return;
+            },
+            None => {
+                self.send_ephemeral_response(ctx, command, &format!("{} Please provide votes like `1:3 4:2` (candidate ID:votes)!", theme::ERROR)).await;
+                return;
+            },
+        };
+
+        // Defer response since updating the live results might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /votemulti command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_votemulti(ctx, command, pairs)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /votemulti command for user: {}", command.user.id);
+                format!("{} Operation timed out, but your votes may have been cast. Please check `/myvotes`.", theme::WAITING)
+            }
+        };
+        // Private, since the confirmation includes the voter's remaining credits
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_vote_all_command(&self, ctx: &Context, command: &CommandInteraction) {
+        // Defer response since updating the live results might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /vote-all command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_vote_all(ctx, command)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /vote-all command for user: {}", command.user.id);
+                format!("{} Operation timed out, but your votes may have been cast. Please check `/myvotes`.", theme::WAITING)
+            }
+        };
+        // Private, since the confirmation includes the voter's remaining credits
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_simulate_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let votes = match command.data.options.first()
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_str())
+            .map(parse_simulated_votes) {
+            Some(Ok(votes)) => votes,
+            Some(Err(why)) => {
+                self.send_ephemeral_response(ctx, command, &why.to_string()).await;
+                return;
+            },
+            None => {
+                self.send_ephemeral_response(ctx, command, &format!("{} Please provide vote counts like `3 3 2`!", theme::ERROR)).await;
+                return;
+            },
+        };
+
+        let result = error::render(self.slash_simulate(command, votes).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_list_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let (content, rows) = match self.slash_list(ctx, command).await {
+            Ok(page) => page,
+            Err(why) => (why.to_string(), Vec::new()),
+        };
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(rows)
+                .ephemeral(true)
+        );
+
+        if let Err(why) = command.create_response(&ctx.http, response).await {
+            eprintln!("Failed to respond to /list: {}", why);
+        }
+    }
+
+    /// Handles the Prev/Next buttons of /list by showing the requested page in place.
+    async fn handle_list_button(&self, ctx: &Context, component: &ComponentInteraction) {
+        // Buttons from before /list could be sorted carry no order, and meant ID order
+        let parsed = component.data.custom_id
+            .strip_prefix(LIST_PAGE_PREFIX)
+            .and_then(|rest| {
+                let mut parts = rest.split(':');
+                let page = parts.next()?.parse::<usize>().ok()?;
+                let issued = parts.next()?.parse::<i64>().ok()?;
+                let sort = match parts.next() {
+                    Some(sort) => ListSort::parse(sort)?,
+                    None => ListSort::Id,
+                };
+                Some((page, issued, sort))
+            });
+        let Some((page, issued, sort)) = parsed else {
+            eprintln!("Received unknown component interaction: {}", component.data.custom_id);
+            return;
+        };
+        let Some(guild_id) = component.guild_id else {
+            return;
+        };
+        let key = self.election_key(guild_id, component.channel_id).await;
+
+        let (content, rows) = if schedule::now_unix() - issued > LIST_PAGE_TTL_SECS {
+            (format!("{} This list has expired. Run `/list` again to see the latest candidates.", theme::WAITING), Vec::new())
+        } else {
+            match self.list_page(ctx, key, &component.user, page, issued, sort).await {
+                Ok(page) => page,
+                Err(why) => (why.to_string(), Vec::new()),
+            }
+        };
+        let response = CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(rows)
+        );
+
+        if let Err(why) = component.create_response(&ctx.http, response).await {
+            eprintln!("Failed to update /list message: {}", why);
+        }
+    }
+
+    async fn handle_leaderboard_command(&self, ctx: &Context, command: &CommandInteraction) {
+        // Posted publicly as recognition, but without pinging the members it lists
+        let response = match self.slash_leaderboard(command).await {
+            Ok(content) => CreateInteractionResponseMessage::new()
+                .content(content)
+                .allowed_mentions(CreateAllowedMentions::new()),
+            Err(why) => CreateInteractionResponseMessage::new()
+                .content(why.to_string())
+                .ephemeral(true),
+        };
+
+        if let Err(why) = command.create_response(&ctx.http, CreateInteractionResponse::Message(response)).await {
+            eprintln!("Failed to respond to /leaderboard: {}", why);
+        }
+    }
+
+    async fn handle_results_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let result = match command.data.options.first().map(|opt| opt.name.as_str()) {
+            Some("private") => error::render(self.slash_private_results(ctx, command).await),
+            _ => format!("{} Please choose `private`!", theme::ERROR),
+        };
+        // Private, since the results may be hidden from everyone else
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_standings_command(&self, ctx: &Context, command: &CommandInteraction) {
+        // Posted publicly so the results are back in view, without pinging proposers listed in them
+        let response = match self.slash_standings(command).await {
+            Ok(content) => CreateInteractionResponseMessage::new()
+                .content(content)
+                .allowed_mentions(CreateAllowedMentions::new()),
+            Err(why) => CreateInteractionResponseMessage::new()
+                .content(why.to_string())
+                .ephemeral(true),
+        };
+
+        if let Err(why) = command.create_response(&ctx.http, CreateInteractionResponse::Message(response)).await {
+            eprintln!("Failed to respond to /standings: {}", why);
+        }
+    }
+
+    async fn handle_status_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let result = error::render(self.slash_status(command).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_history_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let page = command.data.options.first()
+            .and_then(|opt| opt.value.as_i64())
+            .filter(|&v| v > 0)
+            .unwrap_or(1) as usize;
+
+        let result = error::render(self.slash_history(command, page).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_analytics_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let result = error::render(self.slash_analytics(ctx, command).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_export_matrix_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let response = match self.slash_export_matrix(ctx, command).await {
+            Ok((summary, attachment)) => CreateInteractionResponseMessage::new()
+                .content(summary)
+                .add_file(attachment)
+                .ephemeral(true),
+            Err(why) => CreateInteractionResponseMessage::new()
+                .content(why.to_string())
+                .ephemeral(true),
+        };
+
+        if let Err(why) = command.create_response(&ctx.http, CreateInteractionResponse::Message(response)).await {
+            eprintln!("Failed to respond to /export-matrix: {}", why);
+        }
+    }
+
+    async fn handle_debug_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let result = error::render(self.slash_debug(ctx, command).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_whoami_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let result = error::render(self.slash_whoami(ctx, command).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_abstain_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let result = error::render(self.slash_abstain(command).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_unvote_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let candidate_id = command.data.options.first()
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_i64())
+            .filter(|&v| v > 0)
+            .unwrap_or(0) as usize;
+
+        if candidate_id == 0 {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please provide a valid candidate ID (1 or higher)!", theme::ERROR)).await;
+            return;
+        }
+
+        // Defer response since updating the live results might take time
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /unvote command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(12),
+            self.slash_unvote(ctx, command, candidate_id)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /unvote command for user: {}", command.user.id);
+                format!("{} Operation timed out, but your votes may have been retracted. Please check `/myvotes`.", theme::WAITING)
+            }
+        };
+        // Private, since the confirmation includes the voter's remaining credits
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_details_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let candidate_id = command.data.options.first()
+            .map(|opt| &opt.value)
+            .and_then(|val| val.as_i64())
+            .filter(|&v| v > 0)
+            .unwrap_or(0) as usize;
+
+        if candidate_id == 0 {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please provide a valid candidate ID (1 or higher)!", theme::ERROR)).await;
+            return;
+        }
+
+        let result = error::render(self.slash_details(command, candidate_id).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_myvotes_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let Some(guild_id) = command.guild_id else {
+            self.send_ephemeral_response(ctx, command, &CommandError::NotInGuild.to_string()).await;
+            return;
+        };
+
+        let key = self.election_key(guild_id, command.channel_id).await;
+        let Some(state) = self.election_state(&key).await else {
+            self.send_ephemeral_response(ctx, command, &CommandError::NotConfigured.to_string()).await;
+            return;
+        };
+
+        if let Err(why) = self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await {
+            self.send_ephemeral_response(ctx, command, &why.to_string()).await;
+            return;
+        }
+
+        if !self.in_vote_period(&key).await {
+            self.send_ephemeral_response(ctx, command, &CommandError::NoVoteInProgress.to_string()).await;
+            return;
+        }
+
+        // Votes are private, so the summary and its buttons are only shown to the caller
+        let (content, rows) = self.my_votes_summary(&state, command.user.id).await;
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(rows)
+                .ephemeral(true)
+        );
+
+        if let Err(why) = command.create_response(&ctx.http, response).await {
+            eprintln!("Failed to respond to /myvotes: {}", why);
+        }
+    }
+
+    async fn handle_reset_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let Some(guild_id) = command.guild_id else {
+            self.send_ephemeral_response(ctx, command, &CommandError::NotInGuild.to_string()).await;
+            return;
+        };
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            self.send_ephemeral_response(ctx, command, &format!(
+                "{}", CommandError::MissingPermission { action: "reset elections" }
+            )).await;
+            return;
+        }
+
+        // Nothing happens until the admin confirms with the button
+        let row = CreateActionRow::Buttons(vec![
+            CreateButton::new(RESET_CONFIRM_BUTTON)
+                .label("Yes, wipe everything")
+                .style(ButtonStyle::Danger),
+            CreateButton::new(RESET_CANCEL_BUTTON)
+                .label("Cancel")
+                .style(ButtonStyle::Secondary),
+        ]);
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(format!("{} This wipes the current election, all proposals, votes and credits, and any scheduled election. Settings are kept. Are you sure?", theme::WARNING))
+                .components(vec![row])
+                .ephemeral(true)
+        );
+
+        if let Err(why) = command.create_response(&ctx.http, response).await {
+            eprintln!("Failed to respond to /reset: {}", why);
+        }
+    }
+
+    async fn handle_component(&self, ctx: &Context, component: &ComponentInteraction) {
+        match component.data.custom_id.as_str() {
+            RESET_CONFIRM_BUTTON | RESET_CANCEL_BUTTON => self.handle_reset_button(ctx, component).await,
+            STOP_CONFIRM_BUTTON | STOP_CANCEL_BUTTON => self.handle_stop_button(ctx, component).await,
+            id if id.starts_with(APPROVE_PROPOSAL_PREFIX) || id.starts_with(REJECT_PROPOSAL_PREFIX) => {
+                self.handle_moderation_button(ctx, component).await
+            },
+            id if id.starts_with(LIST_PAGE_PREFIX) => self.handle_list_button(ctx, component).await,
+            _ => self.handle_retract_button(ctx, component).await,
+        }
+    }
+
+    /// Handles the confirmation buttons attached to /reset
+    async fn handle_reset_button(&self, ctx: &Context, component: &ComponentInteraction) {
+        let Some(guild_id) = component.guild_id else {
+            return;
+        };
+
+        let notice = if component.data.custom_id == RESET_CANCEL_BUTTON {
+            "Reset cancelled, nothing was changed.".to_string()
+        } else if !check_admin_permission!(self, ctx, guild_id, component.user) {
+            format!("{} You no longer have permission to reset elections.", theme::ERROR)
+        } else {
+            let key = self.election_key(guild_id, component.channel_id).await;
+            error::render(self.reset_guild(ctx, key, component.user.id).await)
+        };
+
+        let response = CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(notice)
+                .components(Vec::new())
+        );
+
+        if let Err(why) = component.create_response(&ctx.http, response).await {
+            eprintln!("Failed to update /reset message: {}", why);
+        }
+    }
+
+    /// Whether /stop would end the guild's vote rather than open it, and the guild
+    /// wants that confirmed first.
+    async fn stop_needs_confirmation(&self, key: &ElectionKey) -> bool {
+        let Some(state) = self.election_state(key).await else {
+            return false;
+        };
+
+        state.config.read().await.confirm_stop && stop_ends_vote(&state).await
+    }
+
+    /// Shows the current leaders and asks the admin to confirm ending the vote
+    async fn ask_stop_confirmation(&self, ctx: &Context, command: &CommandInteraction, key: &ElectionKey) {
+        let leaders = self.live_standings(key).await;
+        let leaders = if leaders.is_empty() {
+            "No votes have been cast yet.".to_string()
+        } else {
+            format!("Current leaders:\n{}", leaders.join("\n"))
+        };
+
+        let row = CreateActionRow::Buttons(vec![
+            CreateButton::new(STOP_CONFIRM_BUTTON)
+                .label("Yes, end the vote")
+                .style(ButtonStyle::Danger),
+            CreateButton::new(STOP_CANCEL_BUTTON)
+                .label("Keep voting")
+                .style(ButtonStyle::Secondary),
+        ]);
+        let content = format!(
+            "{} This ends the vote, announces the winners and resets everyone's credits.\n\n{}\n\nEnd the election now?",
+            theme::WARNING,
+            leaders
+        );
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(vec![row])
+                .ephemeral(true)
+        );
+
+        if let Err(why) = command.create_response(&ctx.http, response).await {
+            eprintln!("Failed to respond to /stop: {}", why);
+        }
+    }
+
+    /// Handles the confirmation buttons attached to a /stop that would end the vote
+    async fn handle_stop_button(&self, ctx: &Context, component: &ComponentInteraction) {
+        let Some(guild_id) = component.guild_id else {
+            return;
+        };
+        let key = self.election_key(guild_id, component.channel_id).await;
+
+        let refusal = if component.data.custom_id == STOP_CANCEL_BUTTON {
+            Some("Voting continues, nothing was changed.".to_string())
+        } else if !check_admin_permission!(self, ctx, guild_id, component.user) {
+            Some(format!("{} You no longer have permission to stop elections.", theme::ERROR))
+        } else {
+            match self.election_state(&key).await {
+                // The button may outlive the vote it was shown for
+                Some(state) if !stop_ends_vote(&state).await => Some(CommandError::NoVoteInProgress.to_string()),
+                Some(_) => None,
+                None => Some(CommandError::NotConfigured.to_string()),
+            }
+        };
+
+        if let Some(notice) = refusal {
+            let response = CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(notice)
+                    .components(Vec::new())
+            );
+            if let Err(why) = component.create_response(&ctx.http, response).await {
+                eprintln!("Failed to update /stop message: {}", why);
+            }
+            return;
+        }
+
+        // Announcing the winners can take longer than Discord waits for a reply
+        if let Err(why) = component.defer(&ctx.http).await {
+            eprintln!("Failed to defer /stop confirmation: {}", why);
+            return;
+        }
+
+        let notice = match tokio::time::timeout(
+            std::time::Duration::from_secs(15),
+            self.slash_stop_internal(ctx, key)
+        ).await {
+            Ok(result) => {
+                self.announce_blocked(ctx, key, &result).await;
+                error::render(result)
+            },
+            Err(_) => {
+                eprintln!("Timeout finalizing election for guild {}", key);
+                format!("{} Election stop operation timed out. Please check the announcements channel for status.", theme::WAITING)
+            }
+        };
+
+        let edit = EditInteractionResponse::new().content(notice).components(Vec::new());
+        if let Err(why) = component.edit_response(&ctx.http, edit).await {
+            eprintln!("Failed to update /stop message: {}", why);
+        }
+    }
+
+    /// Tells the community why `/stop` didn't open voting, if the guild turned the
+    /// notice on. Repeated attempts within `BLOCKED_NOTICE_DEBOUNCE` post it once.
+    async fn announce_blocked(&self, ctx: &Context, key: ElectionKey, result: &CommandResult) {
+        let Err(CommandError::NotEnoughCandidates { needed, have }) = result else {
+            return;
+        };
+        let Some(state) = self.election_state(&key).await else {
+            return;
+        };
+        if !state.config.read().await.blocked_notice {
+            return;
+        }
+
+        if !state.claim_blocked_notice().await {
+            return;
+        }
+
+        let channel_id = election_channel(ctx, key);
+        let Some(channel_id) = channel_id else {
+            return;
+        };
+
+        let content = blocked_notice(state.messages().await, *needed, *have);
+        if let Err(why) = channel_id.say(ctx, content).await {
+            eprintln!("Failed to post blocked notice in guild {}: {}", key, why);
+        }
+    }
+
+    /// Wipes an election's state back to idle, keeping the guild's settings. Resetting
+    /// the guild-wide election also drops any scheduled election.
+    async fn reset_guild(&self, ctx: &Context, key: ElectionKey, admin: UserId) -> CommandResult {
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        self.stop_countdown(&state).await;
+        self.cancel_ping(&state).await;
+        self.cancel_proposal_batch(&state).await;
+        if key.channel_id.is_none() {
+            if let Some(timer) = state.schedule_timer.write().await.take() {
+                timer.abort();
+            }
+            *state.scheduled_start.write().await = None;
+        }
+
+        state.update_votes(|voting| voting.reset()).await;
+        *state.last_outcome.write().await = None;
+        state.upcoming_topics.write().await.clear();
+        *state.election.write().await = None;
+        *state.reaction_board.write().await = None;
+        *state.runoff_decided.write().await = None;
+        state.candidate_authors.write().await.clear();
+        state.descriptions.write().await.clear();
+        state.links.write().await.clear();
+        state.removed_proposals.write().await.clear();
+        state.pending_proposals.write().await.clear();
+        state.last_candidate_id.store(0, Ordering::Relaxed);
+        self.results.write().await.remove(&key);
+        self.persist().await;
+
+        let now = schedule::now_unix();
+        println!("Guild {} was reset by {} at {}", key, admin, now);
+        if self.announce(ctx, key, format!("{} The election state was reset by <@{}> at <t:{}:F>.", state.theme().await.reset, admin, now)).await.is_none() {
+            eprintln!("Failed to announce the reset in guild {}", key);
+        }
+
+        Ok(format!("{} Everything was reset. Start a new election with `/start`.", theme::SUCCESS))
+    }
+
+    /// Handles the approve and reject buttons on proposals waiting for moderation
+    async fn handle_moderation_button(&self, ctx: &Context, component: &ComponentInteraction) {
+        let custom_id = component.data.custom_id.as_str();
+        let (approve, target) = match (custom_id.strip_prefix(APPROVE_PROPOSAL_PREFIX), custom_id.strip_prefix(REJECT_PROPOSAL_PREFIX)) {
+            (Some(target), _) => (true, parse_moderation_target(target)),
+            (_, Some(target)) => (false, parse_moderation_target(target)),
+            _ => (false, None),
+        };
+        let (Some((ticket, channel_id)), Some(guild_id)) = (target, component.guild_id) else {
+            eprintln!("Received unknown component interaction: {}", custom_id);
+            return;
+        };
+
+        // The buttons are posted in the moderation channel, so they carry the
+        // channel of the election they belong to, if it isn't the guild's
+        let key = ElectionKey { guild_id, channel_id };
+        let Some(state) = self.election_state(&key).await else {
+            return;
+        };
+
+        // Anyone who can see the channel can click; only admins get to decide
+        if !check_admin_permission!(self, ctx, guild_id, component.user) {
+            let response = CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(CommandError::MissingPermission { action: "moderate proposals" }.to_string())
+                    .ephemeral(true)
+            );
+            if let Err(why) = component.create_response(&ctx.http, response).await {
+                eprintln!("Failed to respond to moderation button: {}", why);
+            }
+            return;
+        }
+
+        let pending = {
+            let mut pending = state.pending_proposals.write().await;
+            pending.iter().position(|p| p.ticket == ticket).map(|i| pending.remove(i))
+        };
+
+        let notice = match pending {
+            None => format!("{} This proposal was already handled, or its election has ended.", theme::WARNING),
+            Some(proposal) if approve => {
+                let submission = Submission { idea: proposal.idea.clone(), description: proposal.description.clone(), link: proposal.link.clone() };
+                let result = self.accept_proposal(ctx, key, &state, submission, proposal.author).await;
+                match result {
+                    Ok(added) => format!("{}\nProposed by **{}**, approved by <@{}>.", added, proposal.author_name, component.user.id),
+                    Err(why) => {
+                        refund_proposal(&state, &proposal).await;
+                        format!("{}\nThe proposal '{}' by **{}** was dropped.", why, proposal.idea, proposal.author_name)
+                    },
+                }
+            },
+            Some(proposal) => {
+                refund_proposal(&state, &proposal).await;
+                format!("🚫 Rejected by <@{}>: **{}** proposed: {}", component.user.id, proposal.author_name, proposal.idea)
+            },
+        };
+
+        let response = CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(notice)
+                .components(Vec::new())
+        );
+
+        if let Err(why) = component.create_response(&ctx.http, response).await {
+            eprintln!("Failed to update moderation message: {}", why);
+        }
+    }
+
+    /// Handles the retract buttons attached to /myvotes
+    async fn handle_retract_button(&self, ctx: &Context, component: &ComponentInteraction) {
+        let Some(candidate_id) = component.data.custom_id
+            .strip_prefix(RETRACT_BUTTON_PREFIX)
+            .and_then(|id| id.parse::<usize>().ok()) else {
+            eprintln!("Received unknown component interaction: {}", component.data.custom_id);
+            return;
+        };
+
+        let Some(guild_id) = component.guild_id else {
+            return;
+        };
+
+        let key = self.election_key(guild_id, component.channel_id).await;
+        let Some(state) = self.election_state(&key).await else {
+            return;
+        };
+
+        let notice = if let Err(why) = self.check_rate_limit(guild_id, component.user.id, CommandKind::Vote).await {
+            why.to_string()
+        } else {
+            match state.update_votes(|voting| voting.retract(component.user.id, candidate_id)).await {
+                Ok(prev_votes) => {
+                    self.poll_votes(ctx.clone(), &key).await;
+                    format!("{} Retracted {} votes for candidate #{}.", theme::SUCCESS, prev_votes, candidate_id)
+                },
+                Err(why) => why.to_string(),
+            }
+        };
+
+        // Refresh the summary in place so the remaining buttons stay accurate
+        let (content, rows) = self.my_votes_summary(&state, component.user.id).await;
+        let response = CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(format!("{}\n\n{}", notice, content))
+                .components(rows)
+        );
+
+        if let Err(why) = component.create_response(&ctx.http, response).await {
+            eprintln!("Failed to update /myvotes message: {}", why);
+        }
+    }
+
+    async fn handle_points_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let result = error::render(self.slash_points(ctx, command).await);
+        self.send_ephemeral_response(ctx, command, &result).await; // Points are private
+    }
+
+    async fn handle_delegate_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let Some(target) = command.data.options.first()
+            .and_then(|opt| opt.value.as_user_id()) else {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please choose a member to give credits to!", theme::ERROR)).await;
+            return;
+        };
+
+        let credits = command.data.options.get(1)
+            .and_then(|opt| opt.value.as_i64())
+            .filter(|&c| c > 0)
+            .unwrap_or(0) as usize;
+
+        if credits == 0 {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please give at least 1 credit!", theme::ERROR)).await;
+            return;
+        }
+
+        let result = error::render(self.slash_delegate(ctx, command, target, credits).await);
+        // Private, since the confirmation includes the giver's remaining credits
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_start_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let parsed = parse_election(&command.data.options)
+            .and_then(|(prompt, options)| Ok((prompt, options, parse_seeded_candidates(&command.data.options, &options, command.user.id)?)));
+        let (prompt, options, seeds) = match parsed {
+            Ok(election) => election,
+            Err(why) => {
+                self.send_ephemeral_response(ctx, command, &why.to_string()).await;
+                return;
+            },
+        };
+
+        let preview = command.data.options.iter()
+            .find(|opt| opt.name == "preview")
+            .and_then(|opt| opt.value.as_bool())
+            .unwrap_or(false);
+
+        if preview {
+            let result = error::render(self.slash_start_preview(ctx, command, &prompt, options).await);
+            self.send_ephemeral_response(ctx, command, &result).await;
+            return;
+        }
+
+        self.launch_election(ctx, command, prompt, options, seeds).await;
+    }
+
+    /// Starts an election from /start or /template start, replying once it's announced.
+    async fn launch_election(&self, ctx: &Context, command: &CommandInteraction, prompt: String, options: ElectionOptions, seeds: Option<SeededCandidates>) {
+        // Defer response since starting an election might take time
+        if !self.defer_response(ctx, command, false).await {
+            eprintln!("Failed to defer response for /start command from user: {}", command.user.id);
+            return;
+        }
+
+        println!("Processing /start command for user: {} with prompt: {}", command.user.id, prompt);
+
+        // Execute with timeout protection - start command can be complex
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(15),
+            self.slash_start(ctx, command, prompt.clone(), options, seeds)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /start command for user: {}", command.user.id);
+                format!("{} Election start operation timed out. Please check the announcements channel and try again if needed.", theme::WAITING)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+        println!("Completed /start command processing for user: {}", command.user.id);
+    }
+
+    async fn handle_schedule_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let Some(action) = command.data.options.first() else {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please choose `create` or `cancel`!", theme::ERROR)).await;
+            return;
+        };
+
+        let CommandDataOptionValue::SubCommand(options) = &action.value else {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please choose `create` or `cancel`!", theme::ERROR)).await;
+            return;
+        };
+
+        let result = error::render(self.slash_schedule(ctx, command, &action.name, options).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_template_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let Some(action) = command.data.options.first() else {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please choose `save`, `start`, `delete` or `list`!", theme::ERROR)).await;
+            return;
+        };
+
+        let CommandDataOptionValue::SubCommand(options) = &action.value else {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please choose `save`, `start`, `delete` or `list`!", theme::ERROR)).await;
+            return;
+        };
+
+        // Starting goes through the same announcement and reply as /start
+        if action.name == "start" {
+            match self.find_template(command, options).await {
+                Ok(template) => self.launch_election(ctx, command, template.prompt, template.options, None).await,
+                Err(why) => self.send_ephemeral_response(ctx, command, &why.to_string()).await,
+            }
+            return;
+        }
+
+        let result = error::render(self.slash_template(ctx, command, &action.name, options).await);
+        self.send_ephemeral_response(ctx, command, &result).await;
+    }
+
+    async fn handle_stop_command(&self, ctx: &Context, command: &CommandInteraction) {
+        // Ending the vote can't be undone, so admins confirm it with a button first
+        if let Some(guild_id) = command.guild_id {
+            let key = self.election_key(guild_id, command.channel_id).await;
+            if self.stop_needs_confirmation(&key).await && check_admin_permission!(self, ctx, guild_id, command.user) {
+                self.ask_stop_confirmation(ctx, command, &key).await;
+                return;
+            }
+        }
+
+        // Defer response since stopping might take time to calculate results
+        if !self.defer_response(ctx, command, false).await {
+            eprintln!("Failed to defer response for /stop command from user: {}", command.user.id);
+            return;
+        }
+
+        println!("Processing /stop command for user: {}", command.user.id);
+
+        // Execute with timeout protection
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(15),
+            self.slash_stop(ctx, command)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /stop command for user: {}", command.user.id);
+                format!("{} Election stop operation timed out. Please check the announcements channel for status.", theme::WAITING)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+        println!("Completed /stop command processing for user: {}", command.user.id);
+    }
+
+    async fn handle_nextround_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let count = command.data.options.first()
+            .and_then(|opt| opt.value.as_i64())
+            .filter(|&n| (2..=MAX_NEXT_ROUND as i64).contains(&n))
+            .unwrap_or(0) as usize;
+
+        if count == 0 {
+            self.send_ephemeral_response(ctx, command, &CommandError::OutOfRange { what: "The number of candidates", min: 2, max: MAX_NEXT_ROUND as i64, unit: "" }.to_string()).await;
+            return;
+        }
+
+        // Defer response since starting an election might take time
+        if !self.defer_response(ctx, command, false).await {
+            eprintln!("Failed to defer response for /nextround command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            std::time::Duration::from_secs(15),
+            self.slash_nextround(ctx, command, count)
+        ).await {
+            Ok(result) => error::render(result),
+            Err(_) => {
+                eprintln!("Timeout processing /nextround command for user: {}", command.user.id);
+                format!("{} Election start operation timed out. Please check the announcements channel and try again if needed.", theme::WAITING)
+            }
+        };
+
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_cancel_command(&self, ctx: &Context, command: &CommandInteraction) {
+        // Private, since a cancelled election shouldn't draw attention to itself
+        if !self.defer_response(ctx, command, true).await {
+            eprintln!("Failed to defer response for /cancel command from user: {}", command.user.id);
+            return;
+        }
+
+        let result = error::render(self.slash_cancel(ctx, command).await);
+        self.send_followup_guaranteed(ctx, command, &result).await;
+    }
+
+    async fn handle_config_command(&self, ctx: &Context, command: &CommandInteraction) {
+        let Some(setting) = command.data.options.first() else {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please choose a setting to change!", theme::ERROR)).await;
+            return;
+        };
+
+        let CommandDataOptionValue::SubCommand(options) = &setting.value else {
+            self.send_ephemeral_response(ctx, command, &format!("{} Please choose a setting to change!", theme::ERROR)).await;
+            return;
+        };
+
+        if setting.name == "show" {
+            self.handle_config_show(ctx, command).await;
+            return;
+        }
+
+        let result = error::render(self.slash_config(ctx, command, &setting.name, options).await);
+        self.send_ephemeral_response(ctx, command, &result).await; // Settings changes are private
+    }
+
+    async fn handle_config_show(&self, ctx: &Context, command: &CommandInteraction) {
+        let embed = match self.slash_config_show(ctx, command).await {
+            Ok(embed) => embed,
+            Err(why) => {
+                self.send_ephemeral_response(ctx, command, &why.to_string()).await;
+                return;
+            },
+        };
+
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .ephemeral(true)
+        );
+        if let Err(why) = command.create_response(&ctx.http, response).await {
+            eprintln!("Failed to respond to /config show: {}", why);
+        }
+    }
+
+    // ===== SLASH COMMAND HANDLERS =====
+
+    /// Takes a proposal from /prop or the /prop form.
+    async fn slash_prop(&self, ctx: &Context, key: Option<ElectionKey>, user: &User, submission: Submission) -> Result<ProposalReply, CommandError> {
+        let Some(key) = key else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        // Check rate limiting
+        self.check_rate_limit(key.guild_id, user.id, CommandKind::Propose).await?;
+
+        self.submit_proposal(ctx, key, submission, user.id, user.display_name()).await
+    }
+
+    /// Runs a proposal through the checks of /prop, then adds it to the election or
+    /// queues it for moderation. Used by /prop and by proposals posted over HTTP.
+    async fn submit_proposal(&self, ctx: &Context, key: ElectionKey, submission: Submission, author: UserId, author_name: &str) -> Result<ProposalReply, CommandError> {
+        submission.check()?;
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        check_new_proposal(&state, &submission.idea).await?;
+
+        // Proposing can cost credits. They're taken up front, so the same credits can't
+        // also be spent on votes while a moderator reviews the proposal
+        let (moderated, cost) = {
+            let config = state.config.read().await;
+            (config.moderation_channel.is_some(), config.proposal_cost)
+        };
+        let cost = if author == ctx.cache.current_user().id { 0 } else { cost };
+        let remaining = if cost > 0 { Some(state.update_votes(|voting| voting.charge_proposal(author, cost)).await?) } else { None };
+
+        // Moderated servers hold proposals back until a moderator approves them
+        let result = if moderated {
+            self.queue_for_moderation(ctx, key, submission, author, author_name, cost).await
+        } else {
+            self.accept_proposal(ctx, key, &state, submission, author).await
+        };
+
+        settle_proposal_cost(&state, result, author, cost, remaining).await
+    }
+
+    /// Takes proposals posted to the HTTP server, one at a time, for as long as the bot runs.
+    async fn receive_external_proposals(self, mut proposals: mpsc::Receiver<ProposalRequest>) {
+        while let Some(request) = proposals.recv().await {
+            let reply = self.external_proposal(request.guild_id, request.proposal).await;
+            // The client may have hung up in the meantime
+            let _ = request.reply.send(reply);
+        }
+    }
+
+    /// Submits a proposal posted over HTTP, as if its author had run /prop. Without an
+    /// author it's credited to the bot itself, under the configured web form name.
+    async fn external_proposal(&self, guild_id: GuildId, proposal: ExternalProposal) -> Reply {
+        let Some(ctx) = self.context.read().await.clone() else {
+            return Reply::NotReady;
+        };
+        if self.guild_state(&guild_id).await.is_none() {
+            return Reply::UnknownGuild;
+        }
+
+        let idea = proposal.idea.trim().to_string();
+        if idea.is_empty() {
+            return Reply::Rejected(format!("{} Please provide a valid proposal idea!", theme::ERROR));
+        }
+        let description = proposal.description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+        let link = proposal.link.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+
+        let (author, author_name) = match proposal.author {
+            Some(user_id) => match guild_id.member(&ctx, user_id).await {
+                Ok(member) => (user_id, member.display_name().to_string()),
+                Err(_) => return Reply::Rejected(format!("{} The author <@{}> isn't a member of this server!", theme::ERROR, user_id)),
+            },
+            None => (ctx.cache.current_user().id, intake::default_author_name()),
+        };
+        let key = match proposal.channel {
+            Some(channel_id) => self.election_key(guild_id, channel_id).await,
+            None => ElectionKey::guild(guild_id),
+        };
+
+        println!("Received external proposal for guild {} credited to {}: {}", key, author, idea);
+        match self.submit_proposal(&ctx, key, Submission { idea, description, link }, author, &author_name).await {
+            // The reply only goes back to whoever posted the proposal
+            Ok(ProposalReply { outcome, cost: Some(cost) }) => Reply::Accepted(format!("{}\n{}", outcome, cost)),
+            Ok(ProposalReply { outcome, cost: None }) => Reply::Accepted(outcome),
+            Err(why) => Reply::Rejected(why.to_string()),
+        }
+    }
+
+    /// Adds a proposal to the running election and announces it, checking again that
+    /// it can still join. Used by /prop, and by moderators approving a queued proposal.
+    async fn accept_proposal(&self, ctx: &Context, key: ElectionKey, state: &GuildState, submission: Submission, author: UserId) -> CommandResult {
+        let open_voting = check_new_proposal(state, &submission.idea).await?;
+        let idea = submission.idea.clone();
+        let has_description = submission.description.is_some();
+        let has_link = submission.link.is_some();
+
+        println!("Attempting to store proposal '{}' for guild {}", idea, key);
+        let id = store_submission(state, submission, author, open_voting).await?;
+        println!("Successfully stored proposal #{} '{}' by {} for guild {}", id, idea, author, key);
+        state.record_participant(author).await;
+
+        if open_voting {
+            self.poll_votes(ctx.clone(), &key).await;
+        } else if let Err(e) = self.poll_suggestions_safe(ctx, &key).await {
+            // The proposal is stored either way, e.g. when the announcement was deleted
+            eprintln!("Failed to update the suggestions board in guild {}: {}", key, e);
+        }
+
+        // Announce in channel (non-blocking)
+        let ballot = state.theme().await.ballot;
+        let (mut announcement, line) = proposal_announcement(&ballot, id, &idea);
+        if has_description {
+            announcement.push_str(&format!("\nRead its description with `/details {}`", id));
+        } else if has_link {
+            announcement.push_str(&format!("\nSee its image or link with `/details {}`", id));
+        }
+        if open_voting {
+            announcement.push_str(&format!("\nVote for it with `/vote <votes> {}`", id));
+        }
+        let queued = QueuedAnnouncement { full: announcement, line, has_details: has_description || has_link, votable: open_voting };
+        self.announce_proposal(ctx, key, state, queued).await;
+
+        Ok(format!("{} Proposal #{} '{}' added successfully!", theme::SUCCESS, id, idea))
+    }
+
+    /// Announces a new proposal, or holds it to go out together with the others made
+    /// within the guild's batch window.
+    async fn announce_proposal(&self, ctx: &Context, key: ElectionKey, state: &GuildState, proposal: QueuedAnnouncement) {
+        let window = state.config.read().await.proposal_batch_secs;
+        if window == 0 {
+            if self.announce(ctx, key, proposal.full).await.is_none() {
+                eprintln!("Failed to announce new proposal in guild {} - channel not found or no permissions", key);
+            }
+            return;
+        }
+
+        // The first proposal of a batch starts its timer; the rest join it
+        if state.queue_announcement(proposal).await {
+            let handler = self.clone();
+            let ctx = ctx.clone();
+            let timer = tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(window)).await;
+                handler.flush_proposal_batch(&ctx, key).await;
+            });
+            *state.batch_timer.write().await = Some(timer.abort_handle());
+        }
+    }
+
+    /// Posts the proposals held back since the batch window opened.
+    async fn flush_proposal_batch(&self, ctx: &Context, key: ElectionKey) {
+        let Some(state) = self.election_state(&key).await else {
+            return;
+        };
+        let batch = state.take_announcement_batch().await;
+        let count = batch.len();
+
+        let Some(announcement) = batch::render(&state.theme().await.ballot, batch) else {
+            return;
+        };
+        if self.announce(ctx, key, announcement).await.is_none() {
+            eprintln!("Failed to announce {} new proposals in guild {} - channel not found or no permissions", count, key);
+        }
+    }
+
+    /// Holds a proposal back for review, posting it to the moderation channel with
+    /// buttons to approve or reject it.
+    async fn queue_for_moderation(&self, ctx: &Context, key: ElectionKey, submission: Submission, author: UserId, author_name: &str, cost: usize) -> CommandResult {
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        let Some(channel_id) = state.config.read().await.moderation_channel else {
+            return Err(CommandError::ModerationUnavailable);
+        };
+        let Submission { idea, description, link } = submission;
+        let ticket = state.last_ticket.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut content = format!("📝 **{}** proposed: {}", author_name, idea);
+        if let Some(description) = &description {
+            content = format!("{}\n{}", content, description.lines().map(|line| format!("> {}", line)).collect::<Vec<String>>().join("\n"));
+        }
+        if let Some(link) = &link {
+            content = format!("{}\n🔗 {}", content, link);
+        }
+        state.pending_proposals.write().await.push(PendingProposal {
+            ticket,
+            idea: idea.clone(),
+            description,
+            link,
+            author,
+            author_name: author_name.to_string(),
+            cost,
+        });
+
+        let row = CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("{}{}", APPROVE_PROPOSAL_PREFIX, moderation_target(ticket, key)))
+                .label("Approve")
+                .style(ButtonStyle::Success),
+            CreateButton::new(format!("{}{}", REJECT_PROPOSAL_PREFIX, moderation_target(ticket, key)))
+                .label("Reject")
+                .style(ButtonStyle::Danger),
+        ]);
+        let message = CreateMessage::new()
+            .content(content)
+            .components(vec![row]);
+
+        if let Err(why) = channel_id.send_message(ctx, message).await {
+            // Nobody could ever review it, so don't leave it queued
+            eprintln!("Failed to post proposal for moderation in channel {}: {}", channel_id, why);
+            state.pending_proposals.write().await.retain(|p| p.ticket != ticket);
+            return Err(CommandError::ModerationUnavailable);
+        }
+
+        Ok(format!("{} Your proposal '{}' was sent to the moderators and will be announced once approved.", theme::SUCCESS, idea))
+    }
+
+    async fn slash_editprop(&self, ctx: &Context, command: &CommandInteraction, proposal_id: usize, text: String) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // Check rate limiting
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Propose).await?;
+
+        // Check if the new text is too long
+        if text.len() > MAX_PROPOSAL_LEN {
+            return Err(CommandError::TooLong { what: "Proposal ideas", max: MAX_PROPOSAL_LEN });
+        }
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let is_admin = check_admin_permission!(self, ctx, guild_id, command.user);
+        state.phase.read().await.check_edit(is_admin)?;
+
+        {
+            let mut topics = state.upcoming_topics.write().await;
+            let Some(index) = topics.iter().position(|p| p.id == proposal_id) else {
+                return Err(CommandError::NoSuchProposal(proposal_id));
+            };
+            let proposal = &topics[index];
+
+            if proposal.author != command.user.id && !is_admin {
+                return Err(CommandError::NotProposalAuthor);
+            }
+
+            if topics.iter().enumerate().any(|(i, p)| i != index && p.idea == text) {
+                return Err(CommandError::DuplicateProposal(text));
+            }
+
+            println!("Editing proposal #{} in guild {}: '{}' -> '{}'", proposal_id, guild_id, topics[index].idea, text);
+            topics[index].idea = text.clone();
+        }
+
+        // Update suggestions display (only if election is active)
+        if let Err(e) = self.poll_suggestions_safe(ctx, &key).await {
+            eprintln!("No active election to update: {}", e);
+        }
+
+        Ok(format!("{} Proposal #{} updated to '{}'!", theme::SUCCESS, proposal_id, text))
+    }
+
+    async fn slash_merge(&self, ctx: &Context, command: &CommandInteraction, keep: usize, drop: usize) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "merge proposals" });
+        }
+
+        // Merging during voting would also have to merge the tallies
+        if self.in_vote_period(&key).await {
+            return Err(CommandError::VoteInProgress { action: "Proposals cannot be merged" });
+        }
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let (kept, dropped) = merge_proposals(&mut *state.upcoming_topics.write().await, keep, drop)?;
+        let description = state.descriptions.write().await.remove(&drop);
+        let link = state.links.write().await.remove(&drop);
+        println!("Merged proposal #{} into #{} in guild {}: '{}'", drop, keep, guild_id, dropped.idea);
+
+        self.audit.record(AuditEntry {
+            at: schedule::now_unix(),
+            guild_id,
+            actor: command.user.id,
+            action: AuditAction::Merge { kept: keep, dropped: drop, dropped_text: dropped.idea.clone() },
+        }).await;
+
+        // Update suggestions display (only if election is active)
+        if let Err(e) = self.poll_suggestions_safe(ctx, &key).await {
+            eprintln!("No active election to update: {}", e);
+        }
+
+        let message = format!("{} Merged proposal #{} ('{}') into #{} ('{}'). Undo it with `/restore`.", theme::SUCCESS, drop, dropped.idea, keep, kept);
+        state.remember_removed(RemovedProposal { proposal: dropped, description, link }).await;
+        Ok(message)
+    }
+
+    /// Stops the election taking proposals, without opening voting yet, so admins can
+    /// review them with /merge and /editprop first.
+    async fn slash_lock_proposals(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "lock proposals" });
+        }
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        state.update_votes(|voting| voting.lock_proposals()).await?;
+        println!("Proposals locked in guild {} by {}", key, command.user.id);
+
+        let proposals = state.upcoming_topics.read().await.len();
+        let announcement = format!(
+            "🔒 Proposals are now closed, with {} in the running. Voting opens once they've been reviewed.",
+            proposals
+        );
+        if self.announce(ctx, key, announcement).await.is_none() {
+            eprintln!("Failed to announce locked proposals in guild {} - channel not found or no permissions", key);
+        }
+
+        Ok(format!("{} Proposals are locked. Open voting with `/stop` when you're ready.", theme::SUCCESS))
+    }
+
+    async fn slash_restore(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "restore proposals" });
+        }
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        if !matches!(*state.phase.read().await, Phase::Suggestion | Phase::ProposalsLocked) {
+            return Err(CommandError::VoteInProgress { action: "Proposals cannot be restored" });
+        }
+
+        let restored = state.restore_removed().await?;
+        println!("Restored proposal #{} in guild {}: '{}'", restored.0, guild_id, restored.1);
+
+        self.audit.record(AuditEntry {
+            at: schedule::now_unix(),
+            guild_id,
+            actor: command.user.id,
+            action: AuditAction::Restore { restored: restored.0, text: restored.1.clone() },
+        }).await;
+
+        if let Err(e) = self.poll_suggestions_safe(ctx, &key).await {
+            eprintln!("No active election to update: {}", e);
+        }
+
+        Ok(format!("{} Restored proposal #{} ('{}').", theme::SUCCESS, restored.0, restored.1))
+    }
+
+    async fn slash_vote(&self, ctx: &Context, command: &CommandInteraction, votes: usize, candidate_id: usize) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // Check rate limiting
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Vote).await?;
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        if state.election.read().await.as_ref().is_some_and(|e| e.reaction_voting) {
+            return Err(CommandError::ReactionVoting);
+        }
+        check_membership(&state, command.member.as_ref().and_then(|member| member.joined_at), schedule::now_unix()).await?;
+
+        // The phase is checked under the write lock, so a vote can never land after
+        // the election was finalized
+        let (cast, remaining) = state.update_votes(|voting| voting.cast(command.user.id, candidate_id, votes)).await?;
+        state.record_voter(command.user.id).await;
+
+        // Update results (non-blocking)
+        self.poll_votes(ctx.clone(), &key).await;
+
+        Ok(vote_reply(candidate_id, votes, cast, remaining, &state.currency().await))
+    }
+
+    async fn slash_leaderboard(&self, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+
+        if !state.config.read().await.leaderboard {
+            return Err(CommandError::LeaderboardDisabled);
+        }
+
+        let ranking = |members: Vec<(UserId, usize)>, unit: &str| {
+            if members.is_empty() {
+                return "Nobody yet".to_string();
+            }
+            members
+                .iter()
+                .enumerate()
+                .map(|(i, (user_id, score))| format!("{}. <@{}>: {} {}", i + 1, user_id, score, unit))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        let leaderboard = state.leaderboard.read().await;
+        Ok(format!(
+            "{} **Most active members**\n\n**Top proposers:**\n{}\n\n**Top voters:**\n{}",
+            state.theme().await.winners,
+            ranking(leaderboard.top_proposers(), "proposals"),
+            ranking(leaderboard.top_voters(), "credits spent")
+        ))
+    }
+
+    async fn slash_history(&self, command: &CommandInteraction, page: usize) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+
+        let history = state.history.read().await;
+        if history.is_empty() {
+            return Ok(format!("{} No elections have ended here yet.", theme::WAITING));
+        }
+        let pages = history.page_count();
+        if page > pages {
+            return Err(CommandError::InvalidInput(format!(
+                "There {} only {} {} of history!",
+                if pages == 1 { "is" } else { "are" },
+                pages,
+                if pages == 1 { "page" } else { "pages" }
+            )));
+        }
+
+        let guild_theme = state.theme().await;
+        let entries = history
+            .page(page)
+            .iter()
+            .map(|election| {
+                let winners = match election.winners.as_slice() {
+                    [] => "no winners".to_string(),
+                    names if names.len() > HISTORY_WINNERS_SHOWN => format!(
+                        "{} and {} more",
+                        names[..HISTORY_WINNERS_SHOWN].join(", "),
+                        names.len() - HISTORY_WINNERS_SHOWN
+                    ),
+                    names => names.join(", "),
+                };
+                format!(
+                    "**{}** (<t:{}:d>)\n{} {}",
+                    election.prompt.as_deref().unwrap_or("Untitled election"),
+                    election.ended_at,
+                    guild_theme.winners,
+                    winners
+                )
+            })
+            .collect::<Vec<String>>();
+
+        Ok(format!(
+            "{} **Past elections** (page {} of {})\n\n{}",
+            guild_theme.ballot,
+            page,
+            pages,
+            entries.join("\n\n")
+        ))
+    }
+
+    async fn slash_standings(&self, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+        state.phase.read().await.check_vote()?;
+
+        // Spaced out per election rather than per member, so several members can't take turns
+        {
+            let mut last_posted = state.last_standings.write().await;
+            if let Some(wait) = last_posted.and_then(|posted| STANDINGS_INTERVAL.checked_sub(posted.elapsed())) {
+                return Err(CommandError::RateLimited { wait_secs: wait.as_secs().max(1) });
+            }
+            *last_posted = Some(Instant::now());
+        }
+
+        Ok(self.standings_report(&key, &state).await)
+    }
+
+    /// The current standings as /standings reposts them.
+    async fn standings_report(&self, key: &ElectionKey, state: &GuildState) -> String {
+        let messages = state.messages().await;
+        let prefix = format!("{} {}\n", state.theme().await.ballot, messages.results_heading);
+        let standings = self.live_standings(key).await;
+        if standings.is_empty() {
+            return format!("{}{}", prefix, messages.no_votes);
+        }
+        truncate_lines(&prefix, &standings, DISCORD_MESSAGE_LIMIT)
+    }
+
+    /// Shows a spectator the full standings, privately, whether or not the guild
+    /// shows live results.
+    async fn slash_private_results(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        if !self.is_spectator(ctx, guild_id, command.user.id).await {
+            return Err(CommandError::NotSpectator);
+        }
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+        state.phase.read().await.check_vote()?;
+
+        let messages = state.messages().await;
+        let mut prefix = format!("{} {}\n", state.theme().await.ballot, messages.results_heading);
+        if !state.config.read().await.live_results {
+            prefix.push_str("🔒 Hidden from members until the winners are announced.\n");
+        }
+        let standings = self.private_standings(&key).await;
+        if standings.is_empty() {
+            return Ok(format!("{}{}", prefix, messages.no_votes));
+        }
+        Ok(truncate_lines(&prefix, &standings, DISCORD_MESSAGE_LIMIT))
+    }
+
+    async fn slash_status(&self, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+
+        let Some((prompt, participants, abstained)) = state
+            .election
+            .read()
+            .await
+            .as_ref()
+            .map(|e| (e.prompt.clone(), e.participants.len(), e.abstentions.len()))
+        else {
+            return Err(CommandError::NoElection);
+        };
+
+        let phase = match *state.phase.read().await {
+            Phase::Voting if state.runoff_decided.read().await.is_some() => {
+                format!("Runoff vote between {} tied candidates", state.votes.read().await.len())
+            },
+            Phase::Voting => format!("Voting on {} candidates", state.votes.read().await.len()),
+            Phase::ProposalsLocked => format!("Proposals locked for review ({} in the running)", state.upcoming_topics.read().await.len()),
+            _ => format!("Collecting proposals ({} so far)", state.upcoming_topics.read().await.len()),
+        };
+
+        let mut lines = vec![
+            format!("{} **{}**", state.theme().await.ballot, prompt),
+            format!("**Phase:** {}", phase),
+        ];
+        if let Some(deadline) = *state.phase_deadline.read().await {
+            lines.push(format!("**Ends:** <t:{}:R>", deadline));
+        }
+        lines.push(participation(participants, abstained, Locale::En.messages()));
+
+        Ok(lines.join("\n"))
+    }
+
+    async fn slash_list(&self, ctx: &Context, command: &CommandInteraction) -> Result<(String, Vec<CreateActionRow>), CommandError> {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+
+        let sort = match command.data.options.first().and_then(|opt| opt.value.as_str()) {
+            Some(name) => ListSort::parse(name)
+                .ok_or_else(|| CommandError::InvalidInput("Please sort by id, name or votes!".to_string()))?,
+            None => ListSort::Id,
+        };
+        self.list_page(ctx, key, &command.user, 1, schedule::now_unix(), sort).await
+    }
+
+    /// Renders one page of the candidates (or suggestions), with Prev/Next buttons when
+    /// there's more than one page. Pages start at 1 and are clamped to the last page, in
+    /// case candidates were removed since the list was shown.
+    async fn list_page(&self, ctx: &Context, key: ElectionKey, user: &User, page: usize, issued: i64, sort: ListSort) -> Result<(String, Vec<CreateActionRow>), CommandError> {
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let authors = state.candidate_authors.read().await.clone();
+        let mut candidates = state
+            .votes
+            .read()
+            .await
+            .iter()
+            .map(|(id, (name, _, _))| (*id, name.clone(), authors.get(id).copied()))
+            .collect::<Vec<(usize, String, Option<UserId>)>>();
+
+        let heading = if !candidates.is_empty() {
+            "**Candidates:**"
+        } else {
+            candidates = state
+                .upcoming_topics
+                .read()
+                .await
+                .iter()
+                .map(|p| (p.id, p.idea.clone(), Some(p.author)))
+                .collect();
+            "**Suggestions so far:**"
+        };
+
+        // Anonymous elections still show proposers to admins, so they can act on abuse
+        let ballot = state.theme().await.ballot;
+        let show_authors = !self.anonymous_proposals(&key).await
+            || check_admin_permission!(self, ctx, key.guild_id, user);
+
+        if candidates.is_empty() {
+            return Ok((format!("{} There are no candidates yet. Suggest one with `/prop <idea>`!", ballot), Vec::new()));
+        }
+
+        // Proposals have no votes yet, and blind voting must not leak the standings
+        let ranking = if sort == ListSort::Votes {
+            if *state.phase.read().await != Phase::Voting {
+                return Err(CommandError::InvalidInput("Candidates can only be sorted by votes once voting has started!".to_string()));
+            }
+            if !state.config.read().await.live_results {
+                return Err(CommandError::InvalidInput("The standings are hidden until the election ends, so candidates can't be sorted by votes.".to_string()));
+            }
+            self.standings(&key).await.into_iter().map(|(id, _, _)| id).collect()
+        } else {
+            Vec::new()
+        };
+        sort::sort_rows(&mut candidates, sort, &ranking);
+        let pages = candidates.len().div_ceil(LIST_PAGE_SIZE);
+        let page = page.clamp(1, pages);
+        let descriptions = state.descriptions.read().await;
+        let links = state.links.read().await;
+        let lines = candidates
+            .iter()
+            .skip((page - 1) * LIST_PAGE_SIZE)
+            .take(LIST_PAGE_SIZE)
+            .map(|(id, name, author)| {
+                let author = author.filter(|_| show_authors);
+                list_line(*id, name, author, descriptions.get(id).map(String::as_str), links.contains_key(id))
+            })
+            .collect::<Vec<String>>();
+        drop(descriptions);
+        drop(links);
+
+        if pages == 1 {
+            return Ok((truncate_lines(&format!("{} {}\n", ballot, heading), &lines, DISCORD_MESSAGE_LIMIT), Vec::new()));
+        }
+
+        let footer = format!("\n\nPage {} of {}", page, pages);
+        let content = truncate_lines(&format!("{} {}\n", ballot, heading), &lines, DISCORD_MESSAGE_LIMIT - footer.chars().count());
+        let row = CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("{}{}:{}:{}", LIST_PAGE_PREFIX, page - 1, issued, sort.name()))
+                .label("◀ Prev")
+                .style(ButtonStyle::Secondary)
+                .disabled(page == 1),
+            CreateButton::new(format!("{}{}:{}:{}", LIST_PAGE_PREFIX, page + 1, issued, sort.name()))
+                .label("Next ▶")
+                .style(ButtonStyle::Secondary)
+                .disabled(page == pages),
+        ]);
+        Ok((content + &footer, vec![row]))
+    }
+
+    async fn slash_analytics(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "view analytics" });
+        }
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let analytics = state.analytics.read().await;
+        let mut lines = vec![
+            "📊 **Server analytics:**".to_string(),
+            format!("**Elections completed:** {}", analytics.elections),
+            format!("**Votes counted:** {}", analytics.votes),
+        ];
+        if analytics.elections > 0 {
+            lines.push(format!(
+                "**Participants:** {} in total, {:.1} per election on average",
+                analytics.participants,
+                analytics.participants as f64 / analytics.elections as f64
+            ));
+        }
+        lines.push(format!("**Commands used:** {}", analytics.command_total()));
+        lines.extend(
+            analytics
+                .top_commands()
+                .into_iter()
+                .take(MAX_ANALYTICS_COMMANDS)
+                .map(|(name, uses)| format!("• `/{}`: {}", name, uses)),
+        );
+
+        Ok(lines.join("\n"))
+    }
+
+    /// Builds the vote matrix file for /export-matrix, with the message that comes with it.
+    async fn slash_export_matrix(&self, ctx: &Context, command: &CommandInteraction) -> Result<(String, CreateAttachment), CommandError> {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "export the vote matrix" });
+        }
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        if !state.config.read().await.research_export {
+            return Err(CommandError::ResearchExportDisabled);
+        }
+
+        let format = command.data.options.first()
+            .and_then(|opt| opt.value.as_str())
+            .and_then(MatrixFormat::parse)
+            .unwrap_or_default();
+        let matrix = VoteMatrix::pseudonymize(&*state.votes.read().await);
+        if matrix.voter_count() == 0 {
+            return Err(CommandError::InvalidInput("There are no votes to export in this election yet.".to_string()));
+        }
+
+        println!("{} exported the vote matrix of {} voters in guild {}", command.user.id, matrix.voter_count(), key);
+        self.audit.record(AuditEntry {
+            at: schedule::now_unix(),
+            guild_id,
+            actor: command.user.id,
+            action: AuditAction::ExportMatrix { voters: matrix.voter_count() },
+        }).await;
+
+        let file_name = format!("vote-matrix-{}.{}", schedule::now_unix(), format.name());
+        Ok((
+            format!(
+                "{} Vote matrix of {} voters. Voters are numbered in a fresh random order for each export, so pseudonyms can't be traced to members or matched across exports.",
+                theme::SUCCESS,
+                matrix.voter_count()
+            ),
+            CreateAttachment::bytes(matrix.render(format).into_bytes(), file_name),
+        ))
+    }
+
+    async fn slash_debug(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        // Guild admins manage elections, but internal state is only for whoever runs the bot
+        if !is_bot_owner(ctx, command.user.id).await {
+            return Err(CommandError::NotBotOwner);
+        }
+
+        let elections = self.guild_elections(guild_id).await;
+        if elections.is_empty() {
+            return Err(CommandError::NotConfigured);
+        }
+
+        let mut lines = Vec::new();
+        for (key, state) in elections {
+            let timers = [&state.schedule_timer, &state.reset_timer, &state.countdown_timer, &state.ping_timer, &state.batch_timer];
+            let mut running = 0;
+            for timer in timers {
+                if timer.read().await.is_some() {
+                    running += 1;
+                }
+            }
+            let (candidates, total_votes) = {
+                let votes = state.votes.read().await;
+                (votes.len(), votes.values().map(|(_, count, _)| count.load(Ordering::Relaxed)).sum::<usize>())
+            };
+
+            lines.push(format!(
+                "**{}:** phase {:?}, {} proposals, {} candidates, {} votes, {} timers, results message {}",
+                key.channel_id.map_or_else(|| "Guild election".to_string(), |channel_id| format!("<#{}>", channel_id)),
+                *state.phase.read().await,
+                state.upcoming_topics.read().await.len(),
+                candidates,
+                total_votes,
+                running,
+                if self.results.read().await.contains_key(&key) { "tracked" } else { "not tracked" }
+            ));
+        }
+
+        Ok(truncate_lines(&format!("🛠️ **Election state for guild {}:**\n", guild_id), &lines, DISCORD_MESSAGE_LIMIT))
+    }
+
+    async fn slash_whoami(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        // Evaluates exactly what check_admin_permission! checks
+        let Some(permissions) = self.admin_permissions(ctx, guild_id, command.user.id).await else {
+            return Err(CommandError::CacheUnavailable);
+        };
+
+        let mark = |granted: bool| if granted { theme::SUCCESS } else { theme::ERROR };
+        let mut lines = vec![
+            "🔐 **Your election permissions:**".to_string(),
+            format!("{} Server Owner", mark(permissions.owner)),
+            format!("{} Administrator permission", mark(permissions.administrator)),
+        ];
+        lines.extend(permissions.roles.iter().map(|(role, has_role)| match has_role {
+            Some(has_role) => format!("{} '{}' role", mark(*has_role), role),
+            None => format!("{} '{}' role (this server has no such role)", theme::WARNING, role),
+        }));
+
+        if !permissions.member_found && !permissions.owner {
+            lines.push(format!("{} Your member data couldn't be read, so roles and permissions couldn't be checked. Please try again shortly.", theme::WARNING));
+        }
+
+        lines.push(String::new());
+        lines.push(if permissions.allowed() {
+            "You **can** start, stop and configure elections.".to_string()
+        } else {
+            "You **cannot** start, stop or configure elections. Any one of the above is enough.".to_string()
+        });
+
+        Ok(lines.join("\n"))
+    }
+
+    async fn slash_abstain(&self, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Vote).await?;
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        if !state.abstain(command.user.id).await? {
+            return Ok(format!("{} You've already abstained in this election.", theme::WARNING));
+        }
+
+        Ok(format!("{} You've abstained. You count toward turnout without backing any candidate; voting later takes this back.", theme::SUCCESS))
+    }
+
+    async fn slash_unvote(&self, ctx: &Context, command: &CommandInteraction, candidate_id: usize) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // Check rate limiting
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Vote).await?;
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let (prev_votes, remaining) = state
+            .update_votes(|voting| {
+                let prev_votes = voting.retract(command.user.id, candidate_id)?;
+                Ok::<_, CommandError>((prev_votes, voting.balance(command.user.id)))
+            })
+            .await?;
+
+        // Update results (non-blocking)
+        self.poll_votes(ctx.clone(), &key).await;
+
+        Ok(format!(
+            "{} Retracted {} votes for candidate #{}! {} remaining: {}",
+            theme::SUCCESS, prev_votes, candidate_id, capitalize(&state.currency().await), remaining
+        ))
+    }
+
+    async fn slash_votemulti(&self, ctx: &Context, command: &CommandInteraction, pairs: Vec<(usize, usize)>) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // The whole batch counts as a single command
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Vote).await?;
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        if state.election.read().await.as_ref().is_some_and(|e| e.reaction_voting) {
+            return Err(CommandError::ReactionVoting);
+        }
+        check_membership(&state, command.member.as_ref().and_then(|member| member.joined_at), schedule::now_unix()).await?;
+
+        // Applied all-or-nothing under the write locks
+        let remaining = state.update_votes(|voting| voting.cast_batch(command.user.id, &pairs)).await?;
+        state.record_voter(command.user.id).await;
+
+        // Update results (non-blocking)
+        self.poll_votes(ctx.clone(), &key).await;
+
+        let currency = state.currency().await;
+        let allocations = pairs
+            .iter()
+            .map(|(id, votes)| format!("#{}: {} votes ({} {})", id, votes, voting::cost(*votes), currency))
+            .collect::<Vec<String>>();
+
+        Ok(format!(
+            "{} Cast votes for {} candidates!\n{}\n{} remaining: {}",
+            theme::SUCCESS, pairs.len(), allocations.join("\n"), capitalize(&currency), remaining
+        ))
+    }
+
+    /// Adds the same number of votes to every candidate, as many as the member's
+    /// remaining credits cover, all-or-nothing like /votemulti.
+    async fn slash_vote_all(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // The whole spread counts as a single command
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Vote).await?;
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        if state.election.read().await.as_ref().is_some_and(|e| e.reaction_voting) {
+            return Err(CommandError::ReactionVoting);
+        }
+        check_membership(&state, command.member.as_ref().and_then(|member| member.joined_at), schedule::now_unix()).await?;
+
+        // Worked out and cast under the same write locks, so the balance can't change in between
+        let (pairs, remaining) = state
+            .update_votes(|voting| {
+                let pairs = voting.even_split(command.user.id)?;
+                let remaining = voting.cast_batch(command.user.id, &pairs)?;
+                Ok::<_, CommandError>((pairs, remaining))
+            })
+            .await?;
+        state.record_voter(command.user.id).await;
+
+        // Update results (non-blocking)
+        self.poll_votes(ctx.clone(), &key).await;
+
+        let currency = state.currency().await;
+        let allocations = pairs
+            .iter()
+            .map(|(id, votes)| format!("#{}: {} votes ({} {})", id, votes, voting::cost(*votes), currency))
+            .collect::<Vec<String>>();
+
+        // A long ballot gets a shortened list, but the balance always shows
+        let footer = format!("\n{} remaining: {}", capitalize(&currency), remaining);
+        let prefix = format!("{} Spread your votes over {} candidates!\n", theme::SUCCESS, pairs.len());
+        Ok(truncate_lines(&prefix, &allocations, DISCORD_MESSAGE_LIMIT - footer.chars().count()) + &footer)
+    }
+
+    /// Adds up what the given vote counts would cost against the member's starting
+    /// credits. Nothing is cast, so it works in any phase.
+    async fn slash_simulate(&self, command: &CommandInteraction, votes: Vec<usize>) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+
+        let budget = member_starting_credits(&state, command.user.id).await;
+        let costs = votes.iter().map(|v| voting::cost(*v)).collect::<Vec<usize>>();
+        let total: usize = costs.iter().sum();
+
+        let currency = state.currency().await;
+        let mut lines = vec![format!(
+            "🧮 {} = {} = **{}** {} of {}.",
+            votes.iter().map(|v| format!("{}²", v)).collect::<Vec<String>>().join("+"),
+            costs.iter().map(usize::to_string).collect::<Vec<String>>().join("+"),
+            total,
+            currency,
+            budget
+        )];
+        if total <= budget {
+            lines.push(format!("{} That fits your budget, with {} {} to spare.", theme::SUCCESS, budget - total, currency));
+        } else {
+            lines.push(format!("{} That's {} {} more than your budget.", theme::ERROR, total - budget, currency));
+        }
+
+        let max_votes = state.config.read().await.max_votes;
+        if votes.iter().any(|v| *v > max_votes) {
+            lines.push(format!("{} This server allows at most {} votes per candidate.", theme::WARNING, max_votes));
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    /// Shows a candidate's title and the description it was proposed with, if any.
+    async fn slash_details(&self, command: &CommandInteraction, candidate_id: usize) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+
+        let title = match state.votes.read().await.get(&candidate_id) {
+            Some((name, _, _)) => Some(name.clone()),
+            None => state.upcoming_topics.read().await.iter().find(|p| p.id == candidate_id).map(|p| p.idea.clone()),
+        };
+        let Some(title) = title else {
+            return Err(CommandError::NoSuchCandidate(candidate_id));
+        };
+
+        let mut details = match state.descriptions.read().await.get(&candidate_id) {
+            Some(description) => format!("📄 **#{}: {}**\n{}", candidate_id, title, description),
+            None => format!("📄 **#{}: {}**\nThis candidate was proposed without a description.", candidate_id, title),
+        };
+        // Discord previews the link below the reply, so images show up inline
+        if let Some(link) = state.links.read().await.get(&candidate_id) {
+            details.push_str(&format!("\n🔗 {}", link));
+        }
+        Ok(details)
+    }
+
+    async fn slash_points(&self, _ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Read).await?;
+
+        let credits = member_starting_credits(&state, command.user.id).await;
+        let points_left = state.points.read().await
+            .get(&command.user.id)
+            .map(|a| a.load(Ordering::Relaxed))
+            .unwrap_or(credits);
+
+        // The balance is still shown, so members can see what the next election gives them
+        let currency = state.currency().await;
+        if *state.phase.read().await == Phase::Idle {
+            return Ok(format!("{} You have **{}** {}. There's no election running right now to spend them in.",
+                state.theme().await.ballot, points_left, currency));
+        }
+
+        Ok(format!("{} You have **{}** {} left (out of {}) to spend in this election.",
+            state.theme().await.ballot, points_left, currency, credits))
+    }
+
+    async fn slash_delegate(&self, _ctx: &Context, command: &CommandInteraction, target: UserId, credits: usize) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // Check rate limiting
+        self.check_rate_limit(guild_id, command.user.id, CommandKind::Delegate).await?;
+
+        // Bots never vote, so credits given to them would just be lost
+        if command.data.resolved.users.get(&target).is_some_and(|user| user.bot) {
+            return Err(CommandError::InvalidInput("Credits can't be given to bots!".to_string()));
+        }
+
+        // Safe access to guild data
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        // Both balances change under the same write lock
+        let remaining = state.update_votes(|voting| voting.delegate(command.user.id, target, credits)).await?;
+
+        println!("{} delegated {} credits to {} in guild {}", command.user.id, credits, target, guild_id);
+        self.audit.record(AuditEntry {
+            at: schedule::now_unix(),
+            guild_id,
+            actor: command.user.id,
+            action: AuditAction::Delegate { to: target, credits },
+        }).await;
+
+        let currency = state.currency().await;
+        Ok(format!("{} Gave {} {} to <@{}>. {} remaining: {}", theme::SUCCESS, credits, currency, target, capitalize(&currency), remaining))
+    }
+
+    /// Looks up the template named in a /template subcommand. Admin permission is
+    /// checked when the election starts.
+    async fn find_template(&self, command: &CommandInteraction, options: &[CommandDataOption]) -> Result<ElectionTemplate, CommandError> {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let name = template_name(options)?;
+        let template = state.templates.read().await.get(&name).cloned();
+        template.ok_or(CommandError::NoSuchTemplate(name))
+    }
+
+    async fn slash_template(&self, ctx: &Context, command: &CommandInteraction, action: &str, options: &[CommandDataOption]) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "manage election templates" });
+        }
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let result = match action {
+            "save" => {
+                let name = template_name(options)?;
+                let (prompt, election) = parse_election(options)?;
+                let template = ElectionTemplate { prompt, options: election };
+
+                let mut templates = state.templates.write().await;
+                if templates.contains_key(&name) {
+                    return Err(CommandError::DuplicateTemplate(name));
+                }
+                if templates.len() >= template::MAX_TEMPLATES {
+                    return Err(CommandError::TemplateLimit { max: template::MAX_TEMPLATES });
+                }
+                let summary = template.describe();
+                templates.insert(name.clone(), template);
+                drop(templates);
+
+                format!("{} Saved template `{}` ({}). Start it any time with `/template start name:{}`.", theme::SUCCESS, name, summary, name)
+            },
+            "delete" => {
+                let name = template_name(options)?;
+                if state.templates.write().await.remove(&name).is_none() {
+                    return Err(CommandError::NoSuchTemplate(name));
+                }
+
+                format!("{} Deleted template `{}`.", theme::SUCCESS, name)
+            },
+            "list" => {
+                let templates = state.templates.read().await;
+                if templates.is_empty() {
+                    return Ok("No templates saved yet. Save one with `/template save`.".to_string());
+                }
+
+                let lines = templates
+                    .iter()
+                    .map(|(name, template)| format!("`{}`: {} ({})", name, template.prompt, template.describe()))
+                    .collect::<Vec<String>>();
+                // Nothing changed, so there's nothing to persist
+                return Ok(truncate_lines("**Saved templates:**\n", &lines, DISCORD_MESSAGE_LIMIT));
+            },
+            _ => return Err(CommandError::InvalidInput("Please choose `save`, `start`, `delete` or `list`!".to_string())),
+        };
+
+        // Templates outlive restarts
+        self.persist().await;
+        Ok(result)
+    }
+
+    async fn slash_schedule(&self, ctx: &Context, command: &CommandInteraction, action: &str, options: &[CommandDataOption]) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "schedule an election" });
+        }
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let option_str = |name: &str| options.iter()
+            .find(|opt| opt.name == name)
+            .and_then(|opt| opt.value.as_str())
+            .map(|s| s.trim().to_string());
+
+        match action {
+            "create" => {
+                // A scheduled election belongs to no channel, so it could never be run
+                if state.config.read().await.election_scope == ElectionScope::Channel {
+                    return Err(CommandError::InvalidInput("Elections can't be scheduled while each channel runs its own election. Use `/start` in the channel instead!".to_string()));
+                }
+                let prompt = match option_str("prompt") {
+                    Some(prompt) if !prompt.is_empty() => prompt,
+                    _ => return Err(CommandError::InvalidInput("Please provide a valid election prompt!".to_string())),
+                };
+                if prompt.chars().count() > MAX_PROMPT_LEN {
+                    return Err(CommandError::TooLong { what: "Election prompts", max: MAX_PROMPT_LEN });
+                }
+
+                let now = schedule::now_unix();
+                let start_at = match option_str("time").map(|time| schedule::parse_start_time(&time, now)) {
+                    Some(Ok(start_at)) => start_at,
+                    Some(Err(why)) => return Err(CommandError::InvalidInput(why)),
+                    None => return Err(CommandError::InvalidInput("Please provide a start time!".to_string())),
+                };
+
+                let duration_hours = option_str("duration").as_deref().map(parse_duration_hours).transpose()?;
+
+                {
+                    let mut scheduled = state.scheduled_start.write().await;
+                    if let Some(existing) = scheduled.as_ref() {
+                        return Err(CommandError::AlreadyScheduled { start_at: existing.start_at });
+                    }
+                    *scheduled = Some(ScheduledStart { prompt: prompt.clone(), start_at, duration_hours });
+                }
+
+                let timer = self.spawn_scheduled_start(ctx, guild_id, state.clone(), start_at);
+                *state.schedule_timer.write().await = Some(timer);
+                self.persist().await;
+
+                println!("User {} scheduled election in guild {} for {}: {}", command.user.id, guild_id, start_at, prompt);
+                Ok(format!("{} Election '{}' scheduled to start <t:{}:F> (<t:{}:R>).", theme::SUCCESS, prompt, start_at, start_at))
+            },
+            "cancel" => {
+                let Some(scheduled) = state.scheduled_start.write().await.take() else {
+                    return Err(CommandError::NothingScheduled);
+                };
+                if let Some(timer) = state.schedule_timer.write().await.take() {
+                    timer.abort();
+                }
+                self.persist().await;
+
+                println!("User {} cancelled scheduled election in guild {}", command.user.id, guild_id);
+                Ok(format!("{} Cancelled the election '{}' scheduled for <t:{}:F>.", theme::SUCCESS, scheduled.prompt, scheduled.start_at))
+            },
+            _ => Err(CommandError::InvalidInput("Please choose `create` or `cancel`!".to_string())),
+        }
+    }
+
+    async fn slash_start(&self, ctx: &Context, command: &CommandInteraction, prompt: String, options: ElectionOptions, seeds: Option<SeededCandidates>) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        // Check if guild exists in cache
+        if ctx.cache.guild(guild_id).is_none() {
+            return Err(CommandError::CacheUnavailable);
+        };
+
+        // Check admin permissions with timeout protection
+        let has_permission = tokio::time::timeout(
+            std::time::Duration::from_secs(3),
+            async { check_admin_permission!(self, ctx, guild_id, command.user) }
+        ).await.unwrap_or(false);
+
+        if !has_permission {
+            return Err(CommandError::MissingPermission { action: "start an election" });
+        }
+
+        println!("User {} has permission to start election in guild {}", command.user.id, guild_id);
+
+        self.start_election(ctx, key, prompt, options, seeds).await
+    }
+
+    /// Starts a follow-up election between the top `count` candidates of the election
+    /// that just ended, opening voting on them right away.
+    async fn slash_nextround(&self, ctx: &Context, command: &CommandInteraction, count: usize) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "start an election" });
+        }
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        let (prompt, ideas) = state.next_round(count).await?;
+
+        println!("Starting a follow-up round between the top {} candidates in guild {}", count, key);
+        let seeds = SeededCandidates { ideas, vote_now: true, author: command.user.id };
+        self.start_election(ctx, key, prompt, ElectionOptions::default(), Some(seeds)).await
+    }
+
+    /// Shows an admin the announcement `/start` would post, without posting it or
+    /// touching any election state.
+    async fn slash_start_preview(&self, ctx: &Context, command: &CommandInteraction, prompt: &str, options: ElectionOptions) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "start an election" });
+        }
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let credits = match options.credits {
+            Some(credits) => credits,
+            None => state.config.read().await.starting_credits.unwrap_or(STARTING_POINTS),
+        };
+        let voting = if options.reaction_voting { "number reactions" } else { "`/vote`" };
+        let proposers = if options.anonymous_proposals { "hidden" } else { "shown" };
+
+        let config = state.config.read().await.clone();
+        Ok(format!(
+            "👀 **Preview only, nothing was posted.** Run `/start` without `preview` to post it.\n\n{}\n\n⚙️ {} credits per member · voting with {} · proposers {}",
+            election_announcement(prompt, &options, &state.theme().await, &config, true, state.messages().await).render(),
+            credits,
+            voting,
+            proposers
+        ))
+    }
+
+    /// Starts a new election and posts its announcement. Permissions must be checked by the caller.
+    async fn start_election(&self, ctx: &Context, key: ElectionKey, prompt: String, options: ElectionOptions, seeds: Option<SeededCandidates>) -> CommandResult {
+        let duration_hours = options.duration_hours;
+
+        // Two admins starting at once would each stop the other's election and post
+        // their own announcement, so only one start runs at a time
+        let Some(guild_state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+        let Some(_starting) = StartingGuard::acquire(&guild_state.starting) else {
+            return Err(CommandError::ElectionStarting);
+        };
+
+        // Read the snapshot before touching the running election, so a bad file changes nothing
+        let snapshot = match guild_state.config.read().await.credit_snapshot {
+            SnapshotMode::Off => None,
+            mode => Some(
+                CreditSnapshot::load(&self.snapshot_dir, key.guild_id, mode)
+                    .map_err(|problems| CommandError::CreditSnapshot { problems })?
+            ),
+        };
+        if let Some(snapshot) = &snapshot {
+            println!("Loaded starting credits of {} members for guild {}", snapshot.member_count(), key);
+        }
+
+        // Voting can't open right away on fewer candidates than /stop would accept
+        let vote_now = seeds.as_ref().is_some_and(|seeds| seeds.vote_now);
+        if let Some(seeds) = seeds.as_ref().filter(|seeds| seeds.vote_now) {
+            let min_candidates = guild_state.config.read().await.min_candidates;
+            if seeds.ideas.len() < min_candidates {
+                return Err(CommandError::NotEnoughCandidates { needed: min_candidates, have: seeds.ideas.len() });
+            }
+        }
+
+        // Deal with any ongoing election first, as /config on-restart says
+        let on_restart = guild_state.config.read().await.on_restart;
+        if *guild_state.phase.read().await != Phase::Idle {
+            match on_restart {
+                RestartPolicy::Reject => return Err(CommandError::ElectionRunning),
+                RestartPolicy::Cancel => {
+                    self.discard_election(ctx, key, &guild_state).await;
+                    println!("Running election in guild {} was cancelled to start a new one", key);
+                },
+                RestartPolicy::Finalize => {
+                    let stop_result = tokio::time::timeout(
+                        std::time::Duration::from_secs(8),
+                        self.slash_stop_internal(ctx, key)
+                    ).await;
+
+                    if stop_result.is_err() {
+                        eprintln!("Timeout stopping previous election in guild {}", key);
+                    }
+                },
+            }
+        }
+        // The previous election's ping must not fire for this one
+        self.cancel_ping(&guild_state).await;
+        self.cancel_proposal_batch(&guild_state).await;
+
+        // Number the new election's candidates from 1 again, unless proposals are still pending
+        if let Some(state) = self.election_state(&key).await {
+            if state.upcoming_topics.read().await.is_empty() {
+                state.last_candidate_id.store(0, Ordering::Relaxed);
+            }
+            *state.election.write().await = Some(ElectionInfo {
+                prompt: prompt.clone(),
+                started_at: schedule::now_unix(),
+                credits: options.credits,
+                reaction_voting: options.reaction_voting,
+                anonymous_proposals: options.anonymous_proposals,
+                open_voting: options.open_voting,
+                seats: options.seats,
+                participants: HashSet::new(),
+                abstentions: HashSet::new(),
+            });
+            *state.runoff_decided.write().await = None;
+            state.removed_proposals.write().await.clear();
+            *state.last_outcome.write().await = None;
+            if let Some(seeds) = seeds {
+                state.seed_proposals(seeds).await;
+            }
+            if options.open_voting {
+                // Pending proposals become candidates straight away
+                let pending = std::mem::take(&mut *state.upcoming_topics.write().await);
+                state.candidate_authors.write().await.extend(pending.iter().map(|p| (p.id, p.author)));
+                state.update_votes(|voting| voting.open(pending.into_iter().map(|p| (p.id, p.idea)))).await;
+            } else {
+                state.update_votes(|voting| voting.begin_suggestions()).await;
+            }
+            // Everyone starts this election with its budget
+            *state.credit_snapshot.write().await = snapshot;
+            reset_credits(&state).await;
+        }
+
+        // Find announcement channel with error handling
+        let channel_id = election_channel(ctx, key);
+
+        let Some(channel_id) = channel_id else {
+            return Err(CommandError::NoAnnouncementChannel { channel: BOT_CHANNEL });
+        };
+
+        // Create election announcement with timeout protection
+        let guild_theme = guild_state.theme().await;
+        let config = guild_state.config.read().await.clone();
+        let ping_delay = config.ping_delay_secs;
+        let layout = election_announcement(&prompt, &options, &guild_theme, &config, ping_delay == 0, guild_state.messages().await);
+
+        match tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            self.post_board(ctx, channel_id, layout)
+        ).await {
+            Ok(Ok(board)) => {
+                if ping_delay > 0 {
+                    let announcement = format!("{} **{}** {}", guild_theme.ballot, guild_state.messages().await.election_started, prompt);
+                    self.schedule_ping(ctx, key, guild_state.clone(), (channel_id, board.intro.id), announcement, ping_delay).await;
+                }
+                self.results.write().await.insert(key, board);
+                if options.open_voting {
+                    self.poll_votes(ctx.clone(), &key).await;
+                }
+                if let Some(state) = self.election_state(&key).await {
+                    self.start_countdown(ctx, key, state, duration_hours.unwrap_or(SUGG_INTERVAL)).await;
+                }
+                println!("Successfully created election announcement in guild {}", key);
+                if vote_now {
+                    // Opens voting exactly as /stop would, replacing the suggestion countdown
+                    self.slash_stop_internal(ctx, key).await?;
+                    return Ok(format!("{} Election started: '{}'. Voting is open on the listed candidates.", theme::SUCCESS, prompt));
+                }
+                Ok(format!("{} Election started: '{}'", theme::SUCCESS, prompt))
+            },
+            Ok(Err(why)) => {
+                eprintln!("Failed to create election announcement: {}", why);
+                Ok(format!("{} Election started but failed to post announcement: '{}'. Please check channel permissions.", theme::WARNING, prompt))
+            },
+            Err(_) => {
+                eprintln!("Timeout creating election announcement in guild {}", key);
+                Ok(format!("{} Election started but announcement timed out: '{}'. Please check the announcements channel.", theme::WARNING, prompt))
+            }
+        }
+    }
+
+    async fn slash_stop(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        if let Some(guild_id) = command.guild_id {
+            if ctx.cache.guild(guild_id).is_some() {
+                // Check admin permissions (role, administrator, or owner)
+                if !check_admin_permission!(self, ctx, guild_id, command.user) {
+                    return Err(CommandError::MissingPermission { action: "stop an election" });
+                }
+            }
+
+            let key = self.election_key(guild_id, command.channel_id).await;
+            let result = self.slash_stop_internal(ctx, key).await;
+            self.announce_blocked(ctx, key, &result).await;
+            result
+        } else {
+            Err(CommandError::NotInGuild)
+        }
+    }
+
+    async fn slash_cancel(&self, ctx: &Context, command: &CommandInteraction) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+        let key = self.election_key(guild_id, command.channel_id).await;
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "cancel an election" });
+        }
+
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        if state.election.read().await.is_none()
+            && state.upcoming_topics.read().await.is_empty()
+            && state.votes.read().await.is_empty() {
+            return Err(CommandError::NoElection);
+        }
+
+        self.discard_election(ctx, key, &state).await;
+
+        println!("Election in guild {} was cancelled by {}", key, command.user.id);
+        Ok(format!("{} The election was cancelled without announcing winners, and everyone's credits were reset.", theme::SUCCESS))
+    }
+
+    /// Throws away an election's votes, candidates and proposals and marks its
+    /// announcement cancelled. Unlike /stop, nothing is tallied or announced; unlike
+    /// /reset, a scheduled election is kept.
+    async fn discard_election(&self, ctx: &Context, key: ElectionKey, state: &GuildState) {
+        self.stop_countdown(state).await;
+        self.cancel_ping(state).await;
+        self.cancel_proposal_batch(state).await;
+        state.discard().await;
+
+        // Edit rather than delete the announcement, so members can see what happened to it
+        let edited = self.refresh_board(ctx, &key, |layout| {
+            layout.time_line = format!("{} **This election was cancelled.** No winners will be announced.", theme::ERROR);
+            layout.heading = "**Cancelled**";
+            layout.placeholder = "Votes and proposals were discarded.";
+            layout.body.clear();
+        }).await;
+        if let Err(e) = edited {
+            eprintln!("Failed to mark the announcement cancelled in guild {}: {}", key, e);
+        }
+        self.results.write().await.remove(&key);
+        self.persist().await;
+    }
+
+    /// Lists every setting of the guild, marking those left at their default.
+    async fn slash_config_show(&self, ctx: &Context, command: &CommandInteraction) -> Result<CreateEmbed, CommandError> {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "view election settings" });
+        }
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let lines = state.config.read().await.describe_settings();
+
+        Ok(CreateEmbed::new()
+            .title("⚙️ Election settings")
+            .description(lines.join("\n"))
+            .footer(CreateEmbedFooter::new("Change one with /config <setting>")))
+    }
+
+    async fn slash_config(&self, ctx: &Context, command: &CommandInteraction, setting: &str, options: &[CommandDataOption]) -> CommandResult {
+        let Some(guild_id) = command.guild_id else {
+            return Err(CommandError::NotInGuild);
+        };
+
+        if !check_admin_permission!(self, ctx, guild_id, command.user) {
+            return Err(CommandError::MissingPermission { action: "change election settings" });
+        }
+
+        let Some(state) = self.guild_state(&guild_id).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let result = match setting {
+            "min-candidates" => {
+                let Some(n) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&n| n >= 1) else {
+                    return Err(CommandError::InvalidInput("The minimum number of proposals must be at least 1!".to_string()));
+                };
+
+                state.config.write().await.min_candidates = n as usize;
+                format!("{} Voting now requires at least {} proposals.", theme::SUCCESS, n)
+            },
+            "countdown-interval" => {
+                let Some(minutes) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&m| (0..=24 * 60).contains(&m)) else {
+                    return Err(CommandError::OutOfRange { what: "The countdown interval", min: 0, max: 24 * 60, unit: " minutes" });
+                };
+
+                state.config.write().await.countdown_interval_mins = minutes as u64;
+                if minutes == 0 {
+                    format!("{} The announcement countdown is now off.", theme::SUCCESS)
+                } else {
+                    format!("{} The announcement countdown now refreshes every {} minutes.", theme::SUCCESS, minutes)
+                }
+            },
+            "reset-interval" => {
+                let Some(hours) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&h| (0..=MAX_RESET_INTERVAL_HOURS).contains(&h)) else {
+                    return Err(CommandError::OutOfRange { what: "The reset interval", min: 0, max: MAX_RESET_INTERVAL_HOURS, unit: " hours" });
+                };
+
+                state.config.write().await.reset_interval_hours = hours as u64;
+                self.restart_credit_reset(guild_id, state.clone()).await;
+                if hours == 0 {
+                    format!("{} Credits now reset only when an election ends.", theme::SUCCESS)
+                } else {
+                    format!("{} Everyone's credits now reset to {} every {} hours.", theme::SUCCESS, starting_credits(&state).await, hours)
+                }
+            },
+            "credit-carryover" => {
+                let name = options.iter().find(|opt| opt.name == "policy").and_then(|opt| opt.value.as_str()).unwrap_or("");
+                let percent = options.iter().find(|opt| opt.name == "percent").and_then(|opt| opt.value.as_i64());
+                if name == "decay" && !percent.is_some_and(|p| (1..=99).contains(&p)) {
+                    return Err(CommandError::OutOfRange { what: "The percentage decay keeps", min: 1, max: 99, unit: "%" });
+                }
+                let Some(carry_over) = CarryOver::parse(name, percent.map(|p| p as u8)) else {
+                    return Err(CommandError::InvalidInput("Please pick reset, carry or decay!".to_string()));
+                };
+
+                let interval = {
+                    let mut config = state.config.write().await;
+                    config.credit_carryover = carry_over;
+                    config.reset_interval_hours
+                };
+                let applies = if interval == 0 {
+                    " It applies once credits reset on an interval, see `/config reset-interval`."
+                } else {
+                    ""
+                };
+                let summary = match carry_over {
+                    CarryOver::Reset => "Unused credits are now lost at each reset.".to_string(),
+                    CarryOver::Carry => "Members now keep all their unused credits at each reset, on top of their starting credits.".to_string(),
+                    CarryOver::Decay { percent } => format!("Members now keep {}% of their unused credits at each reset, on top of their starting credits.", percent),
+                };
+                format!("{} {}{}", theme::SUCCESS, summary, applies)
+            },
+            "starting-credits" => {
+                let Some(credits) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&c| (1..=MAX_STARTING_CREDITS as i64).contains(&c)) else {
+                    return Err(CommandError::OutOfRange { what: "Starting credits", min: 1, max: MAX_STARTING_CREDITS as i64, unit: "" });
+                };
+
+                state.config.write().await.starting_credits = Some(credits as usize);
+                format!("{} Members now get {} credits per election. `/start credits:` still overrides this for a single election.", theme::SUCCESS, credits)
+            },
+            "max-votes" => {
+                let Some(n) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&n| (1..=MAX_VOTES_CEILING as i64).contains(&n)) else {
+                    return Err(CommandError::OutOfRange { what: "The vote limit", min: 1, max: MAX_VOTES_CEILING as i64, unit: "" });
+                };
+
+                state.config.write().await.max_votes = n as usize;
+                format!("{} Members can now cast up to {} votes ({} credits) for a single candidate.", theme::SUCCESS, n, n * n)
+            },
+            "tie-break" => {
+                let Some(mode) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(TieBreak::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick one of co-winners, runoff, random or first-proposed!".to_string()));
+                };
+
+                state.config.write().await.tie_break = mode;
+                let effect = match mode {
+                    TieBreak::CoWinners => "every tied candidate wins",
+                    TieBreak::Runoff => "the tied candidates go to a runoff vote",
+                    TieBreak::Random => "the remaining places are drawn at random",
+                    TieBreak::FirstProposed => "the candidates proposed first win",
+                };
+                format!("{} Ties for the last winning place are now settled with `{}`: {}.", theme::SUCCESS, mode.name(), effect)
+            },
+            "freeze-window" => {
+                let Some(minutes) = options.iter()
+                    .find(|opt| opt.name == "minutes")
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&m| (0..=VOTE_INTERVAL as i64 * 60).contains(&m)) else {
+                    return Err(CommandError::OutOfRange { what: "The freeze window", min: 0, max: VOTE_INTERVAL as i64 * 60, unit: " minutes" });
+                };
+                let allows_new_votes = options.iter()
+                    .find(|opt| opt.name == "new-votes")
+                    .and_then(|opt| opt.value.as_bool())
+                    .unwrap_or(true);
+
+                let mut config = state.config.write().await;
+                config.freeze_window_mins = minutes as u64;
+                config.freeze_allows_new_votes = allows_new_votes;
+                drop(config);
+
+                match (minutes, allows_new_votes) {
+                    (0, _) => format!("{} Votes can now be changed until voting ends.", theme::SUCCESS),
+                    (_, true) => format!("{} Votes are now frozen in the final {} minutes of voting. New votes are still accepted.", theme::SUCCESS, minutes),
+                    (_, false) => format!("{} Voting now closes entirely in its final {} minutes.", theme::SUCCESS, minutes),
+                }
+            },
+            "live-results" => {
+                let Some(enabled) = options.first().and_then(|opt| opt.value.as_bool()) else {
+                    return Err(CommandError::InvalidInput("Please choose whether to show the standings during voting!".to_string()));
+                };
+
+                state.config.write().await.live_results = enabled;
+                for (key, election) in self.guild_elections(guild_id).await {
+                    if *election.phase.read().await == Phase::Voting {
+                        self.poll_votes(ctx.clone(), &key).await;
+                    }
+                }
+                if enabled {
+                    format!("{} The standings are now shown while voting is open.", theme::SUCCESS)
+                } else {
+                    format!("{} The standings are now hidden until the winners are announced; only the number of votes cast is shown.", theme::SUCCESS)
+                }
+            },
+            "research-export" => {
+                let Some(enabled) = options.first().and_then(|opt| opt.value.as_bool()) else {
+                    return Err(CommandError::InvalidInput("Please choose whether vote data can be exported!".to_string()));
+                };
+
+                state.config.write().await.research_export = enabled;
+                if enabled {
+                    format!(
+                        "{} Admins can now export the vote matrix with `/export-matrix`. Voters appear under pseudonyms, never their Discord IDs, but consider letting members know.",
+                        theme::SUCCESS
+                    )
+                } else {
+                    format!("{} The vote matrix can no longer be exported.", theme::SUCCESS)
+                }
+            },
+            "leaderboard" => {
+                let Some(enabled) = options.first().and_then(|opt| opt.value.as_bool()) else {
+                    return Err(CommandError::InvalidInput("Please choose whether to keep the leaderboard!".to_string()));
+                };
+
+                state.config.write().await.leaderboard = enabled;
+                if enabled {
+                    format!("{} Members' proposals and spending will now count towards `/leaderboard` as elections end.", theme::SUCCESS)
+                } else {
+                    // Opting out also forgets what was collected
+                    state.leaderboard.write().await.clear();
+                    format!("{} The leaderboard is off and its stats were deleted.", theme::SUCCESS)
+                }
+            },
+            "locale" => {
+                let Some(locale) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(Locale::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick one of the listed languages!".to_string()));
+                };
+
+                state.config.write().await.locale = locale;
+                format!("{} Election announcements posted from now on will be in {}.", theme::SUCCESS, locale.label())
+            },
+            "currency-name" => {
+                let name = options.first().and_then(|opt| opt.value.as_str()).unwrap_or("").trim();
+                if name.is_empty() || name.contains('\n') {
+                    return Err(CommandError::InvalidInput("Please provide a name for the credits, e.g. `voice credits`!".to_string()));
+                }
+                if name.chars().count() > config::MAX_CURRENCY_NAME_LEN {
+                    return Err(CommandError::TooLong { what: "The currency name", max: config::MAX_CURRENCY_NAME_LEN });
+                }
+
+                let currency = (!name.eq_ignore_ascii_case(config::DEFAULT_CURRENCY_NAME)).then(|| name.to_string());
+                state.config.write().await.currency_name = currency;
+                format!("{} Credits are now called {} in replies and in the cost table of new elections.", theme::SUCCESS, name)
+            },
+            "overspend-policy" => {
+                let Some(policy) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(OverspendPolicy::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick either reject or clamp!".to_string()));
+                };
+
+                state.config.write().await.overspend_policy = policy;
+                match policy {
+                    OverspendPolicy::Reject => format!("{} `/vote` now refuses votes a member can't afford.", theme::SUCCESS),
+                    OverspendPolicy::Clamp => format!("{} `/vote` now casts as many of the requested votes as a member can afford.", theme::SUCCESS),
+                }
+            },
+            "on-restart" => {
+                let Some(policy) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(RestartPolicy::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick finalize, cancel or reject!".to_string()));
+                };
+
+                state.config.write().await.on_restart = policy;
+                match policy {
+                    RestartPolicy::Finalize => format!("{} `/start` now ends a running election first and announces its winners.", theme::SUCCESS),
+                    RestartPolicy::Cancel => format!("{} `/start` now discards a running election first, without announcing winners.", theme::SUCCESS),
+                    RestartPolicy::Reject => format!("{} `/start` now refuses to start while an election is running.", theme::SUCCESS),
+                }
+            },
+            "results-format" => {
+                let Some(format) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(ResultsFormat::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick either compact or detailed!".to_string()));
+                };
+
+                state.config.write().await.results_format = format;
+                for (key, election) in self.guild_elections(guild_id).await {
+                    if *election.phase.read().await == Phase::Voting {
+                        self.poll_votes(ctx.clone(), &key).await;
+                    }
+                }
+                match format {
+                    ResultsFormat::Compact => format!("{} The live results now show each candidate's votes.", theme::SUCCESS),
+                    ResultsFormat::Detailed => format!("{} The live results now show each candidate's rank, votes, share of the vote and voters.", theme::SUCCESS),
+                }
+            },
+            "election-scope" => {
+                let Some(scope) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(ElectionScope::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick either guild or channel!".to_string()));
+                };
+
+                // Running elections would be cut off from the commands meant for them
+                for (_, election) in self.guild_elections(guild_id).await {
+                    if *election.phase.read().await != Phase::Idle {
+                        return Err(CommandError::InvalidInput("The election scope can't change while an election is running. Stop or cancel it first!".to_string()));
+                    }
+                }
+
+                state.config.write().await.election_scope = scope;
+                match scope {
+                    ElectionScope::Guild => format!("{} The server now runs a single election, announced in #{}.", theme::SUCCESS, BOT_CHANNEL),
+                    ElectionScope::Channel => format!("{} Each channel now runs its own election, announced in that channel. Commands apply to the election of the channel they're used in.", theme::SUCCESS),
+                }
+            },
+            "blocked-notice" => {
+                let Some(enabled) = options.first().and_then(|opt| opt.value.as_bool()) else {
+                    return Err(CommandError::InvalidInput("Please choose whether to post the notice!".to_string()));
+                };
+
+                state.config.write().await.blocked_notice = enabled;
+                if enabled {
+                    format!("{} When `/stop` can't open voting yet, everyone will be told why in #{}.", theme::SUCCESS, BOT_CHANNEL)
+                } else {
+                    format!("{} Only the admin running `/stop` will be told when voting can't open yet.", theme::SUCCESS)
+                }
+            },
+            "ratification" => {
+                let Some(minutes) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&m| (0..=ratify::MAX_RATIFY_WINDOW_MINS as i64).contains(&m)) else {
+                    return Err(CommandError::OutOfRange { what: "The ratification window", min: 0, max: ratify::MAX_RATIFY_WINDOW_MINS as i64, unit: " minutes" });
+                };
+
+                state.config.write().await.ratify_window_mins = minutes as u64;
+                if minutes == 0 {
+                    format!("{} Winners will no longer be put to ratification.", theme::SUCCESS)
+                } else {
+                    format!("{} Members will have {} minutes to ratify the winners with {} or {} once they're announced.", theme::SUCCESS, minutes, ratify::RATIFY_EMOJI, ratify::REJECT_EMOJI)
+                }
+            },
+            "proposal-batch" => {
+                let Some(seconds) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&s| (0..=config::MAX_PROPOSAL_BATCH_SECS as i64).contains(&s)) else {
+                    return Err(CommandError::OutOfRange { what: "The proposal batch window", min: 0, max: config::MAX_PROPOSAL_BATCH_SECS as i64, unit: " seconds" });
+                };
+
+                state.config.write().await.proposal_batch_secs = seconds as u64;
+                if seconds == 0 {
+                    format!("{} Each new proposal will be announced as soon as it's made.", theme::SUCCESS)
+                } else {
+                    format!("{} New proposals will be collected for {} seconds and announced together.", theme::SUCCESS, seconds)
+                }
+            },
+            "ping-delay" => {
+                let Some(seconds) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&s| (0..=config::MAX_PING_DELAY_SECS as i64).contains(&s)) else {
+                    return Err(CommandError::OutOfRange { what: "The ping delay", min: 0, max: config::MAX_PING_DELAY_SECS as i64, unit: " seconds" });
+                };
+
+                state.config.write().await.ping_delay_secs = seconds as u64;
+                if seconds == 0 {
+                    format!("{} New elections will ping @everyone as soon as they're announced.", theme::SUCCESS)
+                } else {
+                    format!("{} New elections will ping @everyone {} seconds after they're announced; `/cancel` before then and nobody is pinged.", theme::SUCCESS, seconds)
+                }
+            },
+            "stop-confirmation" => {
+                let Some(enabled) = options.first().and_then(|opt| opt.value.as_bool()) else {
+                    return Err(CommandError::InvalidInput("Please choose whether /stop should ask first!".to_string()));
+                };
+
+                state.config.write().await.confirm_stop = enabled;
+                if enabled {
+                    format!("{} `/stop` will now ask for confirmation before ending the vote.", theme::SUCCESS)
+                } else {
+                    format!("{} `/stop` will now end the vote right away.", theme::SUCCESS)
+                }
+            },
+            "proposer-dms" => {
+                let Some(enabled) = options.first().and_then(|opt| opt.value.as_bool()) else {
+                    return Err(CommandError::InvalidInput("Please choose whether to send the DMs!".to_string()));
+                };
+
+                state.config.write().await.notify_proposers = enabled;
+                if enabled {
+                    format!("{} Proposers will be DMed their candidate IDs when voting opens.", theme::SUCCESS)
+                } else {
+                    format!("{} Proposers will no longer be DMed when voting opens.", theme::SUCCESS)
+                }
+            },
+            "ping-winners" => {
+                let Some(enabled) = options.first().and_then(|opt| opt.value.as_bool()) else {
+                    return Err(CommandError::InvalidInput("Please choose whether to mention the proposers!".to_string()));
+                };
+
+                state.config.write().await.ping_winners = enabled;
+                if enabled {
+                    format!("{} The winners announcement will mention whoever proposed the winners, except in anonymous elections.", theme::SUCCESS)
+                } else {
+                    format!("{} The winners announcement will no longer mention proposers.", theme::SUCCESS)
+                }
+            },
+            "winners-message" => {
+                let input = options.first().and_then(|opt| opt.value.as_str()).unwrap_or("");
+                state.set_winners_message(input).await?
+            },
+            "min-membership" => {
+                let input = options.first().and_then(|opt| opt.value.as_str()).unwrap_or("");
+                let secs = if input.trim().eq_ignore_ascii_case("off") {
+                    0
+                } else {
+                    match schedule::parse_duration(input) {
+                        Some(secs) if secs <= config::MAX_MIN_MEMBERSHIP_SECS => secs,
+                        Some(_) => {
+                            return Err(CommandError::InvalidInput(format!(
+                                "The membership requirement can be at most {}!",
+                                schedule::format_duration(config::MAX_MIN_MEMBERSHIP_SECS)
+                            )))
+                        },
+                        None => return Err(CommandError::InvalidInput("Please provide a valid duration, e.g. `1d` or `12h`, or `off`!".to_string())),
+                    }
+                };
+
+                state.config.write().await.min_membership_secs = secs;
+                if secs == 0 {
+                    format!("{} Members can now vote no matter when they joined the server.", theme::SUCCESS)
+                } else {
+                    format!(
+                        "{} Members must now have been in the server for {} before they can vote.",
+                        theme::SUCCESS,
+                        schedule::format_duration(secs)
+                    )
+                }
+            },
+            "departed-members" => {
+                let Some(policy) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(DepartedPolicy::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick either keep or purge!".to_string()));
+                };
+
+                state.config.write().await.departed_members = policy;
+                match policy {
+                    DepartedPolicy::Keep => format!("{} Members who leave now keep their votes and credits, restored if they rejoin.", theme::SUCCESS),
+                    DepartedPolicy::Purge => format!("{} Members who leave now have their votes removed from the tally.", theme::SUCCESS),
+                }
+            },
+            "delegation-cap" => {
+                let Some(cap) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&c| (0..=MAX_STARTING_CREDITS as i64).contains(&c)) else {
+                    return Err(CommandError::OutOfRange { what: "The delegation cap", min: 0, max: MAX_STARTING_CREDITS as i64, unit: " credits" });
+                };
+
+                state.config.write().await.delegation_cap = (cap > 0).then_some(cap as usize);
+                if cap == 0 {
+                    format!("{} Members can now receive any number of delegated credits.", theme::SUCCESS)
+                } else {
+                    format!("{} Members can now receive at most {} delegated credits per election.", theme::SUCCESS, cap)
+                }
+            },
+            "proposal-cost" => {
+                let Some(cost) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&c| (0..=MAX_STARTING_CREDITS as i64).contains(&c)) else {
+                    return Err(CommandError::OutOfRange { what: "The proposal cost", min: 0, max: MAX_STARTING_CREDITS as i64, unit: " credits" });
+                };
+
+                state.config.write().await.proposal_cost = cost as usize;
+                if cost == 0 {
+                    format!("{} Proposals are now free.", theme::SUCCESS)
+                } else {
+                    format!("{} Each proposal now costs its author {} credits, refunded if a moderator rejects it.", theme::SUCCESS, cost)
+                }
+            },
+            "credit-snapshot" => {
+                let Some(mode) = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(SnapshotMode::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick off, starting or zero!".to_string()));
+                };
+
+                // Checked now so a bad file is caught before the next /start
+                if mode != SnapshotMode::Off {
+                    CreditSnapshot::load(&self.snapshot_dir, guild_id, mode)
+                        .map_err(|problems| CommandError::InvalidInput(format!(
+                            "The snapshot can't be used yet:\n{}",
+                            snapshot::describe_problems(&problems)
+                        )))?;
+                }
+
+                state.config.write().await.credit_snapshot = mode;
+                match mode {
+                    SnapshotMode::Off => format!("{} Everyone gets the same starting credits again from the next election.", theme::SUCCESS),
+                    SnapshotMode::Starting => format!("{} From the next election, members in the snapshot start with its credits and everyone else with the usual amount.", theme::SUCCESS),
+                    SnapshotMode::Zero => format!("{} From the next election, only members in the snapshot get credits.", theme::SUCCESS),
+                }
+            },
+            "max-concentration" => {
+                let Some(percent) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&p| (1..=100).contains(&p)) else {
+                    return Err(CommandError::OutOfRange { what: "The concentration limit", min: 1, max: 100, unit: "%" });
+                };
+
+                state.config.write().await.max_concentration = (percent < 100).then_some(percent as usize);
+                if percent == 100 {
+                    format!("{} Members can now spend their whole budget on a single candidate.", theme::SUCCESS)
+                } else {
+                    format!("{} Votes for one candidate can now cost at most {}% of a member's starting credits.", theme::SUCCESS, percent)
+                }
+            },
+            "max-candidates-per-voter" => {
+                let Some(count) = options.first()
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&c| (0..=config::MAX_CANDIDATES_PER_VOTER as i64).contains(&c)) else {
+                    return Err(CommandError::OutOfRange { what: "The candidate limit", min: 0, max: config::MAX_CANDIDATES_PER_VOTER as i64, unit: " candidates" });
+                };
+
+                state.config.write().await.max_candidates_per_voter = (count > 0).then_some(count as usize);
+                if count == 0 {
+                    format!("{} Members can now split their votes across any number of candidates.", theme::SUCCESS)
+                } else {
+                    format!(
+                        "{} Members can now vote for at most {} different candidates; votes for candidates they already back can still be changed.",
+                        theme::SUCCESS,
+                        count
+                    )
+                }
+            },
+            "moderation" => {
+                let channel = options.first().and_then(|opt| opt.value.as_channel_id());
+
+                state.config.write().await.moderation_channel = channel;
+                match channel {
+                    Some(channel) => format!("{} Proposals now wait in <#{}> until an admin approves them.", theme::SUCCESS, channel),
+                    None => format!("{} Proposals are announced right away again. Proposals already waiting can still be approved.", theme::SUCCESS),
+                }
+            },
+            "cooldown" => {
+                let Some(kind) = options.iter()
+                    .find(|opt| opt.name == "commands")
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(CommandKind::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick one of propose, vote, delegate or read!".to_string()));
+                };
+                let Some(secs) = options.iter()
+                    .find(|opt| opt.name == "seconds")
+                    .and_then(|opt| opt.value.as_i64())
+                    .filter(|&s| (0..=cooldown::MAX_COOLDOWN_SECS as i64).contains(&s)) else {
+                    return Err(CommandError::OutOfRange { what: "The cooldown", min: 0, max: cooldown::MAX_COOLDOWN_SECS as i64, unit: " seconds" });
+                };
+
+                state.config.write().await.cooldowns.set(kind, secs as u64);
+                if secs == 0 {
+                    format!("{} `{}` commands no longer have a cooldown.", theme::SUCCESS, kind.name())
+                } else {
+                    format!("{} Members now wait {} seconds between `{}` commands.", theme::SUCCESS, secs, kind.name())
+                }
+            },
+            "emoji" => {
+                let Some(slot) = options.iter()
+                    .find(|opt| opt.name == "slot")
+                    .and_then(|opt| opt.value.as_str())
+                    .and_then(Slot::parse) else {
+                    return Err(CommandError::InvalidInput("Please pick one of ballot, winners, runoff or reset!".to_string()));
+                };
+                let emoji = options.iter()
+                    .find(|opt| opt.name == "emoji")
+                    .and_then(|opt| opt.value.as_str())
+                    .map(|emoji| emoji.trim().to_string());
+                if emoji.as_deref().is_some_and(|emoji| !theme::valid_emoji(emoji)) {
+                    return Err(CommandError::InvalidInput(format!(
+                        "Please give a single emoji of at most {} characters, without spaces or mentions!",
+                        theme::MAX_EMOJI_LEN
+                    )));
+                }
+
+                let mut config = state.config.write().await;
+                let restored = emoji.is_none();
+                config.theme.set(slot, emoji);
+                let current = config.theme.get(slot).to_string();
+                drop(config);
+
+                if restored {
+                    format!("{} The {} emoji is back to its default, {}.", theme::SUCCESS, slot.name(), current)
+                } else {
+                    format!("{} Announcements now use {} as the {} emoji.", theme::SUCCESS, current, slot.name())
+                }
+            },
+            "spectator-roles" => {
+                let roles = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .map(parse_roles)
+                    .unwrap_or_default();
+                if roles.len() > config::MAX_ELECTION_ROLES {
+                    return Err(CommandError::InvalidInput(format!("Please give at most {} roles!", config::MAX_ELECTION_ROLES)));
+                }
+
+                let listed = roles.iter().map(|role| format!("'{}'", role)).collect::<Vec<String>>().join(", ");
+                let cleared = roles.is_empty();
+                state.config.write().await.spectator_roles = roles;
+                if cleared {
+                    format!("{} Only the owner and administrators can see hidden results now.", theme::SUCCESS)
+                } else {
+                    format!(
+                        "{} Members with any of these roles can now see hidden results with `/results private`: {}. It doesn't let them run elections.",
+                        theme::SUCCESS,
+                        listed
+                    )
+                }
+            },
+            "election-roles" => {
+                let roles = options.first()
+                    .and_then(|opt| opt.value.as_str())
+                    .map(parse_roles)
+                    .filter(|roles| !roles.is_empty())
+                    .unwrap_or_else(|| vec![BOT_ROLE.to_string()]);
+                if roles.len() > config::MAX_ELECTION_ROLES {
+                    return Err(CommandError::InvalidInput(format!("Please give at most {} roles!", config::MAX_ELECTION_ROLES)));
+                }
+
+                let listed = roles.iter().map(|role| format!("'{}'", role)).collect::<Vec<String>>().join(", ");
+                state.config.write().await.election_roles = roles;
+                format!(
+                    "{} Besides the owner and administrators, members with any of these roles can now run elections: {}. Check with `/whoami`.",
+                    theme::SUCCESS,
+                    listed
+                )
+            },
+            _ => return Err(CommandError::InvalidInput("Unknown setting. Please try again.".to_string())),
+        };
+
+        // Settings outlive restarts
+        self.persist().await;
+        Ok(result)
+    }
+
+    /// DMs each proposer the IDs their candidates got when voting opens, one DM per
+    /// proposer. The DMs are sent in the background, spaced out by PROPOSER_DM_DELAY,
+    /// and members who don't accept DMs are skipped.
+    fn notify_proposers(&self, ctx: &Context, guild_id: GuildId, candidates: &[(usize, String)], authors: &HashMap<usize, UserId>, reaction_voting: bool, ballot: String) {
+        let mut by_author: HashMap<UserId, Vec<String>> = HashMap::new();
+        for (id, name) in candidates {
+            if let Some(author) = authors.get(id) {
+                by_author.entry(*author).or_default().push(format!("#{}: {}", id, name));
+            }
+        }
+
+        let guild_name = ctx.cache.guild(guild_id).map(|g| g.name.clone()).unwrap_or_else(|| "your server".to_string());
+        let how_to_vote = if reaction_voting {
+            "Vote by reacting with a candidate's number on the announcement."
+        } else {
+            "Vote with `/vote <votes> <candidate_number>`."
+        };
+
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            for (author, lines) in by_author {
+                let content = truncate_lines(
+                    &format!("{} Voting has opened in **{}**! Your proposals are now candidates:\n", ballot, guild_name),
+                    &lines,
+                    DISCORD_MESSAGE_LIMIT - how_to_vote.len() - 1,
+                ) + "\n" + how_to_vote;
+
+                if let Err(why) = author.direct_message(&ctx, CreateMessage::new().content(content)).await {
+                    println!("Skipped voting DM to {} in guild {}: {}", author, guild_id, why);
+                }
+                tokio::time::sleep(PROPOSER_DM_DELAY).await;
+            }
+        });
+    }
+
+    /// Announces the candidates of a voting round and posts the board that tracks its results.
+    /// In reaction voting, the board also gets one number reaction per candidate.
+    async fn post_voting_board(&self, ctx: &Context, key: ElectionKey, state: &Arc<GuildState>, header: &str, candidates: &[(usize, String)], reaction_voting: bool) {
+        let channel_id = election_channel(ctx, key);
+
+        let Some(channel_id) = channel_id else {
+            return;
+        };
+
+        let mut candidates_str = String::new();
+        for (i, (id, name)) in candidates.iter().enumerate() {
+            if reaction_voting {
+                candidates_str = format!("{}{} #{}: {}\n", candidates_str, NUMBER_EMOJI[i], id, name);
+            } else {
+                candidates_str = format!("{}#{}: {}\n", candidates_str, id, name);
+            }
+        }
+
+        let messages = state.messages().await;
+        let candidates_msg = format!("{}\n{}", header, candidates_str);
+        let vote_hint = if reaction_voting { messages.reaction_vote_hint } else { messages.vote_hint };
+
+        // Long candidate lists are spread over several messages, the last of which
+        // starts the board, followed by the results section that is kept up to date
+        let mut chunks = split_message(&candidates_msg, DISCORD_MESSAGE_LIMIT);
+        let mut layout = BoardLayout::new(
+            vote_hint.to_string(),
+            format!("{} {}h", messages.time_remaining, VOTE_INTERVAL),
+            messages.results_heading,
+            messages.no_votes,
+        );
+        let last = chunks.pop().unwrap_or_default();
+        if last.chars().count() + 1 + vote_hint.chars().count() <= DISCORD_MESSAGE_LIMIT {
+            layout.intro = format!("{}\n{}", last, vote_hint);
+        } else {
+            chunks.push(last);
+        }
+
+        for chunk in chunks {
+            self.pacer.acquire(channel_id).await;
+            if let Err(why) = channel_id.say(ctx, chunk).await {
+                eprintln!("Failed to post candidates in guild {}: {}", key, why);
+            }
+        }
+
+        let board = match self.post_board(ctx, channel_id, layout).await {
+            Ok(board) => board,
+            Err(why) => {
+                eprintln!("Failed to post results board in guild {}: {}", key, why);
+                return;
+            }
+        };
+
+        if reaction_voting {
+            for emoji in NUMBER_EMOJI.iter().take(candidates.len()) {
+                if let Err(why) = board.intro.react(ctx, ReactionType::Unicode(emoji.to_string())).await {
+                    eprintln!("Failed to add voting reaction in guild {}: {}", key, why);
+                }
+            }
+            let candidate_ids = candidates.iter().map(|(id, _)| *id).collect();
+            *state.reaction_board.write().await = Some((board.intro.id, candidate_ids));
+        }
+        self.results.write().await.insert(key, board);
+        self.start_countdown(ctx, key, state.clone(), VOTE_INTERVAL).await;
+    }
+
+    async fn slash_stop_internal(&self, ctx: &Context, key: ElectionKey) -> CommandResult {
+        let Some(state) = self.election_state(&key).await else {
+            return Err(CommandError::NotConfigured);
+        };
+
+        let phase = phase_to_stop(&state).await?;
+
+        // Check if in suggestion period, or its review window
+        if matches!(phase, Phase::Suggestion | Phase::ProposalsLocked) {
+            // Move from suggestions to voting
+            let (all_candidates, reaction_voting) = state.start_voting_phase().await?;
+
+            if state.config.read().await.notify_proposers {
+                let authors = state.candidate_authors.read().await.clone();
+                self.notify_proposers(ctx, key.guild_id, &all_candidates, &authors, reaction_voting, state.theme().await.ballot);
+            }
+
+            // Proposals still waiting for their batch go out now, so none lands after the board
+            if let Some(timer) = state.batch_timer.write().await.take() {
+                timer.abort();
+            }
+            self.flush_proposal_batch(ctx, key).await;
+
+            let header = format!("@everyone {} **{}**", state.theme().await.ballot, state.messages().await.candidates_selected);
+            self.post_voting_board(ctx, key, &state, &header, &all_candidates, reaction_voting).await;
+
+            Ok(format!("{} Moved to voting phase!", theme::SUCCESS))
+        } else {
+            // Close voting and take the final tally in one step under the write lock,
+            // so no vote can change the results after they are computed
+            let seats = state.election.read().await.as_ref().and_then(|e| e.seats);
+            // Without seats the top places are only informational, so a tie there is
+            // listed in proposal order instead of being broken
+            let tie_break = match seats {
+                Some(_) => state.config.read().await.tie_break,
+                None => TieBreak::FirstProposed,
+            };
+            let seed = state.election.read().await.as_ref().map_or(0, |e| e.started_at as u64) ^ key.guild_id.get();
+            // A runoff that ties again is settled by proposal order rather than another round
+            let decided_before = state.runoff_decided.read().await.clone();
+            let winning_places = seats.unwrap_or(CONVENIENT_WINNERS);
+            let (places, tie_break) = match &decided_before {
+                Some(decided) => (winning_places.saturating_sub(decided.len()), match tie_break {
+                    TieBreak::Runoff => TieBreak::FirstProposed,
+                    other => other,
+                }),
+                None => (winning_places, tie_break),
+            };
+            let (standings, spending, outcome) = state.finalize_votes(places, tie_break, seed).await;
+            // The leaderboard and history are the guild's, whichever channel the election ran in
+            let guild_state = self.guild_state(&key.guild_id).await.unwrap_or_else(|| state.clone());
+            if state.config.read().await.leaderboard {
+                let mut leaderboard = guild_state.leaderboard.write().await;
+                leaderboard.record_spending(&spending);
+                if let Outcome::Decided(_) = outcome {
+                    leaderboard.record_proposals(state.candidate_authors.read().await.values());
+                }
+                drop(leaderboard);
+                self.persist().await;
+            }
+            // Kept for mentioning the winners' proposers once they're known
+            let authors = state.candidate_authors.read().await.clone();
+            if let Outcome::Decided(_) = outcome {
+                *state.reaction_board.write().await = None;
+                state.candidate_authors.write().await.clear();
+                state.descriptions.write().await.clear();
+                state.links.write().await.clear();
+                state.pending_proposals.write().await.clear();
+            }
+
+            let winners = match outcome {
+                Outcome::Runoff { decided, tied } => {
+                    *state.runoff_decided.write().await = Some(decided);
+                    reset_credits(&state).await;
+
+                    let reaction_voting = state.election.read().await.as_ref().is_some_and(|e| e.reaction_voting);
+                    let candidates = tied.iter().map(|(id, name, _)| (*id, name.clone())).collect::<Vec<(usize, String)>>();
+                    self.stop_countdown(&state).await;
+                    self.post_voting_board(
+                        ctx,
+                        key,
+                        &state,
+                        &format!("@everyone {} **{}**", state.theme().await.runoff, state.messages().await.runoff),
+                        &candidates,
+                        reaction_voting,
+                    ).await;
+
+                    return Ok(format!("{} {} candidates tied for the last winning place, so a runoff vote has started.", theme::SUCCESS, tied.len()));
+                },
+                Outcome::Decided(winners) => {
+                    let mut decided = state.runoff_decided.write().await.take().unwrap_or_default();
+                    decided.extend(winners);
+                    decided
+                },
+            };
+            // With seats, everyone elected has equal standing, so they're listed in
+            // proposal order and the tally follows separately
+            let messages = state.messages().await;
+            let winner_lines = match seats {
+                Some(seats) => {
+                    let mut elected = winners.clone();
+                    elected.sort_by_key(|c| c.0);
+                    format!(
+                        "**{}**\n{}\n\n**{}**\n{}",
+                        locale::fill(messages.elected, &[("n", elected.len()), ("seats", seats)]),
+                        elected.iter().map(|c| format!("• {}", c.1)).collect::<Vec<String>>().join("\n"),
+                        messages.final_tally,
+                        format_standings(&standings).join("\n")
+                    )
+                },
+                None => format!("**{}**\n{}", messages.winners, format_standings(&winners).join("\n")),
+            };
+
+            let channel_id = election_channel(ctx, key);
+
+            if let Some(channel_id) = channel_id {
+                let ratify_window = state.config.read().await.ratify_window_mins;
+                let closes_at = schedule::now_unix() + (ratify_window * 60) as i64;
+                let winners_message = state.config.read().await.winners_message.clone();
+                let mut content = match winners_message {
+                    Some(template) => {
+                        let prompt = state.election.read().await.as_ref().map(|e| e.prompt.clone()).unwrap_or_default();
+                        let participants = state.participant_count().await.to_string();
+                        format!(
+                            "@everyone {}",
+                            fill_placeholders(&template, &[("winners", &winner_lines), ("prompt", &prompt), ("participants", &participants)])
+                        )
+                    },
+                    None => format!(
+                        "@everyone {} **{}** {}\n\n{}",
+                        state.theme().await.winners,
+                        messages.election_over,
+                        participation(state.participant_count().await, state.abstention_count().await, messages),
+                        winner_lines
+                    ),
+                };
+                // Anonymous elections keep proposers hidden, so they're never mentioned
+                let anonymous = state.election.read().await.as_ref().is_some_and(|e| e.anonymous_proposals);
+                let proposers = if state.config.read().await.ping_winners && !anonymous {
+                    winning_proposers(&winners, &authors)
+                } else {
+                    Vec::new()
+                };
+                if !proposers.is_empty() {
+                    let mentions = proposers.iter().map(|user_id| format!("<@{}>", user_id)).collect::<Vec<String>>().join(" ");
+                    content = format!("{}\n\n🎉 {} {}", content, messages.winning_proposers, mentions);
+                }
+                if ratify_window > 0 {
+                    content = format!("{}\n\n{}", content, locale::fill(messages.ratify_hint, &[("closes", closes_at as usize)]));
+                }
+
+                let mut last_posted = None;
+                for chunk in split_message(&content, DISCORD_MESSAGE_LIMIT) {
+                    let mut message = CreateMessage::new().content(chunk);
+                    if !proposers.is_empty() {
+                        // Only @everyone and the winning proposers are pinged, not
+                        // candidate names that happen to contain mentions
+                        message = message.allowed_mentions(CreateAllowedMentions::new().everyone(true).users(proposers.clone()));
+                    }
+                    self.pacer.acquire(channel_id).await;
+                    match channel_id.send_message(ctx, message).await {
+                        Ok(message) => last_posted = Some(message),
+                        Err(why) => {
+                            eprintln!("Failed to announce winners in guild {}: {}", key, why);
+                            last_posted = None;
+                            break;
+                        },
+                    }
+                }
+
+                // The hint ends the announcement, so its last message carries the reactions
+                if let (true, Some(message)) = (ratify_window > 0, last_posted) {
+                    self.start_ratification(ctx, key.guild_id, &message, closes_at).await;
+                }
+            }
+
+            let total_votes = standings.iter().map(|(_, _, votes)| votes).sum();
+            guild_state.analytics.write().await.record_election(total_votes, state.participant_count().await);
+            let election = state.election.write().await.take();
+            let ranking = final_ranking(&winners, &standings);
+            *state.last_outcome.write().await = Some(FinishedElection { prompt: election.as_ref().map(|e| e.prompt.clone()), ranking });
+            guild_state.history.write().await.record(ArchivedElection {
+                prompt: election.as_ref().map(|e| e.prompt.clone()),
+                ended_at: schedule::now_unix(),
+                winners: winners.iter().map(|(_, name, _)| name.clone()).collect(),
+            });
+            self.persist().await;
+
+            // Report the results downstream without holding up the announcement
+            if let Some(webhook) = self.results_webhook.clone() {
+                let results = ElectionResults {
+                    guild_id: key.guild_id.to_string(),
+                    prompt: election.as_ref().map(|e| e.prompt.clone()),
+                    started_at: election.as_ref().map(|e| e.started_at),
+                    ended_at: schedule::now_unix(),
+                    total_votes,
+                    // Winners first, in case a tie-break or runoff moved them up
+                    candidates: winners
+                        .iter()
+                        .cloned()
+                        .chain(standings.into_iter().filter(|c| !winners.iter().any(|w| w.0 == c.0)))
+                        .enumerate()
+                        .map(|(i, (_, name, votes))| RankedCandidate { rank: i + 1, name, votes })
+                        .collect(),
+                };
+                tokio::spawn(async move { webhook.deliver(&results).await });
+            }
+
+            // Reset state
+            self.stop_countdown(&state).await;
+            reset_credits(&state).await;
+
+            Ok(format!("{} Election completed and results announced!", theme::SUCCESS))
+        }
+    }
+}
+
+/// A candidate's line in `/list`. Candidates with a description are marked and show
+/// its first line, so members get some context while voting and know /details has the rest.
+fn list_line(id: usize, name: &str, author: Option<UserId>, description: Option<&str>, has_link: bool) -> String {
+    let mut name = if description.is_some() { format!("{} 📄", name) } else { name.to_string() };
+    if has_link {
+        name.push_str(" 🔗");
+    }
+    let line = match author {
+        Some(author) => format!("#{}: {} — by <@{}>", id, name, author),
+        None => format!("#{}: {}", id, name),
+    };
+    match description {
+        Some(description) => format!("{}\n> {}", line, excerpt(description, DESCRIPTION_PREVIEW_LEN)),
+        None => line,
+    }
+}
+
+/// The phase `/stop` ends. Nothing is running in an idle guild, so stopping it fails
+/// without touching any state: no credits reset, no board cleared.
+async fn phase_to_stop(state: &GuildState) -> Result<Phase, CommandError> {
+    match *state.phase.read().await {
+        Phase::Idle => Err(CommandError::NoElection),
+        phase => Ok(phase),
+    }
+}
+
+/// The members who proposed the winners, in the winners' order and each listed once.
+/// Winners with no known author, e.g. added before authorship was tracked, are skipped.
+fn winning_proposers(winners: &[Ranked], authors: &HashMap<usize, UserId>) -> Vec<UserId> {
+    let mut proposers = Vec::new();
+    for (id, _, _) in winners {
+        if let Some(author) = authors.get(id) {
+            if !proposers.contains(author) {
+                proposers.push(*author);
+            }
+        }
+    }
+    proposers
+}
+
+/// Parses a comma-separated list of server IDs, ignoring blank entries. On failure,
+/// returns every entry that isn't a valid ID, so they can all be fixed at once.
+fn parse_approved_servers(input: &str) -> Result<Vec<GuildId>, Vec<String>> {
+    let mut servers = Vec::new();
+    let mut invalid = Vec::new();
+    for entry in input.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        match entry.parse::<u64>() {
+            // Discord IDs are never 0, and GuildId::new panics on it
+            Ok(id) if id != 0 => servers.push(GuildId::new(id)),
+            _ => invalid.push(entry.to_string()),
+        }
+    }
+
+    if invalid.is_empty() {
+        Ok(servers)
+    } else {
+        Err(invalid)
+    }
+}
+
+/// The channel announcement of a new proposal and its line in a batched announcement.
+/// Neither names the proposer, so anonymous and regular elections announce alike.
+fn proposal_announcement(ballot: &str, id: usize, idea: &str) -> (String, String) {
+    (format!("{} New candidate proposed: #{}: {}", ballot, id, idea), format!("#{}: {}", id, idea))
+}
+
+/// Builds the announcement posted when an election starts, also shown by `/start preview:true`.
+fn election_announcement(prompt: &str, options: &ElectionOptions, theme: &Theme, config: &GuildConfig, ping: bool, messages: &'static Messages) -> BoardLayout {
+    let mention = if ping { "@everyone " } else { "" };
+    let mut prompt = match options.seats {
+        Some(1) => format!("{}\n{}", prompt, messages.one_seat),
+        Some(seats) => format!("{}\n{}", prompt, locale::fill(messages.seats, &[("n", seats)])),
+        None => prompt.to_string(),
+    };
+    // Reaction votes always cost a single credit
+    if !options.reaction_voting {
+        prompt = format!("{}\n{}", prompt, cost_table(config.max_votes, config.currency_name.as_deref(), messages));
+    }
+    let time_line = format!("{} {}h", messages.time_remaining, options.duration_hours.unwrap_or(SUGG_INTERVAL));
+    if options.open_voting {
+        BoardLayout::new(
+            format!("{}{} **{}** {}\n\n{}", mention, theme.ballot, messages.election_started, prompt, messages.open_voting_hint),
+            time_line,
+            messages.results_heading,
+            messages.no_candidates,
+        )
+    } else {
+        BoardLayout::new(
+            format!("{}{} **{}** {}\n\n{}", mention, theme.ballot, messages.election_started, prompt, messages.suggest_hint),
+            time_line,
+            messages.suggestions_heading,
+            messages.no_suggestions,
+        )
+    }
+}
+
+/// A one-line reference of what votes cost, e.g. "1 → 1, 2 → 4, 3 → 9, … 10 → 100".
+/// Long limits are elided after the first few rows to keep the announcement short.
+fn cost_table(max_votes: usize, currency: Option<&str>, messages: &Messages) -> String {
+    let shown = max_votes.min(COST_TABLE_ROWS);
+    let mut rows = (1..=shown)
+        .map(|votes| format!("{} → {}", votes, voting::cost(votes)))
+        .collect::<Vec<String>>();
+    if max_votes > shown {
+        rows.push(format!("… {} → {}", max_votes, voting::cost(max_votes)));
+    }
+    let heading = match currency {
+        Some(currency) => messages.vote_costs_in.replace("{currency}", currency),
+        None => messages.vote_costs.to_string(),
+    };
+    format!("**{}** {}", heading, rows.join(", "))
+}
+
+/// Confirms a /vote, saying so when the clamp policy cut the votes down to what the
+/// member could afford.
+fn vote_reply(candidate_id: usize, votes: usize, cast: usize, remaining: usize, currency: &str) -> String {
+    if cast < votes {
+        return format!(
+            "{} You couldn't afford {} votes ({} {}), so {} votes were cast for candidate #{} instead. {} remaining: {}",
+            theme::WARNING, votes, voting::cost(votes), currency, cast, candidate_id, capitalize(currency), remaining
+        );
+    }
+    format!("{} Cast {} votes for candidate #{}! {} remaining: {}", theme::SUCCESS, votes, candidate_id, capitalize(currency), remaining)
+}
+
+/// Reads the name given to a /template subcommand.
+fn template_name(options: &[CommandDataOption]) -> Result<String, CommandError> {
+    options
+        .iter()
+        .find(|opt| opt.name == "name")
+        .and_then(|opt| opt.value.as_str())
+        .and_then(template::normalize_name)
+        .ok_or_else(|| CommandError::InvalidInput(format!(
+            "Template names are up to {} letters, digits, `-` or `_`!",
+            template::MAX_TEMPLATE_NAME_LEN
+        )))
+}
+
+/// Reads the `/start` options describing an election, as also given to `/template save`.
+fn parse_election(options: &[CommandDataOption]) -> Result<(String, ElectionOptions), CommandError> {
+    let find = |name: &str| options.iter().find(|opt| opt.name == name).map(|opt| &opt.value);
+
+    let prompt = match find("prompt").and_then(|val| val.as_str()) {
+        Some(prompt) if !prompt.trim().is_empty() => prompt.trim().to_string(),
+        _ => return Err(CommandError::InvalidInput("Please provide a valid election prompt!".to_string())),
+    };
+
+    if prompt.chars().count() > MAX_PROMPT_LEN {
+        return Err(CommandError::TooLong { what: "Election prompts", max: MAX_PROMPT_LEN });
+    }
+
+    let duration_hours = find("duration").and_then(|val| val.as_str()).map(parse_duration_hours).transpose()?;
+
+    let credits = match find("credits").and_then(|val| val.as_i64()) {
+        Some(credits) if (1..=MAX_STARTING_CREDITS as i64).contains(&credits) => Some(credits as usize),
+        Some(_) => return Err(CommandError::OutOfRange { what: "Credits", min: 1, max: MAX_STARTING_CREDITS as i64, unit: "" }),
+        None => None,
+    };
+
+    let seats = match find("seats").and_then(|val| val.as_i64()) {
+        Some(seats) if (1..=MAX_SEATS as i64).contains(&seats) => Some(seats as usize),
+        Some(_) => return Err(CommandError::OutOfRange { what: "Seats", min: 1, max: MAX_SEATS as i64, unit: "" }),
+        None => None,
+    };
+
+    let reaction_voting = find("reaction").and_then(|val| val.as_bool()).unwrap_or(false);
+    let anonymous_proposals = find("anonymous").and_then(|val| val.as_bool()).unwrap_or(false);
+    let open_voting = find("open").and_then(|val| val.as_bool()).unwrap_or(false);
+
+    // Reactions are added when the candidate list is locked in, which open voting never does
+    if open_voting && reaction_voting {
+        return Err(CommandError::InvalidInput("Reaction voting can't be combined with open voting!".to_string()));
+    }
+
+    Ok((prompt, ElectionOptions { duration_hours, credits, seats, reaction_voting, anonymous_proposals, open_voting }))
+}
+
+/// Candidate names in the order an election finished: the winners, then the rest of
+/// the final tally.
+fn final_ranking(winners: &[Ranked], standings: &[Ranked]) -> Vec<String> {
+    let mut ranking = winners.iter().map(|(_, name, _)| name.clone()).collect::<Vec<String>>();
+    ranking.extend(standings.iter().filter(|(id, _, _)| !winners.iter().any(|w| w.0 == *id)).map(|(_, name, _)| name.clone()));
+    ranking
+}
+
+/// The prompt of a follow-up round: "Prompt (round 2)", then "(round 3)" and so on.
+fn next_round_prompt(prompt: Option<&str>) -> String {
+    let prompt = prompt.unwrap_or("Follow-up vote");
+    let (base, round) = match prompt.strip_suffix(')').and_then(|rest| rest.rsplit_once(" (round ")) {
+        Some((base, round)) => match round.parse::<usize>() {
+            Ok(round) => (base, round + 1),
+            Err(_) => (prompt, 2),
+        },
+        None => (prompt, 2),
+    };
+    let suffix = format!(" (round {})", round);
+    // Keep within the prompt limit by shortening the original text
+    let base = base.chars().take(MAX_PROMPT_LEN - suffix.chars().count()).collect::<String>();
+    format!("{}{}", base, suffix)
+}
+
+/// Parses the `duration` of /start and /schedule into whole hours, rounded up.
+fn parse_duration_hours(duration: &str) -> Result<u64, CommandError> {
+    let Some(secs) = schedule::parse_duration(duration) else {
+        return Err(CommandError::InvalidInput("Please provide a valid duration, e.g. `48h` or `2d`!".to_string()));
+    };
+    match secs.div_ceil(60 * 60) {
+        hours @ 1..=MAX_DURATION_HOURS => Ok(hours),
+        _ => Err(CommandError::OutOfRange { what: "The duration", min: 1, max: MAX_DURATION_HOURS as i64, unit: " hours" }),
+    }
+}
+
+/// The candidates matching what a member typed so far, best matches first: the exact
+/// ID, then IDs starting with the input, then names starting with it, then names
+/// containing it. At most `MAX_AUTOCOMPLETE_CHOICES` are kept.
+fn candidate_suggestions(mut candidates: Vec<(usize, String)>, input: &str) -> Vec<(usize, String)> {
+    let input = input.trim().trim_start_matches('#').to_lowercase();
+    let rank = |(id, name): &(usize, String)| {
+        let id = id.to_string();
+        let name = name.to_lowercase();
+        if input.is_empty() || id == input {
+            Some(0)
+        } else if id.starts_with(&input) {
+            Some(1)
+        } else if name.starts_with(&input) {
+            Some(2)
+        } else if name.contains(&input) {
+            Some(3)
+        } else {
+            None
+        }
+    };
+
+    candidates.retain(|candidate| rank(candidate).is_some());
+    candidates.sort_by_key(|candidate| (rank(candidate), candidate.0));
+    candidates.truncate(MAX_AUTOCOMPLETE_CHOICES);
+    candidates
+}
+
+/// Reads the candidates listed with `/start candidates:`, checked like proposals.
+/// Returns `None` when none were listed.
+fn parse_seeded_candidates(options: &[CommandDataOption], election: &ElectionOptions, author: UserId) -> Result<Option<SeededCandidates>, CommandError> {
+    let find = |name: &str| options.iter().find(|opt| opt.name == name).map(|opt| &opt.value);
+    let vote_now = find("vote-now").and_then(|val| val.as_bool()).unwrap_or(false);
+    parse_seeded(find("candidates").and_then(|val| val.as_str()), vote_now, election, author)
+}
+
+/// Splits a `;`-separated candidate list, refusing it whole if any candidate would be refused.
+fn parse_seeded(list: Option<&str>, vote_now: bool, election: &ElectionOptions, author: UserId) -> Result<Option<SeededCandidates>, CommandError> {
+    let ideas = list
+        .map(|list| list.split(';').map(str::trim).filter(|idea| !idea.is_empty()).map(str::to_string).collect::<Vec<String>>())
+        .unwrap_or_default();
+    if ideas.is_empty() {
+        if vote_now {
+            return Err(CommandError::InvalidInput("List the candidates to vote on with `candidates`, separated by `;`!".to_string()));
+        }
+        return Ok(None);
+    }
+    if vote_now && election.open_voting {
+        return Err(CommandError::InvalidInput("Open voting already puts candidates on the ballot right away!".to_string()));
+    }
+
+    if ideas.iter().any(|idea| idea.len() > MAX_PROPOSAL_LEN) {
+        return Err(CommandError::TooLong { what: "Proposal ideas", max: MAX_PROPOSAL_LEN });
+    }
+    if let Some(duplicate) = ideas.iter().enumerate().find(|(i, idea)| ideas[..*i].contains(idea)).map(|(_, idea)| idea) {
+        return Err(CommandError::DuplicateProposal(duplicate.clone()));
+    }
+    // Each candidate needs its own number reaction
+    if election.reaction_voting && ideas.len() > NUMBER_EMOJI.len() {
+        return Err(CommandError::ReactionLimit { max: NUMBER_EMOJI.len() });
+    }
+
+    Ok(Some(SeededCandidates { ideas, vote_now, author }))
+}
+
+/// The options shared by `/start` and `/template save` that describe an election.
+fn election_command_options() -> Vec<CreateCommandOption> {
+    vec![
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "prompt",
+            "Election topic/question"
+        )
+        .required(true)
+        .max_length(MAX_PROMPT_LEN as u16),
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "duration",
+            "Suggestion phase length, e.g. 48h or 2d"
+        ),
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "credits",
+            "Credits each member gets for this election only"
+        )
+        .min_int_value(1)
+        .max_int_value(MAX_STARTING_CREDITS as u64),
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "seats",
+            "Number of candidates elected, e.g. 3 for a committee"
+        )
+        .min_int_value(1)
+        .max_int_value(MAX_SEATS as u64),
+        CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "reaction",
+            "Vote by reacting with number emoji, one approval per candidate (max 10 candidates)"
+        ),
+        CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "open",
+            "Let members vote on proposals right away, with no separate suggestion phase"
+        ),
+        CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "anonymous",
+            "Hide who proposed each candidate from everyone but admins"
+        ),
+    ]
+}
+
+/// Whether /stop would end the guild's vote, as opposed to moving its proposals to voting.
+async fn stop_ends_vote(state: &GuildState) -> bool {
+    *state.phase.read().await == Phase::Voting
+}
+
+/// Checks that a proposal can join the running election right now, whether it's new
+/// or a queued one being approved. Returns whether the election uses open voting,
+/// where proposals go straight onto the ballot.
+async fn check_new_proposal(state: &GuildState, idea: &str) -> Result<bool, CommandError> {
+    let (open_voting, reaction_voting) = match state.election.read().await.as_ref() {
+        Some(election) => (election.open_voting, election.reaction_voting),
+        None => (false, false),
+    };
+    state.phase.read().await.check_proposal(open_voting)?;
+
+    // Each candidate needs its own number reaction
+    let topics = state.upcoming_topics.read().await;
+    if reaction_voting && topics.len() >= NUMBER_EMOJI.len() {
+        return Err(CommandError::ReactionLimit { max: NUMBER_EMOJI.len() });
+    }
+
+    let is_duplicate = topics.iter().any(|p| p.idea == idea)
+        || state.pending_proposals.read().await.iter().any(|p| p.idea == idea);
+    if is_duplicate {
+        return Err(CommandError::DuplicateProposal(idea.to_string()));
+    }
+
+    Ok(open_voting)
+}
+
+/// Gives a proposal that passed `check_new_proposal` its ID, and adds it to the
+/// upcoming topics, or straight to the ballot in open voting. Returns the ID.
+async fn store_proposal(state: &GuildState, idea: &str, author: UserId, open_voting: bool) -> Result<usize, CommandError> {
+    if open_voting {
+        // The proposal joins the tally immediately
+        let id = state
+            .update_votes(|voting| voting.add_candidate(idea.to_string(), || state.last_candidate_id.fetch_add(1, Ordering::Relaxed) + 1))
+            .await?;
+        state.candidate_authors.write().await.insert(id, author);
+        return Ok(id);
+    }
+
+    let mut topics = state.upcoming_topics.write().await;
+    if topics.iter().any(|p| p.idea == idea) {
+        return Err(CommandError::DuplicateProposal(idea.to_string()));
+    }
+    let id = state.last_candidate_id.fetch_add(1, Ordering::Relaxed) + 1;
+    topics.push(Proposal { id, idea: idea.to_string(), author });
+    Ok(id)
+}
+
+/// Settles what a proposal cost once it was accepted or queued: the proposer is told
+/// privately what's left of their balance, or refunded if it didn't go through.
+async fn settle_proposal_cost(state: &GuildState, result: CommandResult, author: UserId, cost: usize, remaining: Option<usize>) -> Result<ProposalReply, CommandError> {
+    match (result, remaining) {
+        (Ok(outcome), Some(remaining)) => {
+            let currency = state.currency().await;
+            let cost = format!("Proposing cost you {} {}. {} remaining: {}", cost, currency, capitalize(&currency), remaining);
+            Ok(ProposalReply { outcome, cost: Some(cost) })
+        },
+        (Err(why), Some(_)) => {
+            state.update_votes(|voting| voting.refund_proposal(author, cost)).await;
+            Err(why)
+        },
+        (result, None) => result.map(|outcome| ProposalReply { outcome, cost: None }),
+    }
+}
+
+/// Stores a proposal with its description and link, which are kept by candidate ID.
+/// Returns the proposal's ID.
+async fn store_submission(state: &GuildState, submission: Submission, author: UserId, open_voting: bool) -> Result<usize, CommandError> {
+    let Submission { idea, description, link } = submission;
+    let id = store_proposal(state, &idea, author, open_voting).await?;
+    if let Some(description) = description {
+        state.descriptions.write().await.insert(id, description);
+    }
+    if let Some(link) = link {
+        state.links.write().await.insert(id, link);
+    }
+    Ok(id)
+}
+
+/// Waits until a scheduled election's time comes, right away if it already passed,
+/// then takes it off the schedule. Returns None when it was cancelled in the meantime.
+async fn scheduled_start_due(state: &GuildState, start_at: i64) -> Option<ScheduledStart> {
+    let wait = (start_at - schedule::now_unix()).max(0) as u64;
+    tokio::time::sleep(Duration::from_secs(wait)).await;
+
+    // A cancellation may have raced the timer
+    let scheduled = state.scheduled_start.write().await.take()?;
+    state.schedule_timer.write().await.take();
+    Some(scheduled)
+}
+
+/// Forgets a member who left the guild, when it purges departed members' votes.
+/// Returns whether any of their votes were removed.
+async fn member_departed(state: &GuildState, user_id: UserId) -> bool {
+    // By default a departed member's votes and credits are kept, so they pick up
+    // where they left off if they rejoin during the same election
+    if state.config.read().await.departed_members == DepartedPolicy::Keep {
+        return false;
+    }
+    state.update_votes(|voting| voting.purge(user_id)).await
+}
+
+/// Parses a comma-separated list of roles, given by name, ID or mention.
+fn parse_roles(roles: &str) -> Vec<String> {
+    roles.split(',')
+        .map(|role| role.trim().trim_start_matches("<@&").trim_end_matches('>').to_string())
+        .filter(|role| !role.is_empty())
+        .collect()
+}
+
+/// Checks the image or link of a proposal: an http or https address with a host
+/// and no spaces, so it can't smuggle in other schemes or mentions.
+fn check_link(link: &str) -> Result<(), CommandError> {
+    if link.chars().count() > MAX_LINK_LEN {
+        return Err(CommandError::TooLong { what: "Links", max: MAX_LINK_LEN });
+    }
+    let rest = link.strip_prefix("https://").or_else(|| link.strip_prefix("http://"));
+    let host = rest.map(|rest| rest.split(['/', '?', '#']).next().unwrap_or(""));
+    match host {
+        Some(host) if !host.is_empty() && !link.chars().any(|c| c.is_whitespace() || c.is_control() || c == '<' || c == '>') => Ok(()),
+        _ => Err(CommandError::InvalidInput("Links must be a web address starting with http:// or https://!".to_string())),
+    }
+}
+
+/// Parses a /votemulti batch such as `1:3 4:2 7:1` into (candidate ID, votes) pairs.
+/// Pairs may be separated by spaces or commas; any malformed pair rejects the whole batch.
+fn parse_vote_pairs(input: &str) -> Result<Vec<(usize, usize)>, CommandError> {
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+
+    for (position, pair) in input.split(|c: char| c.is_whitespace() || c == ',').filter(|p| !p.is_empty()).enumerate() {
+        let Some((id, votes)) = pair.split_once(':') else {
+            return Err(CommandError::InvalidInput(format!("Pair #{} `{}` is missing a `:`. Use `<candidate ID>:<votes>`, e.g. `1:3`.", position + 1, pair)));
+        };
+
+        let Ok(id) = id.trim().parse::<usize>() else {
+            return Err(CommandError::InvalidInput(format!("Pair #{} `{}` has an invalid candidate ID `{}`.", position + 1, pair, id)));
+        };
+
+        let votes = match votes.trim().parse::<usize>() {
+            Ok(votes) if (1..=MAX_VOTES_CEILING).contains(&votes) => votes,
+            _ => return Err(CommandError::InvalidInput(format!("Pair #{} `{}`: votes must be a number between 1 and {}.", position + 1, pair, MAX_VOTES_CEILING))),
+        };
+
+        if pairs.iter().any(|(existing, _)| *existing == id) {
+            return Err(CommandError::InvalidInput(format!("Pair #{} `{}`: candidate #{} appears more than once.", position + 1, pair, id)));
+        }
+
+        pairs.push((id, votes));
+    }
+
+    if pairs.is_empty() {
+        return Err(CommandError::InvalidInput("Please provide votes like `1:3 4:2` (candidate ID:votes)!".to_string()));
+    }
+
+    if pairs.len() > MAX_BATCH_VOTES {
+        return Err(CommandError::InvalidInput(format!("You can vote for at most {} candidates at once!", MAX_BATCH_VOTES)));
+    }
+
+    Ok(pairs)
+}
+
+/// Parses the vote counts of a /simulate, such as `3 3 2`, separated by spaces or commas.
+fn parse_simulated_votes(input: &str) -> Result<Vec<usize>, CommandError> {
+    let mut votes = Vec::new();
+
+    for (position, count) in input.split(|c: char| c.is_whitespace() || c == ',').filter(|c| !c.is_empty()).enumerate() {
+        match count.parse::<usize>() {
+            Ok(count) if (1..=MAX_VOTES_CEILING).contains(&count) => votes.push(count),
+            _ => return Err(CommandError::InvalidInput(format!("Entry #{} `{}`: votes must be a number between 1 and {}.", position + 1, count, MAX_VOTES_CEILING))),
+        }
+    }
+
+    if votes.is_empty() {
+        return Err(CommandError::InvalidInput("Please provide vote counts like `3 3 2`!".to_string()));
+    }
+
+    if votes.len() > MAX_BATCH_VOTES {
+        return Err(CommandError::InvalidInput(format!("You can simulate at most {} candidates at once!", MAX_BATCH_VOTES)));
+    }
+
+    Ok(votes)
+}
+
+/// The freeze window, if the voting phase is currently within it.
+async fn vote_freeze(state: &GuildState) -> Option<FreezeWindow> {
+    let (minutes, allows_new_votes) = {
+        let config = state.config.read().await;
+        (config.freeze_window_mins, config.freeze_allows_new_votes)
+    };
+    if minutes == 0 || *state.phase.read().await != Phase::Voting {
+        return None;
+    }
+
+    let deadline = (*state.phase_deadline.read().await)?;
+    let window = FreezeWindow { minutes, allows_new_votes };
+    window.covers(deadline, schedule::now_unix()).then_some(window)
+}
+
+/// The public notice that voting couldn't open, e.g. "⏱️ Voting hasn't started yet: at
+/// least 3 candidates are needed, and there are currently 1. …"
+fn blocked_notice(messages: &Messages, needed: usize, have: usize) -> String {
+    format!("{} {}", theme::WAITING, locale::fill(messages.voting_blocked, &[("needed", needed), ("have", have)]))
+}
+
+/// Removes the dropped proposal, leaving the kept one and every other ID untouched.
+/// Returns the kept proposal's text and the removed proposal.
+fn merge_proposals(topics: &mut Vec<Proposal>, keep: usize, drop: usize) -> Result<(String, Proposal), CommandError> {
+    if keep == drop {
+        return Err(CommandError::InvalidInput("Please pick two different proposals to merge!".to_string()));
+    }
+    let Some(kept) = topics.iter().find(|p| p.id == keep).map(|p| p.idea.clone()) else {
+        return Err(CommandError::NoSuchProposal(keep));
+    };
+    let Some(index) = topics.iter().position(|p| p.id == drop) else {
+        return Err(CommandError::NoSuchProposal(drop));
+    };
+    Ok((kept, topics.remove(index)))
+}
+
+/// The headline participation figure, e.g. "12 members participated. 2 of them abstained."
+fn participation(count: usize, abstained: usize, messages: &Messages) -> String {
+    let participated = match count {
+        1 => messages.one_participant.to_string(),
+        n => locale::fill(messages.participants, &[("n", n)]),
+    };
+    match abstained {
+        0 => participated,
+        1 => format!("{} {}", participated, messages.one_abstention),
+        n => format!("{} {}", participated, locale::fill(messages.abstentions, &[("n", n)])),
+    }
+}
+
+/// Formats candidates as `name: votes` lines.
+fn format_standings(standings: &[Ranked]) -> Vec<String> {
+    standings
+        .iter()
+        .map(|w| format!("{}: {}", w.1, w.2))
+        .collect::<Vec<String>>()
+}
+
+/// Credits each member gets in the current election: the `/start` override if one
+/// was given, then the guild's configured default, then STARTING_POINTS.
+async fn starting_credits(state: &GuildState) -> usize {
+    if let Some(credits) = state.election.read().await.as_ref().and_then(|e| e.credits) {
+        return credits;
+    }
+    state.config.read().await.starting_credits.unwrap_or(STARTING_POINTS)
+}
+
+/// A member's starting credits in the guild's current election, from its snapshot if any.
+async fn member_starting_credits(state: &GuildState, user_id: UserId) -> usize {
+    let credits = starting_credits(state).await;
+    match state.credit_snapshot.read().await.as_ref() {
+        Some(snapshot) => snapshot.starting_credits(user_id, credits),
+        None => credits,
+    }
+}
+
+/// Gives a proposal's author back what they paid to propose it, when it won't make
+/// the ballot.
+async fn refund_proposal(state: &GuildState, proposal: &PendingProposal) {
+    if proposal.cost > 0 {
+        state.update_votes(|voting| voting.refund_proposal(proposal.author, proposal.cost)).await;
+    }
+}
+
+/// Gives every user in the guild their starting credits back.
+async fn reset_credits(state: &GuildState) {
+    let credits = starting_credits(state).await;
+    let snapshot = state.credit_snapshot.read().await;
+    // The locks are held together, in update_votes' order, so no vote can land between
+    // the refill and the delegations being cleared and get refunded against a stale cap
+    let points = state.points.write().await;
+    let mut delegated = state.delegated.write().await;
+    let mut carried = state.carried.write().await;
+    for (user_id, balance) in points.iter() {
+        let starting = snapshot.as_ref().map_or(credits, |snapshot| snapshot.starting_credits(*user_id, credits));
+        balance.swap(starting, Ordering::Relaxed);
+    }
+    // Delegated and carried credits were just wiped along with everything else
+    delegated.clear();
+    carried.clear();
+}
+
+/// Refills everyone's credits on the reset interval, keeping the part of their unused
+/// credits the guild's carry-over policy allows on top of their starting credits.
+/// What they keep is recorded so their credit cap covers it until the next refill.
+async fn refill_credits(state: &GuildState) {
+    let carry_over = state.config.read().await.credit_carryover;
+    if carry_over == CarryOver::Reset {
+        return reset_credits(state).await;
+    }
+
+    let credits = starting_credits(state).await;
+    let snapshot = state.credit_snapshot.read().await;
+    let points = state.points.write().await;
+    let mut delegated = state.delegated.write().await;
+    let mut carried = state.carried.write().await;
+    carried.clear();
+    for (user_id, balance) in points.iter() {
+        let starting = snapshot.as_ref().map_or(credits, |snapshot| snapshot.starting_credits(*user_id, credits));
+        let kept = carry_over.carried(balance.load(Ordering::Relaxed));
+        balance.store(starting + kept, Ordering::Relaxed);
+        if kept > 0 {
+            carried.insert(*user_id, kept);
+        }
+    }
+    delegated.clear();
+}
+
+/// Refuses a vote from a member who joined the server more recently than the guild's
+/// `/config min-membership` allows as of `now`, in Unix seconds. Members whose join
+/// time isn't known can vote.
+async fn check_membership(state: &GuildState, joined_at: Option<Timestamp>, now: i64) -> Result<(), CommandError> {
+    let min_secs = state.config.read().await.min_membership_secs;
+    let Some(joined_at) = joined_at.filter(|_| min_secs > 0) else {
+        return Ok(());
+    };
+
+    let eligible_at = joined_at.unix_timestamp().saturating_add(min_secs as i64);
+    if now < eligible_at {
+        return Err(CommandError::MembershipTooNew { eligible_at });
+    }
+    Ok(())
+}
+
+/// A hash of the slash command definitions, to tell whether they changed. Never 0,
+/// which stands for nothing registered yet.
+fn command_fingerprint(commands: &[CreateCommand]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(commands).unwrap_or_default().hash(&mut hasher);
+    hasher.finish().max(1)
+}
+
+/// Whether the user owns the bot's application, or is on the team that owns it.
+async fn is_bot_owner(ctx: &Context, user_id: UserId) -> bool {
+    match ctx.http.get_current_application_info().await {
+        Ok(info) => {
+            info.owner.is_some_and(|owner| owner.id == user_id)
+                || info.team.is_some_and(|team| team.members.iter().any(|member| member.user.id == user_id))
+        },
+        Err(why) => {
+            eprintln!("Failed to fetch the application's owner: {}", why);
+            false
+        },
+    }
+}
+
+/// Formats the time left in a phase for the announcement countdown.
+fn format_remaining(secs: i64) -> String {
+    if secs <= 0 {
+        return "time's up — waiting for an admin to `/stop`".to_string();
+    }
+
+    let minutes = (secs + 59) / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    // Above var can be in proc variables or .env
+    dotenv().ok();
+
+    // The discord API key is necessary for the bot to function
+    let token =
+        env::var(SECRET_KEY).expect(formatcp!("missing discord API secret in {}", SECRET_KEY));
+
+    // Parse approved servers from environment variable. Leaving it out serves every guild,
+    // same as leaving it empty.
+    let approved_servers_str = env::var(APPROVED_SERVERS_KEY).unwrap_or_default();
+    let approved_servers = match parse_approved_servers(&approved_servers_str) {
+        Ok(servers) => servers,
+        Err(invalid) => {
+            // Dropping the bad entries could leave the list empty, which would open the
+            // bot to every guild, so refuse to start instead
+            eprintln!("Invalid server IDs in {}:", APPROVED_SERVERS_KEY);
+            for entry in invalid {
+                eprintln!("  - {:?}", entry);
+            }
+            eprintln!("Server IDs are the numbers copied with Developer Mode's \"Copy Server ID\", separated by commas.");
+            std::process::exit(1);
         }
+    };
+
+    if approved_servers.is_empty() {
+        println!("{} is empty: bot will serve every guild it is invited to", APPROVED_SERVERS_KEY);
+    } else {
+        println!("Bot configured for {} server(s): {:?}", approved_servers.len(), approved_servers);
     }
 
-    // ===== COMMAND HANDLERS WITH PROPER RESPONSE PATTERNS =====
+    let state_file = env::var(STATE_FILE_KEY).unwrap_or_else(|_| store::DEFAULT_STATE_FILE.to_string());
+    println!("Persisting state to {}", state_file);
 
-    async fn handle_prop_command(&self, ctx: &Context, command: &CommandInteraction) {
-        let idea = match command.data.options.get(0)
-            .map(|opt| &opt.value)
-            .and_then(|val| val.as_str()) {
-            Some(idea) if !idea.trim().is_empty() => idea.trim().to_string(),
-            _ => {
-                self.send_ephemeral_response(ctx, command, "❌ Please provide a valid proposal idea!").await;
-                return;
-            },
-        };
+    let audit_file = env::var(AUDIT_FILE_KEY).unwrap_or_else(|_| audit::DEFAULT_AUDIT_FILE.to_string());
 
-        // Defer response since announcing and state updates might take time
-        if !self.defer_response(ctx, command, false).await {
-            eprintln!("Failed to defer response for /prop command from user: {}", command.user.id);
-            return;
-        }
+    let snapshot_dir = env::var(CREDIT_SNAPSHOT_DIR_KEY).unwrap_or_else(|_| snapshot::DEFAULT_SNAPSHOT_DIR.to_string());
 
-        println!("Processing /prop command for user: {} with idea: {}", command.user.id, idea);
+    let handler = <Handler as Default>::default()
+        .register_servers(approved_servers)
+        .restore(Store::new(state_file))
+        .with_results_webhook(ResultsWebhook::from_env())
+        .with_announce_retry(RetryPolicy::from_env())
+        .with_audit_log(AuditLog::new(audit_file))
+        .with_snapshot_dir(snapshot_dir);
 
-        // Execute with timeout protection
-        let result = match tokio::time::timeout(
-            std::time::Duration::from_secs(12),
-            self.slash_prop(ctx, command, idea.clone())
-        ).await {
-            Ok(result) => result,
-            Err(_) => {
-                eprintln!("Timeout processing /prop command for user: {}", command.user.id);
-                format!("⏱️ Operation timed out, but your proposal '{}' may have been recorded. Please check the announcements channel.", idea)
+    let intake = Intake::from_env();
+    match env::var(HEALTH_ADDR_KEY) {
+        Ok(addr) => {
+            let (intake, proposals) = intake.unzip();
+            if let Some(proposals) = proposals {
+                println!("Accepting proposals on http://{}/guild/<id>/proposal", addr);
+                tokio::spawn(handler.clone().receive_external_proposals(proposals));
             }
-        };
-
-        self.send_followup_guaranteed(ctx, command, &result).await;
-        println!("Completed /prop command processing for user: {}", command.user.id);
+            tokio::spawn(health::serve(addr, handler.gateway_ready.clone(), intake.map(Arc::new)));
+        },
+        Err(_) if intake.is_some() => {
+            println!("{} is set but {} isn't, so proposals can't be posted over HTTP", intake::PROPOSAL_API_KEY, HEALTH_ADDR_KEY);
+        },
+        Err(_) => {},
     }
 
-    async fn handle_vote_command(&self, ctx: &Context, command: &CommandInteraction) {
-        let votes = command.data.options.get(0)
-            .map(|opt| &opt.value)
-            .and_then(|val| val.as_i64())
-            .filter(|&v| v > 0 && v <= 10)
-            .unwrap_or(0) as usize;
-
-        let candidate_id = command.data.options.get(1)
-            .map(|opt| &opt.value)
-            .and_then(|val| val.as_i64())
-            .filter(|&v| v >= 0)
-            .unwrap_or(-1) as isize;
+    // Set gateway intents for slash commands and guild operations
+    let intents = GatewayIntents::GUILDS
+        | GatewayIntents::GUILD_MEMBERS
+        | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS;
+
+    // Run the bot. Serenity reconnects after transient drops on its own; this only
+    // restarts the client when the gateway gives up entirely.
+    let mut failures = 0;
+    loop {
+        let started_at = Instant::now();
+        let mut client = Client::builder(&token, intents)
+            .event_handler(handler.clone())
+            .await
+            .expect("failed to create client");
 
-        if votes == 0 {
-            self.send_ephemeral_response(ctx, command, "❌ Number of votes must be between 1 and 10!").await;
-            return;
+        let why = match client.start().await {
+            Ok(()) => break,
+            Err(why) => why,
+        };
+        if let serenity::Error::Gateway(
+            GatewayError::InvalidAuthentication | GatewayError::InvalidGatewayIntents | GatewayError::DisallowedGatewayIntents
+        ) = why {
+            // Retrying can't fix the bot's configuration
+            panic!("Discord refused the connection: {}", why);
         }
 
-        if candidate_id < 0 {
-            self.send_ephemeral_response(ctx, command, "❌ Please provide a valid candidate ID (0 or higher)!").await;
-            return;
+        // A client that stayed up for a while failed for a new reason, so start counting again
+        if started_at.elapsed() >= START_RETRY_MAX_DELAY {
+            failures = 0;
+        }
+        failures += 1;
+        if failures > MAX_START_RETRIES {
+            panic!("Giving up on the gateway after {} failed attempts: {}", MAX_START_RETRIES, why);
         }
 
-        let result = self.slash_vote(ctx, command, votes, candidate_id as usize).await;
-        self.send_response(ctx, command, &result).await;
+        let delay = START_RETRY_BASE_DELAY.saturating_mul(1 << (failures - 1)).min(START_RETRY_MAX_DELAY);
+        eprintln!(
+            "Gateway connection failed: {}. Retrying in {}s (attempt {}/{})",
+            why,
+            delay.as_secs(),
+            failures,
+            MAX_START_RETRIES
+        );
+        tokio::time::sleep(delay).await;
     }
+}
 
-    async fn handle_points_command(&self, ctx: &Context, command: &CommandInteraction) {
-        let result = self.slash_points(ctx, command).await;
-        self.send_ephemeral_response(ctx, command, &result).await; // Points are private
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    async fn handle_start_command(&self, ctx: &Context, command: &CommandInteraction) {
-        let prompt = match command.data.options.get(0)
-            .map(|opt| &opt.value)
-            .and_then(|val| val.as_str()) {
-            Some(prompt) if !prompt.trim().is_empty() => prompt.trim().to_string(),
-            _ => {
-                self.send_ephemeral_response(ctx, command, "❌ Please provide a valid election prompt!").await;
-                return;
-            },
-        };
+    fn user(id: u64) -> UserId {
+        UserId::new(id)
+    }
 
-        // Defer response since starting an election might take time
-        if !self.defer_response(ctx, command, false).await {
-            eprintln!("Failed to defer response for /start command from user: {}", command.user.id);
-            return;
-        }
+    /// Opens voting on the given candidates, numbered from 1.
+    async fn open_ballot(state: &GuildState, candidates: &[&str]) {
+        let candidates = candidates.iter().enumerate().map(|(i, name)| (i + 1, name.to_string())).collect::<Vec<(usize, String)>>();
+        state.update_votes(|voting| voting.open(candidates)).await;
+    }
 
-        println!("Processing /start command for user: {} with prompt: {}", command.user.id, prompt);
+    /// A guild whose voting phase is open on the given candidates.
+    async fn voting_state(candidates: &[&str]) -> Arc<GuildState> {
+        let state = Arc::new(GuildState::default());
+        open_ballot(&state, candidates).await;
+        state
+    }
 
-        // Execute with timeout protection - start command can be complex
-        let result = match tokio::time::timeout(
-            std::time::Duration::from_secs(15),
-            self.slash_start(ctx, command, prompt.clone())
-        ).await {
-            Ok(result) => result,
-            Err(_) => {
-                eprintln!("Timeout processing /start command for user: {}", command.user.id);
-                format!("⏱️ Election start operation timed out. Please check the announcements channel and try again if needed.")
-            }
-        };
+    /// A handler serving a single guild, and the key of that guild's election.
+    fn guild_handler() -> (Handler, ElectionKey) {
+        let guild_id = GuildId::new(1);
+        (Handler::default().register_servers(vec![guild_id]), ElectionKey::guild(guild_id))
+    }
 
-        self.send_followup_guaranteed(ctx, command, &result).await;
-        println!("Completed /start command processing for user: {}", command.user.id);
+    #[tokio::test]
+    async fn guild_create_sets_up_a_new_guild_for_commands() {
+        let handler = Handler::default().register_servers(Vec::new());
+        let guild_id = GuildId::new(42);
+        assert!(handler.guild_state(&guild_id).await.is_none());
+
+        assert!(handler.add_guild(guild_id).await);
+        // A reconnect fires GuildCreate again without losing the state
+        assert!(!handler.add_guild(guild_id).await);
+
+        // Commands in the new guild find its state instead of panicking
+        assert!(handler.check_rate_limit(guild_id, user(1), CommandKind::Vote).await.is_ok());
+        let state = handler.election_state(&ElectionKey::guild(guild_id)).await.expect("guild state");
+        let result = state.update_votes(|voting| voting.cast(user(1), 1, 1)).await;
+        assert!(matches!(result, Err(CommandError::NoElection)));
     }
 
-    async fn handle_stop_command(&self, ctx: &Context, command: &CommandInteraction) {
-        // Defer response since stopping might take time to calculate results
-        if !self.defer_response(ctx, command, false).await {
-            eprintln!("Failed to defer response for /stop command from user: {}", command.user.id);
-            return;
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn votes_racing_the_finalize_never_miss_the_announced_tally() {
+        let state = voting_state(&["Park", "Library"]).await;
+
+        let voters = (1..=64)
+            .map(|id| {
+                let state = state.clone();
+                tokio::spawn(async move { state.update_votes(|voting| voting.cast(user(id), 1 + id as usize % 2, 1)).await })
+            })
+            .collect::<Vec<_>>();
+        tokio::task::yield_now().await;
+        let (standings, _, outcome) = state.finalize_votes(CONVENIENT_WINNERS, TieBreak::CoWinners, 0).await;
+
+        let mut counted = 0;
+        for voter in voters {
+            match voter.await.unwrap() {
+                Ok(_) => counted += 1,
+                // Votes arriving after the finalize find the election over
+                Err(why) => assert!(matches!(why, CommandError::NoElection), "unexpected error: {}", why),
+            }
         }
+        // Every accepted vote is in the announced tally, and nothing lands after it
+        assert_eq!(standings.iter().map(|(_, _, votes)| votes).sum::<usize>(), counted);
+        assert!(matches!(outcome, Outcome::Decided(_)));
+        assert_eq!(*state.phase.read().await, Phase::Idle);
+        assert!(state.votes.read().await.is_empty());
+    }
 
-        println!("Processing /stop command for user: {}", command.user.id);
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn a_first_time_voters_racing_votes_are_all_charged() {
+        let names = (1..=8).map(|i| format!("Candidate {}", i)).collect::<Vec<String>>();
+        let state = voting_state(&names.iter().map(String::as_str).collect::<Vec<&str>>()).await;
+
+        // The member has no balance yet, so every vote races to set up their starting credits
+        let votes = (1..=8)
+            .map(|candidate_id| {
+                let state = state.clone();
+                tokio::spawn(async move { state.update_votes(|voting| voting.cast(user(1), candidate_id, 3)).await })
+            })
+            .collect::<Vec<_>>();
+        for vote in votes {
+            assert!(vote.await.unwrap().is_ok());
+        }
 
-        // Execute with timeout protection
-        let result = match tokio::time::timeout(
-            std::time::Duration::from_secs(15),
-            self.slash_stop(ctx, command)
-        ).await {
-            Ok(result) => result,
-            Err(_) => {
-                eprintln!("Timeout processing /stop command for user: {}", command.user.id);
-                "⏱️ Election stop operation timed out. Please check the announcements channel for status.".to_string()
-            }
-        };
+        let (balance, spent) = state.update_votes(|voting| (voting.balance(user(1)), voting.spending()[&user(1)])).await;
+        assert_eq!(spent, 8 * 9);
+        assert_eq!(balance, STARTING_POINTS - spent);
+    }
 
-        self.send_followup_guaranteed(ctx, command, &result).await;
-        println!("Completed /stop command processing for user: {}", command.user.id);
+    #[tokio::test]
+    async fn start_credits_override_only_the_election_they_were_given_for() {
+        let state = GuildState::default();
+        state.config.write().await.starting_credits = Some(150);
+        state.points.write().await.insert(user(2), AtomicUsize::new(7));
+
+        *state.election.write().await = Some(ElectionInfo { credits: Some(500), ..ElectionInfo::default() });
+        reset_credits(&state).await;
+        assert_eq!(starting_credits(&state).await, 500);
+        assert_eq!(state.update_votes(|voting| (voting.balance(user(1)), voting.balance(user(2)))).await, (500, 500));
+
+        // The next election without an override is back to the guild's default
+        *state.election.write().await = Some(ElectionInfo::default());
+        reset_credits(&state).await;
+        assert_eq!(starting_credits(&state).await, 150);
+        assert_eq!(state.update_votes(|voting| (voting.balance(user(1)), voting.balance(user(2)))).await, (150, 150));
+
+        *state.election.write().await = None;
+        state.config.write().await.starting_credits = None;
+        assert_eq!(starting_credits(&state).await, STARTING_POINTS);
     }
 
-    // ===== SLASH COMMAND HANDLERS =====
+    #[tokio::test]
+    async fn public_standings_never_show_a_voters_remaining_credits() {
+        let (handler, key) = guild_handler();
+        let state = handler.election_state(&key).await.unwrap();
+        open_ballot(&state, &["Park", "Library"]).await;
+
+        let (_, remaining) = state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+        assert_eq!(remaining, 91);
+
+        // The vote confirmation is the only place the balance appears, and it's ephemeral;
+        // the board everyone sees only has the tallies
+        for format in ResultsFormat::ALL {
+            state.config.write().await.results_format = format;
+            let board = handler.live_standings(&key).await.join("\n");
+            assert!(board.contains("Park: 3"), "{}", board);
+            assert!(!board.contains(&remaining.to_string()), "{}", board);
+        }
+    }
 
-    async fn slash_prop(&self, ctx: &Context, command: &CommandInteraction, idea: String) -> String {
-        let Some(guild_id) = command.guild_id else {
-            return "❌ This command can only be used in a server!".to_string();
-        };
+    #[tokio::test]
+    async fn public_prop_replies_never_show_what_the_proposal_cost() {
+        let (handler, key) = guild_handler();
+        let state = handler.election_state(&key).await.unwrap();
+
+        let remaining = state.update_votes(|voting| voting.charge_proposal(user(1), 10)).await.unwrap();
+        assert_eq!(remaining, STARTING_POINTS - 10);
+        let outcome = "✅ Added 'Park' to the upcoming election (#1)".to_string();
+        let reply = settle_proposal_cost(&state, Ok(outcome.clone()), user(1), 10, Some(remaining)).await.unwrap();
+        // /prop's reply may be posted in the channel, so the balance goes in a private follow-up
+        assert_eq!(reply.outcome, outcome);
+        assert_eq!(reply.cost, Some(format!("Proposing cost you 10 points. Points remaining: {}", remaining)));
+
+        let refused = settle_proposal_cost(&state, Err(CommandError::DuplicateProposal("Park".to_string())), user(1), 10, Some(remaining)).await;
+        assert_eq!(refused, Err(CommandError::DuplicateProposal("Park".to_string())));
+        assert_eq!(state.update_votes(|voting| voting.balance(user(1))).await, STARTING_POINTS);
+    }
 
-        // Check rate limiting
-        if self.check_rate_limit(guild_id, command.user.id).await {
-            return "⏱️ Please wait 2 seconds between commands!".to_string();
+    #[tokio::test]
+    async fn blind_voting_boards_show_only_the_number_of_votes() {
+        let (handler, key) = guild_handler();
+        let state = handler.election_state(&key).await.unwrap();
+        state.config.write().await.live_results = false;
+        open_ballot(&state, &["Park", "Library"]).await;
+        assert!(handler.live_standings(&key).await.is_empty());
+
+        state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+        state.update_votes(|voting| voting.cast(user(2), 2, 4)).await.unwrap();
+        for format in ResultsFormat::ALL {
+            state.config.write().await.results_format = format;
+            let board = handler.live_standings(&key).await;
+            assert_eq!(board, vec!["7 votes cast so far. The standings will be revealed when the election ends.".to_string()]);
+            assert!(!board[0].contains("Park") && !board[0].contains("Library"));
         }
 
-        // Check if the idea is too long
-        if idea.len() > 100 {
-            return "❌ Proposal ideas must be 100 characters or less!".to_string();
-        }
+        // The tallies are still kept for the final announcement
+        assert_eq!(handler.standings(&key).await.iter().map(|(_, _, votes)| *votes).collect::<Vec<usize>>(), vec![4, 3]);
+    }
 
-        // Check if in voting period
-        if self.in_vote_period(&guild_id).await {
-            return "❌ Candidates cannot be proposed while the vote is ongoing!".to_string();
-        }
+    #[tokio::test]
+    async fn reposted_standings_show_the_current_tallies() {
+        let (handler, key) = guild_handler();
+        let state = handler.election_state(&key).await.unwrap();
+        open_ballot(&state, &["Park", "Library"]).await;
+        let report = handler.standings_report(&key, &state).await;
+        assert!(report.contains(state.messages().await.results_heading) && report.contains("Park: 0"), "{}", report);
+
+        state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+        let report = handler.standings_report(&key, &state).await;
+        assert!(report.contains("Park: 3") && report.contains("Library: 0"), "{}", report);
+
+        state.update_votes(|voting| voting.cast(user(2), 2, 5)).await.unwrap();
+        state.update_votes(|voting| voting.cast(user(1), 1, 1)).await.unwrap();
+        let report = handler.standings_report(&key, &state).await;
+        assert!(report.contains("Library: 5") && report.contains("Park: 1"), "{}", report);
+        assert!(report.find("Library").unwrap() < report.find("Park").unwrap(), "{}", report);
+    }
 
-        // Safe access to guild data
-        let Some(topics_lock) = self.upcoming_topics.get(&guild_id) else {
-            return "❌ Server not configured for voting. Contact an administrator.".to_string();
-        };
+    #[tokio::test]
+    async fn replies_use_the_configured_currency_name() {
+        let state = GuildState::default();
+        let messages = state.messages().await;
+        assert_eq!(vote_reply(2, 3, 3, 91, &state.currency().await), "✅ Cast 3 votes for candidate #2! Points remaining: 91");
+
+        state.config.write().await.currency_name = Some("voice credits".to_string());
+        let currency = state.currency().await;
+        assert_eq!(vote_reply(2, 3, 3, 91, &currency), "✅ Cast 3 votes for candidate #2! Voice credits remaining: 91");
+        assert_eq!(
+            vote_reply(2, 5, 3, 1, &currency),
+            "⚠️ You couldn't afford 5 votes (25 voice credits), so 3 votes were cast for candidate #2 instead. Voice credits remaining: 1"
+        );
+        assert!(cost_table(3, state.config.read().await.currency_name.as_deref(), messages).contains("voice credits"));
+    }
 
-        // Check for duplicates with proper error handling - scope the read lock
-        let is_duplicate = {
-            topics_lock.read().await.contains(&idea)
-        };
+    #[tokio::test]
+    async fn votes_freeze_only_within_the_window_of_the_voting_deadline() {
+        let state = GuildState::default();
+        state.config.write().await.freeze_window_mins = 10;
+        *state.phase_deadline.write().await = Some(schedule::now_unix() + 5 * 60);
+        // Proposals aren't votes, so they never freeze
+        assert!(vote_freeze(&state).await.is_none());
 
-        match is_duplicate {
-            true => format!("❌ Your proposal '{}' already exists!", idea),
-            false => {
-                // Add the proposal with error handling
-                println!("Attempting to store proposal '{}' for guild {}", idea, guild_id);
-                topics_lock.write().await.push(idea.clone());
-                println!("Successfully stored proposal '{}' for guild {}", idea, guild_id);
+        open_ballot(&state, &["Park"]).await;
+        assert!(vote_freeze(&state).await.is_some_and(|window| window.minutes == 10 && window.allows_new_votes));
 
-                // Update suggestions display (only if election is active)
-                if let Err(e) = self.poll_suggestions_safe(ctx, &guild_id).await {
-                    // Silent fail if no active election - this is normal for first proposals
-                    eprintln!("No active election to update: {}", e);
-                }
+        *state.phase_deadline.write().await = Some(schedule::now_unix() + 60 * 60);
+        assert!(vote_freeze(&state).await.is_none());
 
-                // Announce in channel (non-blocking)
-                if let Some(_) = announce!(ctx, guild_id, format!("🗳️ New candidate proposed: {}", idea)) {
-                    // Announcement successful
-                } else {
-                    eprintln!("Failed to announce new proposal in guild {} - channel not found or no permissions", guild_id);
-                }
+        *state.phase_deadline.write().await = Some(schedule::now_unix());
+        state.config.write().await.freeze_window_mins = 0;
+        assert!(vote_freeze(&state).await.is_none());
+    }
 
-                format!("✅ Proposal '{}' added successfully!", idea)
-            }
-        }
+    #[tokio::test]
+    async fn guild_create_ignores_guilds_outside_the_allowlist() {
+        let handler = Handler::default().register_servers(vec![GuildId::new(1)]);
+
+        assert!(!handler.add_guild(GuildId::new(2)).await);
+        assert!(handler.guild_state(&GuildId::new(2)).await.is_none());
+        assert!(handler.guild_state(&GuildId::new(1)).await.is_some());
     }
 
-    async fn slash_vote(&self, ctx: &Context, command: &CommandInteraction, votes: usize, candidate_id: usize) -> String {
-        let Some(guild_id) = command.guild_id else {
-            return "❌ This command can only be used in a server!".to_string();
-        };
+    #[test]
+    fn proposal_announcements_keep_the_default_text_without_the_proposer() {
+        let (announcement, line) = proposal_announcement("🗳️", 3, "Bike racks");
+        assert_eq!(announcement, "🗳️ New candidate proposed: #3: Bike racks");
+        assert_eq!(line, "#3: Bike racks");
+    }
 
-        // Check rate limiting
-        if self.check_rate_limit(guild_id, command.user.id).await {
-            return "⏱️ Please wait 2 seconds between commands!".to_string();
-        }
+    #[tokio::test]
+    async fn participants_are_counted_once_each() {
+        let state = GuildState::default();
+        // Nothing is counted outside of an election
+        state.record_participant(user(1)).await;
+        assert_eq!(state.participant_count().await, 0);
+
+        *state.election.write().await = Some(ElectionInfo::default());
+        state.record_participant(user(1)).await;
+        state.record_participant(user(1)).await;
+        state.record_participant(user(2)).await;
+        assert_eq!(state.participant_count().await, 2);
+    }
 
-        // Validate vote count
-        if votes == 0 || votes > 10 {
-            return "❌ Number of votes must be between 1 and 10!".to_string();
-        }
+    #[tokio::test]
+    async fn cancelled_elections_leave_no_winners_to_announce() {
+        let state = voting_state(&["Bike racks", "Benches"]).await;
+        *state.election.write().await = Some(ElectionInfo::default());
+        state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+        state.update_votes(|voting| voting.cast(user(2), 2, 2)).await.unwrap();
+
+        state.discard().await;
+
+        assert!(state.election.read().await.is_none());
+        assert_eq!(*state.phase.read().await, Phase::Idle);
+        assert!(state.points.read().await.is_empty());
+        // A /stop racing the cancel finds nothing to tally
+        let (standings, spending, outcome) = state.finalize_votes(5, TieBreak::default(), 0).await;
+        assert!(standings.is_empty());
+        assert!(spending.is_empty());
+        assert!(matches!(outcome, Outcome::Decided(winners) if winners.is_empty()));
+    }
 
-        // Safe access to guild data
-        let Some(votes_lock) = self.votes.get(&guild_id) else {
-            return "❌ Server not configured for voting. Contact an administrator.".to_string();
-        };
+    #[tokio::test]
+    async fn each_kind_of_command_is_rate_limited_separately() {
+        let (handler, key) = guild_handler();
+        let guild_id = key.guild_id;
 
-        let Some(points_lock) = self.points.get(&guild_id) else {
-            return "❌ Server not configured for voting. Contact an administrator.".to_string();
-        };
+        assert!(handler.check_rate_limit(guild_id, user(1), CommandKind::Vote).await.is_ok());
+        assert_eq!(
+            handler.check_rate_limit(guild_id, user(1), CommandKind::Vote).await,
+            Err(CommandError::RateLimited { wait_secs: 2 })
+        );
+        // Other kinds and other members aren't held up by that vote
+        assert!(handler.check_rate_limit(guild_id, user(1), CommandKind::Propose).await.is_ok());
+        assert!(handler.check_rate_limit(guild_id, user(2), CommandKind::Vote).await.is_ok());
+        // Reads have no cooldown by default
+        assert!(handler.check_rate_limit(guild_id, user(1), CommandKind::Read).await.is_ok());
+        assert!(handler.check_rate_limit(guild_id, user(1), CommandKind::Read).await.is_ok());
+    }
 
-        // Convert user's 1-based candidate ID to 0-based internal index
-        if candidate_id == 0 {
-            return "❌ Candidate IDs start from 1. Use `/vote <votes> <candidate_id>` where candidate_id ≥ 1".to_string();
+    #[test]
+    fn expired_cooldowns_are_swept_out_once_the_rate_limiter_is_large() {
+        let start = Instant::now();
+        let cooldown = Duration::from_secs(2);
+        let mut times = CommandTimes::default();
+        for id in 1..=COOLDOWN_PRUNE_THRESHOLD as u64 {
+            times.check((GuildId::new(1), user(id), CommandKind::Vote), cooldown, start).unwrap();
         }
-        let internal_candidate_id = candidate_id - 1;
+        assert_eq!(times.last_used.len(), COOLDOWN_PRUNE_THRESHOLD);
 
-        // Check if candidate exists
-        let votes_read = votes_lock.read().await;
-        if !votes_read.contains_key(&internal_candidate_id) {
-            return format!("❌ Candidate #{} does not exist!", candidate_id);
-        }
-        drop(votes_read);
+        // An hour on, every entry is past the longest cooldown there can be
+        let later = start + Duration::from_secs(cooldown::MAX_COOLDOWN_SECS);
+        times.check((GuildId::new(1), user(1), CommandKind::Vote), cooldown, later).unwrap();
+        assert_eq!(times.last_used.len(), 1);
+        assert_eq!(times.last_swept, Some(later));
 
-        // Initialize user points if needed
-        if !points_lock.read().await.contains_key(&command.user.id) {
-            points_lock.write().await.insert(command.user.id, AtomicUsize::new(STARTING_POINTS));
+        // Live entries are kept, and a full map isn't swept again right away
+        for id in 2..=COOLDOWN_PRUNE_THRESHOLD as u64 {
+            times.check((GuildId::new(1), user(id), CommandKind::Vote), cooldown, later).unwrap();
         }
+        let soon = later + COOLDOWN_SWEEP_INTERVAL / 2;
+        times.check((GuildId::new(2), user(1), CommandKind::Vote), cooldown, soon).unwrap();
+        assert_eq!(times.last_used.len(), COOLDOWN_PRUNE_THRESHOLD + 1);
+        assert_eq!(times.last_swept, Some(later));
+        assert_eq!(
+            times.check((GuildId::new(2), user(1), CommandKind::Vote), cooldown, soon),
+            Err(CommandError::RateLimited { wait_secs: 2 })
+        );
+    }
 
-        let req_points = votes.pow(2);
-        let mut can_spend = points_lock.read().await
-            .get(&command.user.id).unwrap().load(Ordering::Relaxed);
+    #[tokio::test]
+    async fn members_who_leave_and_rejoin_keep_their_votes_by_default() {
+        let state = voting_state(&["Bike racks", "Benches"]).await;
+        state.update_votes(|voting| voting.delegate(user(2), user(1), 20)).await.unwrap();
+        state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+
+        assert!(!member_departed(&state, user(1)).await);
+        // Back in the guild, they pick up where they left off
+        let (balance, cap, votes) = state
+            .update_votes(|voting| (voting.balance(user(1)), voting.credit_cap(user(1)), voting.standings()[0].2))
+            .await;
+        assert_eq!((balance, cap, votes), (111, 120, 3));
+    }
+
+    #[tokio::test]
+    async fn members_who_leave_and_rejoin_start_over_when_departures_are_purged() {
+        let state = voting_state(&["Bike racks", "Benches"]).await;
+        state.config.write().await.departed_members = DepartedPolicy::Purge;
+        state.update_votes(|voting| voting.delegate(user(2), user(1), 20)).await.unwrap();
+        state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+        state.update_votes(|voting| voting.cast(user(3), 1, 2)).await.unwrap();
+
+        assert!(member_departed(&state, user(1)).await);
+        // Nothing is left to purge the second time
+        assert!(!member_departed(&state, user(1)).await);
+
+        // Back in the guild, they start from the starting credits, without what was delegated
+        let (balance, cap, standings) = state
+            .update_votes(|voting| (voting.balance(user(1)), voting.credit_cap(user(1)), voting.standings()))
+            .await;
+        assert_eq!((balance, cap), (STARTING_POINTS, STARTING_POINTS));
+        assert_eq!(standings[0], (1, "Bike racks".to_string(), 2));
+        assert_eq!(state.update_votes(|voting| voting.cast(user(1), 2, 10)).await, Ok((10, 0)));
+    }
+
+    #[tokio::test]
+    async fn vote_cycles_after_a_carry_over_refill_never_create_credits() {
+        for carry_over in [None, Some(CarryOver::Carry)] {
+            let state = voting_state(&["Bike racks", "Benches", "Trees"]).await;
+            state.update_votes(|voting| voting.cast(user(1), 1, 6)).await.unwrap();
+            if let Some(carry_over) = carry_over {
+                state.config.write().await.credit_carryover = carry_over;
+                refill_credits(&state).await;
+            }
 
-        // Check for existing votes and calculate refund
-        let votes_read = votes_lock.read().await;
-        if let Some(candidate) = votes_read.get(&internal_candidate_id) {
-            if let Some(existing_votes) = candidate.2.get(&command.user.id) {
-                can_spend += existing_votes.load(Ordering::Relaxed).pow(2);
+            let total = |voting: &VotingState| voting.balance(user(1)) + voting.spending().get(&user(1)).copied().unwrap_or(0);
+            let before = state.update_votes(|voting| total(voting)).await;
+            for votes in (1..=10).chain((1..=10).rev()) {
+                let after = state
+                    .update_votes(|voting| {
+                        let _ = voting.retract(user(1), 2);
+                        let _ = voting.cast(user(1), 3, votes);
+                        let _ = voting.cast(user(1), 2, 11 - votes);
+                        total(voting)
+                    })
+                    .await;
+                // Credits are neither created nor lost, whether the votes went through or not
+                assert_eq!(after, before, "{:?}: credits after casting {} votes", carry_over, votes);
             }
         }
-        drop(votes_read);
+    }
+
+    #[tokio::test]
+    async fn members_can_spend_the_credits_each_carry_over_policy_keeps() {
+        // 36 credits are spent before the refill, leaving 64 unused
+        for (carry_over, kept, extra_votes) in [(CarryOver::Reset, 0, 0), (CarryOver::Carry, 64, 8), (CarryOver::Decay { percent: 25 }, 16, 4)] {
+            let state = voting_state(&["Bike racks", "Benches", "Trees"]).await;
+            state.config.write().await.credit_carryover = carry_over;
+            state.update_votes(|voting| voting.cast(user(1), 1, 6)).await.unwrap();
+            refill_credits(&state).await;
+
+            state
+                .update_votes(|voting| {
+                    assert_eq!(voting.balance(user(1)), 100 + kept, "{:?}", carry_over);
+                    assert_eq!(voting.credit_cap(user(1)), 100 + kept, "{:?}", carry_over);
+                    assert_eq!(voting.cast(user(1), 2, 10), Ok((10, kept)), "{:?}", carry_over);
+                    if extra_votes > 0 {
+                        assert_eq!(voting.cast(user(1), 3, extra_votes), Ok((extra_votes, 0)), "{:?}", carry_over);
+                    }
+                    assert!(matches!(voting.cast(user(1), 3, extra_votes + 1), Err(CommandError::InsufficientCredits { .. })));
 
-        if can_spend < req_points {
-            return format!("❌ Insufficient points! {} votes cost {} points, but you can only spend {}.",
-                votes, req_points, can_spend);
+                    // Retracting gives the carried credits back instead of clamping them away
+                    assert_eq!(voting.retract(user(1), 2), Ok(10));
+                    assert_eq!(voting.balance(user(1)), 100);
+                })
+                .await;
         }
 
-        // Process the vote with proper error handling
-        let mut votes_map = votes_lock.write().await;
-        if let Some(candidate_entry) = votes_map.get_mut(&internal_candidate_id) {
-            // Handle existing votes refund
-            if let Some(existing_votes) = candidate_entry.2.get(&command.user.id) {
-                let prev_votes = existing_votes.swap(votes, Ordering::Relaxed);
-                candidate_entry.1.fetch_sub(prev_votes, Ordering::Relaxed);
-                points_lock.read().await.get(&command.user.id).unwrap()
-                    .fetch_add(prev_votes.pow(2), Ordering::Relaxed);
-            } else {
-                candidate_entry.2.insert(command.user.id, AtomicUsize::new(votes));
-            }
+        // The next plain reset forgets what was carried
+        let state = voting_state(&["Bike racks"]).await;
+        state.config.write().await.credit_carryover = CarryOver::Carry;
+        state.update_votes(|voting| voting.cast(user(1), 1, 6)).await.unwrap();
+        refill_credits(&state).await;
+        reset_credits(&state).await;
+        assert_eq!(state.update_votes(|voting| voting.credit_cap(user(1))).await, 100);
+    }
 
-            // Add new votes and charge points
-            candidate_entry.1.fetch_add(votes, Ordering::Relaxed);
-            points_lock.read().await.get(&command.user.id).unwrap()
-                .fetch_sub(req_points, Ordering::Relaxed);
-        } else {
-            return format!("❌ Candidate #{} no longer exists!", candidate_id);
+    #[tokio::test]
+    async fn three_seats_go_to_the_top_three_when_the_cutoff_is_clear() {
+        let state = voting_state(&["A", "B", "C", "D", "E"]).await;
+        for (voter, candidate_id, votes) in [(1, 1, 6), (2, 2, 5), (3, 3, 4), (4, 4, 3), (5, 5, 1)] {
+            state.update_votes(|voting| voting.cast(user(voter), candidate_id, votes)).await.unwrap();
         }
-        drop(votes_map);
 
-        // Update results (non-blocking)
-        self.poll_votes(ctx.clone(), &guild_id).await;
+        let (_, _, outcome) = state.finalize_votes(3, TieBreak::Runoff, 0).await;
+        let Outcome::Decided(winners) = outcome else {
+            panic!("a clear cutoff shouldn't need a runoff");
+        };
+        assert_eq!(winners.iter().map(|c| c.0).collect::<Vec<usize>>(), vec![1, 2, 3]);
+        assert_eq!(*state.phase.read().await, Phase::Idle);
+    }
+
+    #[test]
+    fn approved_servers_skip_blanks_and_report_every_invalid_entry() {
+        assert_eq!(parse_approved_servers(" 1, 22 ,,333, "), Ok(vec![GuildId::new(1), GuildId::new(22), GuildId::new(333)]));
+        assert_eq!(parse_approved_servers(""), Ok(Vec::new()));
+        assert_eq!(
+            parse_approved_servers("1, abc, 0, 2, -5"),
+            Err(vec!["abc".to_string(), "0".to_string(), "-5".to_string()])
+        );
+    }
 
-        let remaining = points_lock.read().await
-            .get(&command.user.id).unwrap().load(Ordering::Relaxed);
+    #[test]
+    fn only_one_start_holds_the_starting_flag_at_a_time() {
+        let starting = AtomicBool::new(false);
+        let guard = StartingGuard::acquire(&starting).expect("the first start");
+        assert!(StartingGuard::acquire(&starting).is_none());
+        assert!(starting.load(Ordering::Acquire));
 
-        format!("✅ Cast {} votes for candidate #{}! Points remaining: {}", votes, candidate_id, remaining)
+        drop(guard);
+        assert!(!starting.load(Ordering::Acquire));
+        assert!(StartingGuard::acquire(&starting).is_some());
     }
 
-    async fn slash_points(&self, _ctx: &Context, command: &CommandInteraction) -> String {
-        let Some(guild_id) = command.guild_id else {
-            return "❌ This command can only be used in a server!".to_string();
-        };
+    #[tokio::test]
+    async fn blocked_notices_name_the_shortfall_and_are_debounced() {
+        assert_eq!(
+            blocked_notice(Locale::En.messages(), 3, 1),
+            "⏱️ Voting hasn't started yet: at least 3 candidates are needed, and there are currently 1. Keep suggesting with `/prop`!"
+        );
 
-        // Safe access to guild data
-        let Some(points_lock) = self.points.get(&guild_id) else {
-            return "❌ Server not configured for voting. Contact an administrator.".to_string();
-        };
+        let state = GuildState::default();
+        assert!(state.claim_blocked_notice().await);
+        assert!(!state.claim_blocked_notice().await);
 
-        let points_left = points_lock.read().await
-            .get(&command.user.id)
-            .map(|a| a.load(Ordering::Relaxed))
-            .unwrap_or(STARTING_POINTS);
+        // Once the debounce period is over, the next notice goes out
+        *state.last_blocked_notice.write().await = Instant::now().checked_sub(BLOCKED_NOTICE_DEBOUNCE);
+        assert!(state.claim_blocked_notice().await);
+    }
 
-        format!("🗳️ You have **{}** points left (out of {}) to spend in this election.",
-            points_left, STARTING_POINTS)
+    #[test]
+    fn election_roles_are_read_by_name_id_or_mention() {
+        assert_eq!(parse_roles("Election Manager, <@&11>, ,22"), vec!["Election Manager", "11", "22"]);
+        assert!(parse_roles(" , ").is_empty());
     }
 
-    async fn slash_start(&self, ctx: &Context, command: &CommandInteraction, prompt: String) -> String {
-        let Some(guild_id) = command.guild_id else {
-            return "❌ This command can only be used in a server!".to_string();
-        };
+    #[test]
+    fn any_of_several_election_roles_grants_permission() {
+        let mut guild = Guild::default();
+        guild.owner_id = user(99);
+        for (id, name) in [(10, "Election Manager"), (11, "Moderators")] {
+            let mut role = serenity::all::Role::default();
+            role.id = RoleId::new(id);
+            role.name = name.to_string();
+            guild.roles.insert(role.id, role);
+        }
+        let mut member = Member::default();
+        member.user.id = user(1);
+        member.roles = vec![RoleId::new(11)];
+
+        let roles = parse_roles("Election Manager, <@&11>, Missing");
+        let permissions = guild_admin_permissions(&guild, user(1), &roles, Some(&member));
+        assert!(permissions.allowed());
+        assert_eq!(
+            permissions.roles,
+            vec![
+                ("Election Manager".to_string(), Some(false)),
+                ("Moderators".to_string(), Some(true)),
+                ("Missing".to_string(), None),
+            ]
+        );
 
-        // Check if guild exists in cache
-        if ctx.cache.guild(guild_id).is_none() {
-            return "❌ Unable to access server information. Please try again.".to_string();
-        };
+        // Without any of the roles, a plain member isn't allowed
+        member.roles = vec![RoleId::new(12)];
+        assert!(!guild_admin_permissions(&guild, user(1), &roles, Some(&member)).allowed());
+        assert!(guild_admin_permissions(&guild, user(99), &roles, None).allowed());
+    }
 
-        // Check admin permissions with timeout protection
-        let has_permission = tokio::time::timeout(
-            std::time::Duration::from_secs(3),
-            async { check_admin_permission!(ctx, guild_id, command.user) }
-        ).await.unwrap_or(false);
+    #[tokio::test]
+    async fn accepted_proposals_join_the_upcoming_topics() {
+        let state = GuildState::default();
+        assert_eq!(check_new_proposal(&state, "Bike racks").await, Err(CommandError::NoElection));
 
-        if !has_permission {
-            return format!(
-                "❌ You need one of the following to start an election:\n• Server Owner\n• Administrator permission\n• '{}' role",
-                BOT_ROLE
-            );
+        *state.election.write().await = Some(ElectionInfo::default());
+        state.update_votes(|voting| voting.begin_suggestions()).await;
+        for idea in ["Bike racks", "Benches"] {
+            let open_voting = check_new_proposal(&state, idea).await.unwrap();
+            store_proposal(&state, idea, user(1), open_voting).await.unwrap();
         }
+        let topics = state.upcoming_topics.read().await;
+        assert_eq!(topics.iter().map(|p| (p.id, p.idea.as_str())).collect::<Vec<_>>(), vec![(1, "Bike racks"), (2, "Benches")]);
+        drop(topics);
+        assert_eq!(check_new_proposal(&state, "Benches").await, Err(CommandError::DuplicateProposal("Benches".to_string())));
+
+        // In open voting, proposals go straight onto the ballot
+        let state = voting_state(&["Bike racks"]).await;
+        state.last_candidate_id.store(1, Ordering::Relaxed);
+        *state.election.write().await = Some(ElectionInfo { open_voting: true, ..ElectionInfo::default() });
+        let open_voting = check_new_proposal(&state, "Trees").await.unwrap();
+        assert_eq!(store_proposal(&state, "Trees", user(2), open_voting).await, Ok(2));
+        assert_eq!(state.votes.read().await[&2].0, "Trees");
+        assert_eq!(state.candidate_authors.read().await[&2], user(2));
+    }
 
-        println!("User {} has permission to start election in guild {}", command.user.id, guild_id);
+    #[tokio::test]
+    async fn external_proposals_wait_for_the_gateway() {
+        let (handler, key) = guild_handler();
+        let proposal = ExternalProposal { idea: "Bike racks".to_string(), description: None, link: None, author: None, channel: None };
+        assert!(matches!(handler.external_proposal(key.guild_id, proposal).await, Reply::NotReady));
+    }
 
-        // Stop any ongoing election first with timeout protection
-        let stop_result = tokio::time::timeout(
-            std::time::Duration::from_secs(8),
-            self.slash_stop_internal(ctx, guild_id)
-        ).await;
+    #[tokio::test(start_paused = true)]
+    async fn scheduled_starts_that_came_due_while_down_fire_immediately() {
+        let state = GuildState::default();
+        let scheduled = |start_at| ScheduledStart { prompt: "Park names".to_string(), start_at, duration_hours: None };
+
+        let overdue = schedule::now_unix() - 60 * 60;
+        *state.scheduled_start.write().await = Some(scheduled(overdue));
+        let start = tokio::time::Instant::now();
+        let due = scheduled_start_due(&state, overdue).await.expect("the overdue start");
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(due.prompt, "Park names");
+        assert!(state.scheduled_start.read().await.is_none());
+
+        // A start still ahead waits for its time
+        let ahead = schedule::now_unix() + 60;
+        *state.scheduled_start.write().await = Some(scheduled(ahead));
+        let start = tokio::time::Instant::now();
+        assert!(scheduled_start_due(&state, ahead).await.is_some());
+        assert!(start.elapsed() >= Duration::from_secs(59));
+
+        // One cancelled before its time doesn't start
+        assert!(scheduled_start_due(&state, overdue).await.is_none());
+    }
 
-        if stop_result.is_err() {
-            eprintln!("Timeout stopping previous election in guild {}", guild_id);
+    #[tokio::test]
+    async fn rapid_proposals_go_out_in_one_batched_announcement() {
+        let state = GuildState::default();
+        let mut first_of_batch = Vec::new();
+        for (id, idea) in [(1, "Bike racks"), (2, "Benches"), (3, "Trees")] {
+            let (full, line) = proposal_announcement("🗳️", id, idea);
+            first_of_batch.push(state.queue_announcement(QueuedAnnouncement { full, line, has_details: false, votable: false }).await);
         }
+        // Only the first proposal starts the batch's timer
+        assert_eq!(first_of_batch, vec![true, false, false]);
 
-        // Find announcement channel with error handling
-        let channel_id = ctx.cache.guild(guild_id)
-            .and_then(|guild| guild.channels.iter().find(|(_, ch)| ch.name == BOT_CHANNEL).map(|(id, _)| *id));
+        let announcement = batch::render("🗳️", state.take_announcement_batch().await);
+        assert_eq!(announcement.as_deref(), Some("🗳️ 3 new candidates proposed:\n#1: Bike racks\n#2: Benches\n#3: Trees"));
+        assert!(state.announcement_batch.read().await.is_empty());
 
-        let Some(channel_id) = channel_id else {
-            return format!("❌ Announcement channel '{}' not found. Please create it first.", BOT_CHANNEL);
+        // The next proposal starts a new batch
+        let (full, line) = proposal_announcement("🗳️", 4, "Fountain");
+        assert!(state.queue_announcement(QueuedAnnouncement { full, line, has_details: false, votable: false }).await);
+    }
+
+    #[test]
+    fn spectators_see_private_results_but_regular_members_cannot() {
+        let mut guild = Guild::default();
+        guild.owner_id = user(99);
+        for (id, name) in [(10, "voting"), (11, "Observer")] {
+            let mut role = serenity::all::Role::default();
+            role.id = RoleId::new(id);
+            role.name = name.to_string();
+            guild.roles.insert(role.id, role);
+        }
+        let member = |id: u64, roles: &[u64]| {
+            let mut member = Member::default();
+            member.user.id = user(id);
+            member.roles = roles.iter().map(|&id| RoleId::new(id)).collect();
+            member
         };
+        let spectator_roles = parse_roles("Observer");
+
+        let spectator = member(1, &[11]);
+        assert!(guild_admin_permissions(&guild, user(1), &spectator_roles, Some(&spectator)).allowed());
+        let regular = member(2, &[]);
+        assert!(!guild_admin_permissions(&guild, user(2), &spectator_roles, Some(&regular)).allowed());
+        // Running elections doesn't make a member a spectator
+        let election_manager = member(3, &[10]);
+        assert!(!guild_admin_permissions(&guild, user(3), &spectator_roles, Some(&election_manager)).allowed());
+        // Without any spectator roles, only the owner sees them
+        assert!(!guild_admin_permissions(&guild, user(1), &[], Some(&spectator)).allowed());
+        assert!(guild_admin_permissions(&guild, user(99), &[], None).allowed());
+    }
 
-        // Create election announcement with timeout protection
-        let announcement_content = format!(
-            "@everyone 🗳️ **An election has started:** {}\n\nSuggest candidates with `/prop <idea>`\n\n⏰ Time remaining: {}h\n\n**Suggestions so Far:**\nNo suggestions yet",
-            prompt, SUGG_INTERVAL
+    #[test]
+    fn the_announcement_channel_is_found_by_its_exact_name() {
+        let channel = |id: u64, name: &'static str| (ChannelId::new(id), name);
+        assert_eq!(
+            find_announcement_channel([channel(1, "general"), channel(2, "announcements"), channel(3, "voting")]),
+            Some(ChannelId::new(2))
         );
+        assert_eq!(find_announcement_channel([channel(1, "general"), channel(2, "Announcements"), channel(3, "announcements-old")]), None);
+        assert_eq!(find_announcement_channel(Vec::new()), None);
+    }
 
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(10),
-            channel_id.say(ctx, announcement_content)
-        ).await {
-            Ok(Ok(message)) => {
-                self.results.write().await.insert(guild_id, message);
-                println!("Successfully created election announcement in guild {}", guild_id);
-                format!("✅ Election started: '{}'", prompt)
-            },
-            Ok(Err(why)) => {
-                eprintln!("Failed to create election announcement: {}", why);
-                format!("⚠️ Election started but failed to post announcement: '{}'. Please check channel permissions.", prompt)
-            },
-            Err(_) => {
-                eprintln!("Timeout creating election announcement in guild {}", guild_id);
-                format!("⚠️ Election started but announcement timed out: '{}'. Please check the announcements channel.", prompt)
-            }
-        }
+    #[tokio::test]
+    async fn abstainers_count_toward_turnout_but_add_no_votes() {
+        let state = voting_state(&["Park", "Library"]).await;
+        *state.election.write().await = Some(ElectionInfo::default());
+        state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+        state.record_voter(user(1)).await;
+
+        assert_eq!(state.abstain(user(2)).await, Ok(true));
+        assert_eq!(state.abstain(user(2)).await, Ok(false));
+        // Voters have to retract before abstaining
+        assert!(matches!(state.abstain(user(1)).await, Err(CommandError::InvalidInput(_))));
+        assert_eq!((state.participant_count().await, state.abstention_count().await), (2, 1));
+        assert!(!state.points.read().await.contains_key(&user(2)));
+
+        let (standings, _, _) = state.finalize_votes(CONVENIENT_WINNERS, TieBreak::CoWinners, 0).await;
+        assert_eq!(standings.iter().map(|(_, _, votes)| votes).sum::<usize>(), 3);
     }
 
-    async fn slash_stop(&self, ctx: &Context, command: &CommandInteraction) -> String {
-        if let Some(guild_id) = command.guild_id {
-            if let Some(_guild) = ctx.cache.guild(guild_id) {
-                // Check admin permissions (role, administrator, or owner)
-                if !check_admin_permission!(ctx, guild_id, command.user) {
-                    return format!(
-                        "❌ You need one of the following to stop an election:\n• Server Owner\n• Administrator permission\n• '{}' role",
-                        BOT_ROLE
-                    );
-                }
-            }
+    #[tokio::test]
+    async fn members_can_vote_once_they_have_been_in_the_server_long_enough() {
+        let state = GuildState::default();
+        let now = 1_700_000_000;
+        let joined = |secs_ago: i64| Some(Timestamp::from_unix_timestamp(now - secs_ago).unwrap());
+        // No minimum by default
+        assert_eq!(check_membership(&state, joined(0), now).await, Ok(()));
+
+        state.config.write().await.min_membership_secs = 86_400;
+        assert_eq!(check_membership(&state, joined(86_400), now).await, Ok(()));
+        assert_eq!(check_membership(&state, joined(90_000), now).await, Ok(()));
+        assert_eq!(check_membership(&state, joined(86_399), now).await, Err(CommandError::MembershipTooNew { eligible_at: now + 1 }));
+        assert_eq!(check_membership(&state, joined(0), now).await, Err(CommandError::MembershipTooNew { eligible_at: now + 86_400 }));
+        // Unknown join times fail open
+        assert_eq!(check_membership(&state, None, now).await, Ok(()));
+    }
 
-            self.slash_stop_internal(ctx, guild_id).await
-        } else {
-            "❌ This command can only be used in a server!".to_string()
+    #[tokio::test]
+    async fn descriptions_are_still_listed_once_voting_starts() {
+        let state = GuildState::default();
+        state.update_votes(|voting| *voting.phase = Phase::Suggestion).await;
+        for (idea, author) in [("Bike racks", user(2)), ("Benches", user(3))] {
+            store_proposal(&state, idea, author, false).await.unwrap();
         }
-    }
+        state.descriptions.write().await.insert(1, "Covered racks by the station.\nThey'd hold 40 bikes.".to_string());
 
-    async fn slash_stop_internal(&self, ctx: &Context, guild_id: GuildId) -> String {
-        // Check if in suggestion period
-        if !self.upcoming_topics.get(&guild_id).unwrap().read().await.is_empty() {
-            // Move from suggestions to voting
-            let all_candidates: Vec<String> = self.upcoming_topics.get(&guild_id).unwrap().read().await.iter().cloned().collect();
-            
-            let mut candidates_str = String::new();
-            for (i, name) in all_candidates.iter().enumerate() {
-                self.votes.get(&guild_id).unwrap().write().await
-                    .insert(i, (name.clone(), AtomicUsize::new(0), HashMap::new()));
-                candidates_str = format!("{}#{}: {}\n", candidates_str, i + 1, name);
-            }
+        let (candidates, _) = state.start_voting_phase().await.unwrap();
+        assert_eq!(candidates, vec![(1, "Bike racks".to_string()), (2, "Benches".to_string())]);
+        assert_eq!(*state.phase.read().await, Phase::Voting);
+        assert_eq!(*state.candidate_authors.read().await, HashMap::from([(1, user(2)), (2, user(3))]));
 
-            // Clear suggestions
-            self.upcoming_topics.get(&guild_id).unwrap().write().await.clear();
+        let descriptions = state.descriptions.read().await;
+        let line = |id: usize, name: &str| list_line(id, name, None, descriptions.get(&id).map(String::as_str), false);
+        assert_eq!(line(1, "Bike racks"), "#1: Bike racks 📄\n> Covered racks by the station.…");
+        assert_eq!(line(2, "Benches"), "#2: Benches");
+    }
 
-            // Post voting message
-            let channel_id = ctx.cache.guild(guild_id)
-                .and_then(|guild| guild.channels.iter().find(|(_, ch)| ch.name == BOT_CHANNEL).map(|(id, _)| *id));
+    #[tokio::test]
+    async fn seeded_candidates_can_go_straight_to_voting() {
+        let seeds = parse_seeded(Some(" Bike racks ; Benches;;Trees "), true, &ElectionOptions::default(), user(9)).unwrap().unwrap();
+        assert_eq!(seeds.ideas, vec!["Bike racks", "Benches", "Trees"]);
+        assert!(seeds.vote_now);
+
+        let state = GuildState::default();
+        state.update_votes(|voting| voting.begin_suggestions()).await;
+        state.seed_proposals(seeds).await;
+        let (candidates, _) = state.start_voting_phase().await.unwrap();
+        assert_eq!(candidates, vec![(1, "Bike racks".to_string()), (2, "Benches".to_string()), (3, "Trees".to_string())]);
+        assert_eq!(*state.phase.read().await, Phase::Voting);
+        assert!(state.candidate_authors.read().await.values().all(|author| *author == user(9)));
+        assert_eq!(state.update_votes(|voting| voting.cast(user(1), 3, 2)).await, Ok((2, 96)));
+    }
 
-            if let Some(channel_id) = channel_id {
-                if let Ok(message) = channel_id.say(ctx, format!(
-                    "@everyone 🗳️ **Candidates selected:**\n{}\nVote with `/vote <votes> <candidate_number>`\n\n**Results so Far:**\nNo votes cast yet!",
-                    candidates_str
-                )).await {
-                    self.results.write().await.insert(guild_id, message);
-                }
-            }
+    #[test]
+    fn seeded_candidates_are_refused_whole_like_proposals() {
+        let options = ElectionOptions::default();
+        let parse = |list: &str| parse_seeded(Some(list), false, &options, user(9)).err();
+        assert_eq!(parse("Bike racks; Benches; Bike racks"), Some(CommandError::DuplicateProposal("Bike racks".to_string())));
+        let too_long = "x".repeat(MAX_PROPOSAL_LEN + 1);
+        assert_eq!(parse(&format!("Benches; {}", too_long)), Some(CommandError::TooLong { what: "Proposal ideas", max: MAX_PROPOSAL_LEN }));
+        assert_eq!(parse(&format!("Benches; {}", &too_long[1..])), None);
+        // Nothing listed is fine, unless voting is meant to open on the list
+        assert!(parse_seeded(Some(" ; "), false, &options, user(9)).is_ok_and(|seeds| seeds.is_none()));
+        assert!(matches!(parse_seeded(None, true, &options, user(9)), Err(CommandError::InvalidInput(_))));
+    }
 
-            "✅ Moved to voting phase!".to_string()
-        } else if !self.votes.get(&guild_id).unwrap().read().await.is_empty() {
-            // End voting and show results
-            let winners = self.winners(&guild_id).await.join("\n");
-            
-            let channel_id = ctx.cache.guild(guild_id)
-                .and_then(|guild| guild.channels.iter().find(|(_, ch)| ch.name == BOT_CHANNEL).map(|(id, _)| *id));
+    #[tokio::test]
+    async fn cancelling_for_a_new_start_discards_the_election_without_a_tally() {
+        let state = voting_state(&["Park", "Library"]).await;
+        *state.election.write().await = Some(ElectionInfo::default());
+        state.update_votes(|voting| voting.cast(user(1), 1, 3)).await.unwrap();
+        state.descriptions.write().await.insert(1, "By the river".to_string());
+        state.last_candidate_id.store(2, Ordering::Relaxed);
+
+        // What /config on-restart cancel does before the new election starts
+        state.discard().await;
+        assert_eq!(*state.phase.read().await, Phase::Idle);
+        assert!(state.votes.read().await.is_empty() && state.points.read().await.is_empty());
+        assert!(state.election.read().await.is_none() && state.descriptions.read().await.is_empty());
+        assert_eq!(state.last_candidate_id.load(Ordering::Relaxed), 0);
+        // Nothing was counted, unlike a finalized election
+        assert_eq!(state.analytics.read().await.elections, 0);
+        assert!(state.last_outcome.read().await.is_none());
+    }
 
-            if let Some(channel_id) = channel_id {
-                let _ = channel_id.say(ctx, format!(
-                    "@everyone 🏆 **The election is over!**\n\n**Winners:**\n{}",
-                    winners
-                )).await;
-            }
+    #[tokio::test]
+    async fn restored_proposals_keep_their_id_and_text() {
+        let state = GuildState::default();
+        state.update_votes(|voting| voting.begin_suggestions()).await;
+        for idea in ["Bike racks", "More bike racks", "Benches"] {
+            store_proposal(&state, idea, user(2), false).await.unwrap();
+        }
+        state.descriptions.write().await.insert(2, "Outside the library".to_string());
+        assert_eq!(state.restore_removed().await, Err(CommandError::NothingToRestore));
+
+        // As /merge removes a proposal
+        let (_, dropped) = merge_proposals(&mut *state.upcoming_topics.write().await, 1, 2).unwrap();
+        let description = state.descriptions.write().await.remove(&2);
+        state.remember_removed(RemovedProposal { proposal: dropped, description, link: None }).await;
+        assert_eq!(state.upcoming_topics.read().await.len(), 2);
+
+        assert_eq!(state.restore_removed().await, Ok((2, "More bike racks".to_string())));
+        let topics = state.upcoming_topics.read().await.iter().map(|p| (p.id, p.idea.clone(), p.author)).collect::<Vec<_>>();
+        assert_eq!(topics, vec![
+            (1, "Bike racks".to_string(), user(2)),
+            (2, "More bike racks".to_string(), user(2)),
+            (3, "Benches".to_string(), user(2)),
+        ]);
+        assert_eq!(state.descriptions.read().await.get(&2).map(String::as_str), Some("Outside the library"));
+        assert_eq!(state.restore_removed().await, Err(CommandError::NothingToRestore));
+    }
 
-            // Reset state
-            self.votes.get(&guild_id).unwrap().write().await.clear();
-            for (_user, points) in self.points.get(&guild_id).unwrap().read().await.iter() {
-                points.swap(STARTING_POINTS, Ordering::Relaxed);
-            }
+    #[tokio::test]
+    async fn only_web_links_are_stored_with_proposals() {
+        let submission = |link: &str| Submission { idea: "New logo".to_string(), description: None, link: Some(link.to_string()) };
+        for link in ["javascript:alert(1)", "ftp://example.com/logo.png", "https://", "https://exa mple.com", "https://<@1>", "example.com/logo.png"] {
+            assert!(matches!(submission(link).check(), Err(CommandError::InvalidInput(_))), "{}", link);
+        }
+        let too_long = format!("https://example.com/{}", "a".repeat(MAX_LINK_LEN));
+        assert_eq!(submission(&too_long).check(), Err(CommandError::TooLong { what: "Links", max: MAX_LINK_LEN }));
+
+        let state = GuildState::default();
+        state.update_votes(|voting| voting.begin_suggestions()).await;
+        let valid = submission("https://example.com/logos/round.png?v=2");
+        assert_eq!(valid.check(), Ok(()));
+        let id = store_submission(&state, valid, user(2), false).await.unwrap();
+        assert_eq!(state.links.read().await.get(&id).map(String::as_str), Some("https://example.com/logos/round.png?v=2"));
+        assert!(submission("http://example.com").check().is_ok());
+    }
 
-            "✅ Election completed and results announced!".to_string()
-        } else {
-            "❌ No active election to stop!".to_string()
+    #[tokio::test]
+    async fn only_the_top_candidates_go_through_to_the_next_round() {
+        let state = voting_state(&["Park", "Library", "Pool", "Trees"]).await;
+        for (voter, candidate, votes) in [(1, 3, 4), (2, 2, 3), (3, 1, 2), (4, 4, 1)] {
+            state.update_votes(|voting| voting.cast(user(voter), candidate, votes)).await.unwrap();
         }
+        assert_eq!(state.next_round(2).await, Err(CommandError::ElectionRunning));
+
+        let (standings, _, outcome) = state.finalize_votes(2, TieBreak::CoWinners, 0).await;
+        let Outcome::Decided(winners) = outcome else { panic!("no runoff expected") };
+        let ranking = final_ranking(&winners, &standings);
+        assert_eq!(ranking, vec!["Pool", "Library", "Park", "Trees"]);
+        *state.last_outcome.write().await = Some(FinishedElection { prompt: Some("Spring budget".to_string()), ranking });
+
+        assert!(matches!(state.next_round(5).await, Err(CommandError::InvalidInput(_))));
+        let (prompt, ideas) = state.next_round(3).await.unwrap();
+        assert_eq!(prompt, "Spring budget (round 2)");
+        assert_eq!(ideas, vec!["Pool", "Library", "Park"]);
+
+        // The follow-up opens on exactly those candidates, with fresh credits
+        state.update_votes(|voting| voting.begin_suggestions()).await;
+        state.last_candidate_id.store(0, Ordering::Relaxed);
+        state.seed_proposals(SeededCandidates { ideas, vote_now: true, author: user(9) }).await;
+        reset_credits(&state).await;
+        let (candidates, _) = state.start_voting_phase().await.unwrap();
+        assert_eq!(candidates, vec![(1, "Pool".to_string()), (2, "Library".to_string()), (3, "Park".to_string())]);
+        assert_eq!(state.update_votes(|voting| voting.balance(user(1))).await, STARTING_POINTS);
     }
-}
 
-#[tokio::main]
-async fn main() {
-    // Above var can be in proc variables or .env
-    dotenv().ok();
+    #[test]
+    fn durations_are_capped_at_30_days() {
+        let too_long = Err(CommandError::OutOfRange { what: "The duration", min: 1, max: 720, unit: " hours" });
+        assert_eq!(parse_duration_hours("30d"), Ok(720));
+        assert_eq!(parse_duration_hours("720"), Ok(720));
+        assert_eq!(parse_duration_hours("29d23h30m"), Ok(720));
+        assert_eq!(parse_duration_hours("30d1h"), too_long);
+        assert_eq!(parse_duration_hours("720h1s"), too_long);
+        assert_eq!(parse_duration_hours("5w"), too_long);
+        assert!(matches!(parse_duration_hours("0h"), Err(CommandError::InvalidInput(_))));
+        assert_eq!(parse_duration_hours("90m"), Ok(2));
+    }
 
-    // The discord API key is necessary for the bot to function
-    let token =
-        env::var(SECRET_KEY).expect(formatcp!("missing discord API secret in {}", SECRET_KEY));
+    #[tokio::test]
+    async fn stopping_an_idle_guild_leaves_its_credits_and_proposals_alone() {
+        let state = GuildState::default();
+        state.points.write().await.insert(user(2), AtomicUsize::new(7));
+        state.carried.write().await.insert(user(2), 3);
+        state.delegated.write().await.insert(user(3), 5);
+        state.upcoming_topics.write().await.push(Proposal { id: 1, idea: "Bike racks".to_string(), author: user(2) });
+
+        assert_eq!(phase_to_stop(&state).await, Err(CommandError::NoElection));
+        assert_eq!(*state.phase.read().await, Phase::Idle);
+        assert_eq!(state.points.read().await[&user(2)].load(Ordering::SeqCst), 7);
+        assert_eq!(*state.carried.read().await, HashMap::from([(user(2), 3)]));
+        assert_eq!(*state.delegated.read().await, HashMap::from([(user(3), 5)]));
+        assert_eq!(state.upcoming_topics.read().await.len(), 1);
+
+        open_ballot(&state, &["Bike racks"]).await;
+        assert_eq!(phase_to_stop(&state).await, Ok(Phase::Voting));
+    }
 
-    // Parse approved servers from environment variable
-    let approved_servers_str = env::var(APPROVED_SERVERS_KEY)
-        .expect(formatcp!("missing {} in environment variables", APPROVED_SERVERS_KEY));
-    
-    let approved_servers: Vec<GuildId> = approved_servers_str
-        .split(',')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| GuildId::new(s.parse::<u64>().expect(&format!("Invalid server ID: {}", s))))
-        .collect();
+    #[test]
+    fn only_the_winners_proposers_are_mentioned_each_once() {
+        let winners = vec![(4, "Parks".to_string(), 9), (2, "Benches".to_string(), 7), (7, "Lights".to_string(), 7), (5, "Trees".to_string(), 3)];
+        // Bike racks (1) lost; Trees (5) has no known author
+        let authors = HashMap::from([(1, user(10)), (2, user(20)), (4, user(30)), (7, user(20))]);
+        assert_eq!(winning_proposers(&winners, &authors), vec![user(30), user(20)]);
+        assert_eq!(winning_proposers(&winners[..1], &authors), vec![user(30)]);
+        assert_eq!(winning_proposers(&[], &authors), Vec::<UserId>::new());
+    }
 
-    println!("Bot configured for {} server(s): {:?}", approved_servers.len(), approved_servers);
+    fn proposals(ideas: &[&str]) -> Vec<Proposal> {
+        ideas.iter().enumerate().map(|(i, idea)| Proposal { id: i + 1, idea: idea.to_string(), author: user(1) }).collect()
+    }
 
-    let handler = <Handler as Default>::default().register_servers(approved_servers);
+    #[test]
+    fn merging_removes_only_the_dropped_proposal() {
+        let mut topics = proposals(&["Bike racks", "More bike racks", "Benches"]);
+        let (kept, dropped) = merge_proposals(&mut topics, 1, 2).unwrap();
+        assert_eq!(kept, "Bike racks");
+        assert_eq!((dropped.id, dropped.idea.as_str()), (2, "More bike racks"));
+        // The other IDs stay valid
+        assert_eq!(topics.iter().map(|p| p.id).collect::<Vec<usize>>(), vec![1, 3]);
+    }
 
-    // Set gateway intents for slash commands and guild operations
-    let intents = GatewayIntents::GUILDS
-        | GatewayIntents::GUILD_MEMBERS
-        | GatewayIntents::GUILD_MESSAGES;
+    #[test]
+    fn merging_needs_two_existing_different_proposals() {
+        let mut topics = proposals(&["Bike racks", "Benches"]);
+        assert!(matches!(merge_proposals(&mut topics, 1, 1), Err(CommandError::InvalidInput(_))));
+        assert!(matches!(merge_proposals(&mut topics, 5, 1), Err(CommandError::NoSuchProposal(5))));
+        assert!(matches!(merge_proposals(&mut topics, 1, 5), Err(CommandError::NoSuchProposal(5))));
+        assert_eq!(topics.len(), 2);
+    }
 
-    // Run the bot
-    Client::builder(token, intents)
-        .event_handler(handler)
-        .await
-        .expect("failed to create client")
-        .start()
-        .await
-        .unwrap();
+    #[tokio::test]
+    async fn going_back_to_the_usual_winners_message_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("qvoting-winners-message-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let guild_id = GuildId::new(1);
+        let restart = || Handler::default().register_servers(vec![guild_id]).restore(Store::new(&path));
+        let winners_message = |handler: Handler| async move {
+            handler.guild_state(&guild_id).await.unwrap().config.read().await.winners_message.clone()
+        };
+
+        let handler = restart();
+        let state = handler.guild_state(&guild_id).await.unwrap();
+        state.set_winners_message("🎉 {winners}").await.unwrap();
+        handler.persist().await;
+        assert_eq!(winners_message(restart()).await, Some("🎉 {winners}".to_string()));
+
+        state.set_winners_message("default").await.unwrap();
+        handler.persist().await;
+        assert_eq!(winners_message(restart()).await, None);
+        std::fs::remove_file(&path).unwrap();
+    }
 }
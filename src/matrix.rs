@@ -0,0 +1,167 @@
+//! The pseudonymous vote matrix exported with `/export-matrix` for research: how many
+//! votes each voter gave each candidate, with voters renamed `voter-1`, `voter-2`, …
+//! Discord IDs never leave the bot.
+
+use crate::voting::CandidateVotes;
+use serde::Serialize;
+use serenity::all::UserId;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::sync::atomic::Ordering;
+
+/// File formats `/export-matrix` can produce
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatrixFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl MatrixFormat {
+    /// Every format, as accepted by `/export-matrix`
+    pub const ALL: [MatrixFormat; 2] = [MatrixFormat::Csv, MatrixFormat::Json];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MatrixFormat::Csv => "csv",
+            MatrixFormat::Json => "json",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+}
+
+/// A vote matrix with voters already replaced by pseudonyms
+#[derive(Serialize)]
+pub struct VoteMatrix {
+    /// Candidate names, by candidate ID
+    candidates: BTreeMap<usize, String>,
+
+    /// Votes per candidate ID, by pseudonym
+    voters: BTreeMap<String, BTreeMap<usize, usize>>,
+}
+
+impl VoteMatrix {
+    /// Builds the matrix from a tally. Pseudonyms are numbered in an order drawn
+    /// fresh for each export, so they say nothing about the member behind them and
+    /// two exports can't be matched up voter by voter.
+    pub fn pseudonymize(votes: &HashMap<usize, CandidateVotes>) -> Self {
+        let mut users = votes
+            .values()
+            .flat_map(|(_, _, voters)| voters.iter().filter(|(_, count)| count.load(Ordering::Relaxed) > 0).map(|(user_id, _)| *user_id))
+            .collect::<HashSet<UserId>>()
+            .into_iter()
+            .collect::<Vec<UserId>>();
+        let order = RandomState::new();
+        users.sort_by_key(|user_id| order.hash_one(user_id));
+        let pseudonyms = users
+            .into_iter()
+            .enumerate()
+            .map(|(i, user_id)| (user_id, format!("voter-{}", i + 1)))
+            .collect::<HashMap<UserId, String>>();
+
+        let mut voters: BTreeMap<String, BTreeMap<usize, usize>> = BTreeMap::new();
+        for (id, (_, _, candidate_voters)) in votes {
+            for (user_id, count) in candidate_voters {
+                let count = count.load(Ordering::Relaxed);
+                if count > 0 {
+                    voters.entry(pseudonyms[user_id].clone()).or_default().insert(*id, count);
+                }
+            }
+        }
+
+        Self {
+            candidates: votes.iter().map(|(id, (name, _, _))| (*id, name.clone())).collect(),
+            voters,
+        }
+    }
+
+    /// Number of voters in the matrix.
+    pub fn voter_count(&self) -> usize {
+        self.voters.len()
+    }
+
+    /// Renders the matrix in the given format.
+    pub fn render(&self, format: MatrixFormat) -> String {
+        match format {
+            MatrixFormat::Csv => self.to_csv(),
+            MatrixFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+        }
+    }
+
+    /// One `voter,candidate_id,candidate,votes` row per voter and candidate they voted for.
+    fn to_csv(&self) -> String {
+        let mut lines = vec!["voter,candidate_id,candidate,votes".to_string()];
+        for (voter, votes) in &self.voters {
+            for (id, count) in votes {
+                lines.push(format!("{},{},{},{}", voter, id, csv_field(&self.candidates[id]), count));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// A tally with the given (user ID, votes) backers per candidate.
+    fn tally(candidates: &[(&str, &[(u64, usize)])]) -> HashMap<usize, CandidateVotes> {
+        candidates
+            .iter()
+            .enumerate()
+            .map(|(i, (name, backers))| {
+                let voters = backers.iter().map(|&(id, votes)| (UserId::new(id), AtomicUsize::new(votes))).collect::<HashMap<UserId, AtomicUsize>>();
+                let total = backers.iter().map(|(_, votes)| votes).sum();
+                (i + 1, (name.to_string(), AtomicUsize::new(total), voters))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exports_never_contain_real_user_ids() {
+        let ids = [285_417_096_338_915_328, 731_902_266_505_330_788, 912_004_551_203_776_512];
+        let votes = tally(&[
+            ("Bike racks", &[(ids[0], 3), (ids[1], 1)]),
+            ("Benches, \"covered\"", &[(ids[1], 2), (ids[2], 0)]),
+        ]);
+        let matrix = VoteMatrix::pseudonymize(&votes);
+        // A voter whose only votes were taken back isn't in the matrix
+        assert_eq!(matrix.voter_count(), 2);
+
+        for format in MatrixFormat::ALL {
+            let export = matrix.render(format);
+            for id in ids {
+                assert!(!export.contains(&id.to_string()), "{}", export);
+            }
+            assert!(export.contains("voter-1") && export.contains("voter-2") && !export.contains("voter-3"), "{}", export);
+        }
+
+        let csv = matrix.render(MatrixFormat::Csv);
+        assert_eq!(csv.lines().next(), Some("voter,candidate_id,candidate,votes"));
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains(",2,\"Benches, \"\"covered\"\"\",2\n"), "{}", csv);
+    }
+
+    #[test]
+    fn each_voter_keeps_one_pseudonym_across_candidates() {
+        let votes = tally(&[("Bike racks", &[(11, 3), (22, 1)]), ("Benches", &[(11, 2)]), ("Trees", &[(11, 1), (22, 4)])]);
+        let matrix = VoteMatrix::pseudonymize(&votes);
+        let mut ballots = matrix.voters.values().cloned().collect::<Vec<BTreeMap<usize, usize>>>();
+        ballots.sort();
+        assert_eq!(ballots, vec![BTreeMap::from([(1, 1), (3, 4)]), BTreeMap::from([(1, 3), (2, 2), (3, 1)])]);
+    }
+}
@@ -0,0 +1,94 @@
+//! Paces the messages the bot sends and edits in each channel, so a burst of votes
+//! queues up behind Discord's per-channel rate limit instead of failing on it.
+
+use serenity::all::ChannelId;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Messages that can go out back to back in a quiet channel
+pub const BURST: f64 = 5.0;
+
+/// Time for one more message to be allowed once the burst is used up
+pub const REFILL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A token bucket for one channel. Tokens go negative while requests are queued,
+/// so each waiter reserves its own slot and they go out in order.
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets for every channel the bot writes to
+#[derive(Default)]
+pub struct ChannelPacer {
+    buckets: Mutex<HashMap<ChannelId, Bucket>>,
+}
+
+impl ChannelPacer {
+    /// Waits until a message may be sent or edited in the channel.
+    pub async fn acquire(&self, channel_id: ChannelId) {
+        let wait = {
+            let mut buckets = self.buckets.lock().await;
+            let now = Instant::now();
+            let bucket = buckets.entry(channel_id).or_insert(Bucket { tokens: BURST, updated: now });
+
+            let refilled = now.duration_since(bucket.updated).as_secs_f64() / REFILL_INTERVAL.as_secs_f64();
+            bucket.tokens = (bucket.tokens + refilled).min(BURST) - 1.0;
+            bucket.updated = now;
+
+            if bucket.tokens >= 0.0 {
+                Duration::ZERO
+            } else {
+                REFILL_INTERVAL.mul_f64(-bucket.tokens)
+            }
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn a_burst_goes_out_at_once_and_the_rest_are_paced() {
+        let pacer = ChannelPacer::default();
+        let channel_id = ChannelId::new(1);
+        let start = Instant::now();
+
+        for _ in 0..BURST as usize {
+            pacer.acquire(channel_id).await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        pacer.acquire(channel_id).await;
+        assert_eq!(start.elapsed(), REFILL_INTERVAL);
+        pacer.acquire(channel_id).await;
+        assert_eq!(start.elapsed(), REFILL_INTERVAL * 2);
+
+        // Other channels have their own bucket
+        pacer.acquire(ChannelId::new(2)).await;
+        assert_eq!(start.elapsed(), REFILL_INTERVAL * 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_quiet_channel_refills_its_burst() {
+        let pacer = ChannelPacer::default();
+        let channel_id = ChannelId::new(1);
+        for _ in 0..BURST as usize {
+            pacer.acquire(channel_id).await;
+        }
+
+        tokio::time::advance(REFILL_INTERVAL * 10).await;
+        let start = Instant::now();
+        for _ in 0..BURST as usize {
+            pacer.acquire(channel_id).await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
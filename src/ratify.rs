@@ -0,0 +1,64 @@
+//! Opt-in ratification of an election's outcome: members react to the winners
+//! announcement with ✅ or ❌, and once the window closes the bot posts whether the
+//! outcome was ratified. This is a plain majority of members, separate from the
+//! quadratic vote that picked the winners.
+
+use serenity::all::{ChannelId, Http, MessageId, ReactionType, UserId};
+use std::collections::HashSet;
+
+/// Reaction members add to ratify the outcome
+pub const RATIFY_EMOJI: &str = "✅";
+
+/// Reaction members add to reject the outcome
+pub const REJECT_EMOJI: &str = "❌";
+
+/// Longest ratification window `/config ratification` accepts, one week
+pub const MAX_RATIFY_WINDOW_MINS: u64 = 7 * 24 * 60;
+
+/// Most users Discord returns per page of a reaction's users
+const REACTION_PAGE_SIZE: u8 = 100;
+
+/// The members for and against an outcome
+pub struct Tally {
+    pub for_votes: usize,
+    pub against_votes: usize,
+}
+
+impl Tally {
+    /// Whether more members ratified the outcome than rejected it.
+    pub fn ratified(&self) -> bool {
+        self.for_votes > self.against_votes
+    }
+}
+
+/// Counts the ✅ and ❌ reactions on the winners announcement. Bots are ignored, and
+/// members who reacted with both count for neither side.
+pub async fn tally(http: &Http, channel_id: ChannelId, message_id: MessageId) -> serenity::Result<Tally> {
+    let for_users = reaction_users(http, channel_id, message_id, RATIFY_EMOJI).await?;
+    let against_users = reaction_users(http, channel_id, message_id, REJECT_EMOJI).await?;
+
+    Ok(Tally {
+        for_votes: for_users.difference(&against_users).count(),
+        against_votes: against_users.difference(&for_users).count(),
+    })
+}
+
+async fn reaction_users(http: &Http, channel_id: ChannelId, message_id: MessageId, emoji: &str) -> serenity::Result<HashSet<UserId>> {
+    let mut users = HashSet::new();
+    let mut after = None;
+    loop {
+        let page = channel_id
+            .reaction_users(http, message_id, ReactionType::Unicode(emoji.to_string()), Some(REACTION_PAGE_SIZE), after)
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some(last.id);
+        let full_page = page.len() == REACTION_PAGE_SIZE as usize;
+        users.extend(page.into_iter().filter(|user| !user.bot).map(|user| user.id));
+        if !full_page {
+            break;
+        }
+    }
+    Ok(users)
+}
@@ -0,0 +1,58 @@
+//! What `/start` does when an election is already running, set with
+//! `/config on-restart`. Scheduled and template starts follow the same policy.
+
+use serde::{Deserialize, Serialize};
+
+/// How a new election treats the one still running
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Ends the running election as `/stop` would, announcing its winners
+    Finalize,
+
+    /// Discards the running election as `/cancel` would, without announcing winners
+    Cancel,
+
+    /// Refuses to start until the running election is stopped or cancelled
+    #[default]
+    Reject,
+}
+
+impl RestartPolicy {
+    /// Every policy, as accepted by `/config on-restart`
+    pub const ALL: [RestartPolicy; 3] = [RestartPolicy::Finalize, RestartPolicy::Cancel, RestartPolicy::Reject];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RestartPolicy::Finalize => "finalize",
+            RestartPolicy::Cancel => "cancel",
+            RestartPolicy::Reject => "reject",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|policy| policy.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_are_refused_unless_a_guild_chooses_otherwise() {
+        assert_eq!(RestartPolicy::default(), RestartPolicy::Reject);
+    }
+
+    #[test]
+    fn each_policy_is_saved_under_its_config_name() {
+        for policy in RestartPolicy::ALL {
+            assert_eq!(RestartPolicy::parse(policy.name()), Some(policy));
+            // The saved config uses the same names as /config on-restart
+            assert_eq!(serde_json::to_string(&policy).unwrap(), format!("\"{}\"", policy.name()));
+            assert_eq!(serde_json::from_str::<RestartPolicy>(&format!("\"{}\"", policy.name())).unwrap(), policy);
+        }
+        assert_eq!(RestartPolicy::parse("Finalize"), None);
+        assert_eq!(RestartPolicy::parse("stop"), None);
+    }
+}
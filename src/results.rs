@@ -0,0 +1,98 @@
+//! How the live standings are written out on the announcement, chosen per guild
+//! with `/config results-format`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::tiebreak::Ranked;
+
+/// How much detail each line of the live standings shows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResultsFormat {
+    /// `Name: votes`
+    #[default]
+    Compact,
+
+    /// Rank, votes, share of all votes and number of voters
+    Detailed,
+}
+
+impl ResultsFormat {
+    /// Every format, as accepted by `/config results-format`
+    pub const ALL: [ResultsFormat; 2] = [ResultsFormat::Compact, ResultsFormat::Detailed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ResultsFormat::Compact => "compact",
+            ResultsFormat::Detailed => "detailed",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+}
+
+/// Writes out the top of the standings, one line per candidate. `voters` counts the
+/// members who voted for each candidate, and `total_votes` is the sum over every
+/// candidate, including those not shown.
+pub fn render(top: &[Ranked], voters: &HashMap<usize, usize>, total_votes: usize, format: ResultsFormat) -> Vec<String> {
+    match format {
+        ResultsFormat::Compact => top.iter().map(|(_, name, votes)| format!("{}: {}", name, votes)).collect(),
+        ResultsFormat::Detailed => top
+            .iter()
+            .enumerate()
+            .map(|(i, (id, name, votes))| {
+                let share = (votes * 100).checked_div(total_votes).unwrap_or(0);
+                let voter_count = voters.get(id).copied().unwrap_or(0);
+                format!(
+                    "{}. {}: {} votes ({}%) from {} {}",
+                    i + 1,
+                    name,
+                    votes,
+                    share,
+                    voter_count,
+                    if voter_count == 1 { "voter" } else { "voters" }
+                )
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn top() -> Vec<Ranked> {
+        vec![(2, "Benches".to_string(), 6), (1, "Bike racks".to_string(), 3)]
+    }
+
+    #[test]
+    fn compact_lines_show_names_and_votes() {
+        let voters = HashMap::from([(1, 1), (2, 3)]);
+        assert_eq!(render(&top(), &voters, 10, ResultsFormat::Compact), vec!["Benches: 6", "Bike racks: 3"]);
+    }
+
+    #[test]
+    fn detailed_lines_add_rank_share_and_voters() {
+        let voters = HashMap::from([(1, 1), (2, 3)]);
+        // The share counts votes for candidates that aren't shown too
+        assert_eq!(
+            render(&top(), &voters, 10, ResultsFormat::Detailed),
+            vec!["1. Benches: 6 votes (60%) from 3 voters", "2. Bike racks: 3 votes (30%) from 1 voter"]
+        );
+        assert_eq!(
+            render(&[(1, "Bike racks".to_string(), 0)], &HashMap::new(), 0, ResultsFormat::Detailed),
+            vec!["1. Bike racks: 0 votes (0%) from 0 voters"]
+        );
+    }
+
+    #[test]
+    fn formats_parse_from_their_names() {
+        for format in ResultsFormat::ALL {
+            assert_eq!(ResultsFormat::parse(format.name()), Some(format));
+        }
+        assert_eq!(ResultsFormat::parse("full"), None);
+    }
+}
@@ -0,0 +1,154 @@
+//! How persistently the bot retries channel announcements. The defaults suit most
+//! deployments; slow or busy ones can give Discord more time or more tries with the
+//! environment variables below, read once at startup.
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// Environment variable holding how many times an announcement is attempted
+pub const ANNOUNCE_ATTEMPTS_KEY: &str = "ANNOUNCE_ATTEMPTS";
+
+/// Environment variable holding how long one attempt may take, in seconds
+pub const ANNOUNCE_TIMEOUT_KEY: &str = "ANNOUNCE_TIMEOUT_SECS";
+
+/// Environment variable holding the pause between attempts, in milliseconds
+pub const ANNOUNCE_RETRY_DELAY_KEY: &str = "ANNOUNCE_RETRY_DELAY_MS";
+
+/// Most attempts the environment can ask for, so a typo can't stall a command for minutes
+const MAX_ATTEMPTS: u32 = 10;
+
+/// Retry settings for sending an announcement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts before giving up, at least 1
+    pub attempts: u32,
+
+    /// How long a single attempt may take
+    pub timeout: Duration,
+
+    /// Pause before each retry
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 2,
+            timeout: Duration::from_secs(8),
+            delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Reads the announcement settings from the environment. Unset values keep their
+    /// defaults; invalid ones are reported and ignored.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            attempts: read_env(ANNOUNCE_ATTEMPTS_KEY, 1..=MAX_ATTEMPTS as u64).map_or(default.attempts, |n| n as u32),
+            timeout: read_env(ANNOUNCE_TIMEOUT_KEY, 1..=60).map_or(default.timeout, Duration::from_secs),
+            delay: read_env(ANNOUNCE_RETRY_DELAY_KEY, 0..=60_000).map_or(default.delay, Duration::from_millis),
+        }
+    }
+
+    /// Runs `attempt` until it succeeds, each try under the timeout. Failures and
+    /// timeouts are logged under `what`; returns `None` once every attempt has failed.
+    pub async fn run<T, E, F, Fut>(&self, what: &str, mut attempt: F) -> Option<T>
+    where
+        E: Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        for n in 1..=self.attempts.max(1) {
+            if n > 1 {
+                tokio::time::sleep(self.delay).await;
+            }
+            match tokio::time::timeout(self.timeout, attempt()).await {
+                Ok(Ok(value)) => return Some(value),
+                Ok(Err(e)) => eprintln!("Discord API error in {} (attempt {}): {}", what, n, e),
+                Err(_) => eprintln!("Timed out in {} (attempt {}) - Discord API took too long", what, n),
+            }
+        }
+        None
+    }
+}
+
+/// Reads a whole number from the environment, warning when it's set but unusable.
+fn read_env(key: &str, range: std::ops::RangeInclusive<u64>) -> Option<u64> {
+    let value = std::env::var(key).ok()?;
+    match value.trim().parse::<u64>() {
+        Ok(n) if range.contains(&n) => Some(n),
+        _ => {
+            eprintln!("Ignoring {}={:?}: expected a whole number from {} to {}", key, value, range.start(), range.end());
+            None
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy { attempts: 3, timeout: Duration::from_secs(8), delay: Duration::from_millis(500) }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn announcements_give_up_once_every_attempt_failed() {
+        let tries = Cell::new(0);
+        let result = policy()
+            .run("test", || async {
+                tries.set(tries.get() + 1);
+                Err::<(), _>("unavailable")
+            })
+            .await;
+        assert_eq!(result, None);
+        assert_eq!(tries.get(), 3);
+
+        // Attempts that hang are cut off by the timeout and count as failures too
+        let tries = Cell::new(0);
+        let result = policy()
+            .run("test", || async {
+                tries.set(tries.get() + 1);
+                std::future::pending::<Result<(), &str>>().await
+            })
+            .await;
+        assert_eq!(result, None);
+        assert_eq!(tries.get(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn announcements_stop_retrying_once_one_succeeds() {
+        let tries = Cell::new(0);
+        let result = policy()
+            .run("test", || async {
+                tries.set(tries.get() + 1);
+                if tries.get() < 2 { Err("unavailable") } else { Ok(tries.get()) }
+            })
+            .await;
+        assert_eq!(result, Some(2));
+        assert_eq!(tries.get(), 2);
+    }
+
+    #[test]
+    fn settings_out_of_range_fall_back_to_the_defaults() {
+        std::env::set_var(ANNOUNCE_ATTEMPTS_KEY, "0");
+        std::env::set_var(ANNOUNCE_TIMEOUT_KEY, "61");
+        std::env::set_var(ANNOUNCE_RETRY_DELAY_KEY, "soon");
+        assert_eq!(RetryPolicy::from_env(), RetryPolicy::default());
+
+        std::env::set_var(ANNOUNCE_ATTEMPTS_KEY, "10");
+        std::env::set_var(ANNOUNCE_TIMEOUT_KEY, " 60 ");
+        std::env::set_var(ANNOUNCE_RETRY_DELAY_KEY, "0");
+        assert_eq!(RetryPolicy::from_env(), RetryPolicy { attempts: 10, timeout: Duration::from_secs(60), delay: Duration::ZERO });
+
+        std::env::set_var(ANNOUNCE_ATTEMPTS_KEY, "11");
+        assert_eq!(RetryPolicy::from_env().attempts, RetryPolicy::default().attempts);
+        for key in [ANNOUNCE_ATTEMPTS_KEY, ANNOUNCE_TIMEOUT_KEY, ANNOUNCE_RETRY_DELAY_KEY] {
+            std::env::remove_var(key);
+        }
+    }
+}
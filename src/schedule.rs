@@ -0,0 +1,114 @@
+//! Parsing and bookkeeping for elections queued with `/schedule`.
+
+use serde::{Deserialize, Serialize};
+use serenity::model::Timestamp;
+
+/// The furthest ahead an election may be scheduled (30 days)
+pub const MAX_SCHEDULE_HORIZON_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// An election queued to start at a later time
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledStart {
+    /// The election prompt, as it would be passed to `/start`
+    pub prompt: String,
+
+    /// Unix timestamp (in seconds) at which the election starts
+    pub start_at: i64,
+
+    /// Length of the suggestion phase in hours, when not the default
+    pub duration_hours: Option<u64>,
+}
+
+/// Current time as a unix timestamp in seconds.
+pub fn now_unix() -> i64 {
+    Timestamp::now().unix_timestamp()
+}
+
+/// Parses a relative duration such as `90m`, `2h` or `1d12h` into seconds.
+/// A bare number is read as hours.
+pub fn parse_duration(input: &str) -> Option<u64> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+
+    if let Ok(hours) = input.parse::<u64>() {
+        return hours.checked_mul(60 * 60);
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        let amount = number.parse::<u64>().ok()?;
+        total = total.checked_add(amount.checked_mul(unit)?)?;
+        number.clear();
+    }
+
+    // Trailing digits without a unit are ambiguous
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+
+    Some(total)
+}
+
+/// Formats seconds the way `parse_duration` reads them, e.g. `1d 12h`. Units
+/// that are zero are left out.
+pub fn format_duration(secs: u64) -> String {
+    let units = [(7 * 24 * 60 * 60, 'w'), (24 * 60 * 60, 'd'), (60 * 60, 'h'), (60, 'm'), (1, 's')];
+    let mut rest = secs;
+    let mut parts = Vec::new();
+    for (size, unit) in units {
+        if rest >= size {
+            parts.push(format!("{}{}", rest / size, unit));
+            rest %= size;
+        }
+    }
+    if parts.is_empty() {
+        return "0s".to_string();
+    }
+    parts.join(" ")
+}
+
+/// Parses a start time given either as an ISO 8601 / RFC 3339 timestamp
+/// (`2025-06-01T18:00:00Z`) or relative to now (`2h`, `in 1d`).
+pub fn parse_start_time(input: &str, now: i64) -> Result<i64, String> {
+    let input = input.trim();
+
+    let start_at = if let Ok(timestamp) = Timestamp::parse(input) {
+        timestamp.unix_timestamp()
+    } else if let Some(offset) = parse_duration(input.strip_prefix("in ").unwrap_or(input)) {
+        now.saturating_add(i64::try_from(offset).unwrap_or(i64::MAX))
+    } else {
+        return Err(format!(
+            "Couldn't understand the time '{}'. Use ISO 8601 (e.g. `2025-06-01T18:00:00Z`) or a relative time (e.g. `2h`, `1d12h`).",
+            input
+        ));
+    };
+
+    if start_at <= now {
+        return Err("The scheduled time must be in the future!".to_string());
+    }
+
+    if start_at - now > MAX_SCHEDULE_HORIZON_SECS {
+        return Err(format!(
+            "Elections can be scheduled at most {} days ahead!",
+            MAX_SCHEDULE_HORIZON_SECS / (24 * 60 * 60)
+        ));
+    }
+
+    Ok(start_at)
+}
@@ -0,0 +1,69 @@
+//! Where elections live in a guild: one election for the whole guild (the default),
+//! or an independent election in each channel, set with `/config election-scope`.
+
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, GuildId};
+use std::fmt;
+
+/// Whether a guild runs one election, or one per channel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ElectionScope {
+    /// A single election, announced in the announcements channel
+    #[default]
+    Guild,
+
+    /// Each channel hosts its own election, announced in that channel
+    Channel,
+}
+
+impl ElectionScope {
+    /// Every scope, as accepted by `/config election-scope`
+    pub const ALL: [ElectionScope; 2] = [ElectionScope::Guild, ElectionScope::Channel];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ElectionScope::Guild => "guild",
+            ElectionScope::Channel => "channel",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.name() == name)
+    }
+}
+
+/// Identifies one election: the guild's, or one of its channels'
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ElectionKey {
+    pub guild_id: GuildId,
+
+    /// `None` for the guild-wide election
+    pub channel_id: Option<ChannelId>,
+}
+
+impl ElectionKey {
+    /// The guild-wide election.
+    pub fn guild(guild_id: GuildId) -> Self {
+        Self { guild_id, channel_id: None }
+    }
+
+    /// The election a command run in `channel_id` is about, under the given scope.
+    pub fn resolve(guild_id: GuildId, channel_id: ChannelId, scope: ElectionScope) -> Self {
+        match scope {
+            ElectionScope::Guild => Self::guild(guild_id),
+            ElectionScope::Channel => Self { guild_id, channel_id: Some(channel_id) },
+        }
+    }
+}
+
+/// Written as the guild ID, followed by the channel for channel elections, so log
+/// lines such as "in guild {}" read naturally either way.
+impl fmt::Display for ElectionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.channel_id {
+            Some(channel_id) => write!(f, "{} (channel {})", self.guild_id, channel_id),
+            None => write!(f, "{}", self.guild_id),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Per-member starting credits read from a snapshot file when an election starts,
+//! e.g. token holdings exported from another system. Each guild's snapshot lives
+//! in the snapshot directory as `<guild_id>.json` or `<guild_id>.csv`.
+
+use serde::{Deserialize, Serialize};
+use serenity::all::{GuildId, UserId};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Where snapshots are read from when no directory is configured
+pub const DEFAULT_SNAPSHOT_DIR: &str = "data/credits";
+
+/// Most problems listed when a snapshot is refused
+const MAX_PROBLEMS_SHOWN: usize = 5;
+
+/// Whether elections use a snapshot, and what members missing from it get
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnapshotMode {
+    /// Everyone gets the usual starting credits
+    #[default]
+    Off,
+
+    /// Members missing from the snapshot get the usual starting credits
+    Starting,
+
+    /// Members missing from the snapshot get no credits
+    Zero,
+}
+
+impl SnapshotMode {
+    /// Every mode, as accepted by `/config credit-snapshot`
+    pub const ALL: [SnapshotMode; 3] = [SnapshotMode::Off, SnapshotMode::Starting, SnapshotMode::Zero];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SnapshotMode::Off => "off",
+            SnapshotMode::Starting => "starting",
+            SnapshotMode::Zero => "zero",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
+/// The starting credits of the members listed in a guild's snapshot
+pub struct CreditSnapshot {
+    credits: HashMap<UserId, usize>,
+    mode: SnapshotMode,
+}
+
+impl CreditSnapshot {
+    /// Reads the guild's snapshot from the directory, refusing it whole if any
+    /// line is invalid. Errors list what's wrong, with line numbers for CSV files.
+    pub fn load(dir: &Path, guild_id: GuildId, mode: SnapshotMode) -> Result<Self, Vec<String>> {
+        let json_path = dir.join(format!("{}.json", guild_id));
+        let csv_path = dir.join(format!("{}.csv", guild_id));
+
+        let credits = if json_path.exists() {
+            parse_json(&read(&json_path)?)?
+        } else if csv_path.exists() {
+            parse_csv(&read(&csv_path)?)?
+        } else {
+            return Err(vec![format!("No snapshot found at {} or {}", json_path.display(), csv_path.display())]);
+        };
+
+        Ok(Self { credits, mode })
+    }
+
+    /// A member's starting credits, given what members usually start with.
+    pub fn starting_credits(&self, user_id: UserId, default: usize) -> usize {
+        match self.credits.get(&user_id) {
+            Some(credits) => *credits,
+            None if self.mode == SnapshotMode::Zero => 0,
+            None => default,
+        }
+    }
+
+    /// Number of members listed in the snapshot.
+    pub fn member_count(&self) -> usize {
+        self.credits.len()
+    }
+}
+
+/// Formats a snapshot's problems for an error message, eliding long lists.
+pub fn describe_problems(problems: &[String]) -> String {
+    let mut lines = problems
+        .iter()
+        .take(MAX_PROBLEMS_SHOWN)
+        .map(|problem| format!("• {}", problem))
+        .collect::<Vec<String>>();
+    if problems.len() > MAX_PROBLEMS_SHOWN {
+        lines.push(format!("• …and {} more", problems.len() - MAX_PROBLEMS_SHOWN));
+    }
+    lines.join("\n")
+}
+
+fn read(path: &Path) -> Result<String, Vec<String>> {
+    std::fs::read_to_string(path).map_err(|e| vec![format!("Failed to read {}: {}", path.display(), e)])
+}
+
+/// Parses `{"<user id>": credits, ...}`.
+fn parse_json(contents: &str) -> Result<HashMap<UserId, usize>, Vec<String>> {
+    let entries: HashMap<String, usize> = serde_json::from_str(contents)
+        .map_err(|e| vec![format!("Expected an object of user IDs to credits: {}", e)])?;
+
+    let mut credits = HashMap::new();
+    let mut problems = Vec::new();
+    for (user, amount) in entries {
+        match parse_user(&user) {
+            Some(user_id) => {
+                credits.insert(user_id, amount);
+            },
+            None => problems.push(format!("'{}' is not a user ID", user)),
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(credits)
+    } else {
+        problems.sort();
+        Err(problems)
+    }
+}
+
+/// Parses `user_id,credits` lines. Blank lines, `#` comments and a header line are skipped.
+fn parse_csv(contents: &str) -> Result<HashMap<UserId, usize>, Vec<String>> {
+    let mut credits = HashMap::new();
+    let mut problems = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split(',').map(str::trim);
+        let (user, amount) = (fields.next().unwrap_or(""), fields.next());
+        if i == 0 && parse_user(user).is_none() {
+            // e.g. "user_id,credits"
+            continue;
+        }
+
+        match (parse_user(user), amount.and_then(|amount| amount.parse::<usize>().ok()), fields.next()) {
+            (Some(user_id), Some(amount), None) => {
+                if credits.insert(user_id, amount).is_some() {
+                    problems.push(format!("Line {}: user {} is listed more than once", i + 1, user_id));
+                }
+            },
+            _ => problems.push(format!("Line {}: expected `user_id,credits`, got `{}`", i + 1, line)),
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(credits)
+    } else {
+        Err(problems)
+    }
+}
+
+fn parse_user(user: &str) -> Option<UserId> {
+    // Discord IDs are never 0, and UserId::new panics on it
+    user.parse::<u64>().ok().filter(|&id| id != 0).map(UserId::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for a test's snapshot files.
+    fn snapshot_dir(test: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("qvoting-snapshot-{}-{}", std::process::id(), test));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn csv_snapshots_skip_the_header_comments_and_blank_lines() {
+        let credits = parse_csv("user_id,credits\n# exported today\n\n11, 250\n22,0\n").unwrap();
+        assert_eq!(credits, HashMap::from([(UserId::new(11), 250), (UserId::new(22), 0)]));
+    }
+
+    #[test]
+    fn csv_snapshots_report_every_malformed_row() {
+        assert_eq!(
+            parse_csv("11,250\n22\n33,-5\n44,10,extra\n11,300\n"),
+            Err(vec![
+                "Line 2: expected `user_id,credits`, got `22`".to_string(),
+                "Line 3: expected `user_id,credits`, got `33,-5`".to_string(),
+                "Line 4: expected `user_id,credits`, got `44,10,extra`".to_string(),
+                "Line 5: user 11 is listed more than once".to_string(),
+            ])
+        );
+        // Only the first line can be a header
+        assert!(parse_csv("11,250\nuser,credits\n").is_err());
+    }
+
+    #[test]
+    fn json_snapshots_need_user_ids_and_whole_credits() {
+        assert_eq!(parse_json(r#"{"11": 250, "22": 5}"#).unwrap().len(), 2);
+        assert_eq!(parse_json(r#"{"alice": 250, "0": 5}"#), Err(vec!["'0' is not a user ID".to_string(), "'alice' is not a user ID".to_string()]));
+        assert!(parse_json(r#"{"11": -1}"#).is_err());
+        assert!(parse_json("[11, 250]").is_err());
+    }
+
+    #[test]
+    fn members_missing_from_a_loaded_snapshot_get_what_the_mode_says() {
+        let dir = snapshot_dir("modes");
+        std::fs::write(dir.join("7.csv"), "user_id,credits\n11,250\n").unwrap();
+
+        let snapshot = CreditSnapshot::load(&dir, GuildId::new(7), SnapshotMode::Starting).unwrap();
+        assert_eq!(snapshot.member_count(), 1);
+        assert_eq!(snapshot.starting_credits(UserId::new(11), 100), 250);
+        assert_eq!(snapshot.starting_credits(UserId::new(22), 100), 100);
+
+        let snapshot = CreditSnapshot::load(&dir, GuildId::new(7), SnapshotMode::Zero).unwrap();
+        assert_eq!(snapshot.starting_credits(UserId::new(22), 100), 0);
+
+        // A JSON snapshot is preferred over a CSV one
+        std::fs::write(dir.join("7.json"), r#"{"11": 40}"#).unwrap();
+        let snapshot = CreditSnapshot::load(&dir, GuildId::new(7), SnapshotMode::Starting).unwrap();
+        assert_eq!(snapshot.starting_credits(UserId::new(11), 100), 40);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_or_missing_snapshots_are_refused_whole() {
+        let dir = snapshot_dir("invalid");
+        assert!(CreditSnapshot::load(&dir, GuildId::new(7), SnapshotMode::Starting).is_err_and(|problems| problems[0].starts_with("No snapshot found")));
+
+        std::fs::write(dir.join("7.csv"), "11,250\nbob,3\n").unwrap();
+        assert!(CreditSnapshot::load(&dir, GuildId::new(7), SnapshotMode::Starting).is_err_and(|problems| problems.len() == 1));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn long_problem_lists_are_elided() {
+        let problems = (1..=7).map(|line| format!("Line {}: bad", line)).collect::<Vec<String>>();
+        assert_eq!(describe_problems(&problems[..1]), "• Line 1: bad");
+        assert_eq!(describe_problems(&problems).lines().last(), Some("• …and 2 more"));
+        assert_eq!(describe_problems(&problems).lines().count(), MAX_PROBLEMS_SHOWN + 1);
+    }
+}
@@ -0,0 +1,93 @@
+//! The orders `/list` can show candidates in. ID order is the default, since
+//! members refer to candidates by number.
+
+/// How `/list` orders the candidates it shows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListSort {
+    /// By candidate number
+    #[default]
+    Id,
+
+    /// Alphabetically by name, ignoring case
+    Name,
+
+    /// Most votes first, the way the standings rank them; only once voting has started
+    Votes,
+}
+
+impl ListSort {
+    /// Every order, as accepted by `/list`
+    pub const ALL: [ListSort; 3] = [ListSort::Id, ListSort::Name, ListSort::Votes];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ListSort::Id => "id",
+            ListSort::Name => "name",
+            ListSort::Votes => "votes",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sort| sort.name() == name)
+    }
+}
+
+/// Sorts `(id, name, ..)` rows. `ranking` lists candidate IDs in standings order and
+/// is only used for [`ListSort::Votes`]; candidates missing from it go last, by ID.
+pub fn sort_rows<T>(rows: &mut [(usize, String, T)], sort: ListSort, ranking: &[usize]) {
+    match sort {
+        ListSort::Id => rows.sort_by_key(|(id, _, _)| *id),
+        ListSort::Name => rows.sort_by_cached_key(|(id, name, _)| (name.to_lowercase(), *id)),
+        ListSort::Votes => rows.sort_by_key(|(id, _, _)| {
+            (ranking.iter().position(|ranked| ranked == id).unwrap_or(ranking.len()), *id)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<(usize, String, ())> {
+        [(3, "benches"), (1, "Trees"), (4, "Bike racks"), (2, "bike racks")]
+            .into_iter()
+            .map(|(id, name)| (id, name.to_string(), ()))
+            .collect()
+    }
+
+    fn ids(rows: &[(usize, String, ())]) -> Vec<usize> {
+        rows.iter().map(|(id, _, _)| *id).collect()
+    }
+
+    #[test]
+    fn id_order_is_by_candidate_number() {
+        let mut rows = rows();
+        sort_rows(&mut rows, ListSort::Id, &[4, 2]);
+        assert_eq!(ids(&rows), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn name_order_ignores_case_and_breaks_ties_by_number() {
+        let mut rows = rows();
+        sort_rows(&mut rows, ListSort::Name, &[]);
+        assert_eq!(ids(&rows), vec![3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn vote_order_follows_the_standings_with_unranked_candidates_last() {
+        let mut rows = rows();
+        sort_rows(&mut rows, ListSort::Votes, &[4, 1]);
+        assert_eq!(ids(&rows), vec![4, 1, 2, 3]);
+
+        sort_rows(&mut rows, ListSort::Votes, &[3, 1, 4, 2]);
+        assert_eq!(ids(&rows), vec![3, 1, 4, 2]);
+    }
+
+    #[test]
+    fn every_order_parses_from_its_name() {
+        for sort in ListSort::ALL {
+            assert_eq!(ListSort::parse(sort.name()), Some(sort));
+        }
+        assert_eq!(ListSort::parse("alphabetical"), None);
+    }
+}
@@ -0,0 +1,159 @@
+//! Saves the state that has to survive a restart to a JSON file on disk.
+
+use crate::analytics::Analytics;
+use crate::config::GuildConfig;
+use crate::history::History;
+use crate::leaderboard::Leaderboard;
+use crate::schedule::ScheduledStart;
+use crate::template::ElectionTemplate;
+use serde::{Deserialize, Serialize};
+use serenity::all::GuildId;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+/// Where state is stored when no path is configured
+pub const DEFAULT_STATE_FILE: &str = "data/state.json";
+
+/// Everything the bot persists, keyed by guild
+#[derive(Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    #[serde(default)]
+    pub guilds: HashMap<GuildId, PersistedGuild>,
+}
+
+/// The persisted part of a single guild's state
+#[derive(Default, Serialize, Deserialize)]
+pub struct PersistedGuild {
+    #[serde(default)]
+    pub config: GuildConfig,
+
+    #[serde(default)]
+    pub scheduled_start: Option<ScheduledStart>,
+
+    #[serde(default)]
+    pub leaderboard: Leaderboard,
+
+    #[serde(default)]
+    pub templates: BTreeMap<String, ElectionTemplate>,
+
+    #[serde(default)]
+    pub history: History,
+
+    #[serde(default)]
+    pub analytics: Analytics,
+}
+
+/// A JSON file holding the latest snapshot
+pub struct Store {
+    path: PathBuf,
+
+    // Serializes writers so two saves never interleave on disk
+    write_lock: Mutex<()>,
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATE_FILE)
+    }
+}
+
+impl Store {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Reads the last snapshot. A missing or unreadable file starts from scratch.
+    pub fn load(&self) -> Snapshot {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Snapshot::default(),
+            Err(e) => {
+                eprintln!("Failed to read state file {}: {}", self.path.display(), e);
+                return Snapshot::default();
+            }
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            eprintln!("Ignoring corrupt state file {}: {}", self.path.display(), e);
+            Snapshot::default()
+        })
+    }
+
+    /// Writes the snapshot to a temporary file and swaps it in, so a crash
+    /// mid-write never leaves a truncated state file behind. The snapshot is taken
+    /// once the previous save finished, so an older state can never overwrite a newer one.
+    pub async fn save(&self, snapshot: impl Future<Output = Snapshot>) -> Result<(), String> {
+        let _guard = self.write_lock.lock().await;
+        let contents = serde_json::to_string_pretty(&snapshot.await)
+            .map_err(|e| format!("Failed to serialize state: {}", e))?;
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, contents)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(|e| format!("Failed to replace {}: {}", self.path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A snapshot holding only the given guild, to tell snapshots apart.
+    fn snapshot(guild: u64) -> Snapshot {
+        Snapshot { guilds: HashMap::from([(GuildId::new(guild), PersistedGuild::default())]) }
+    }
+
+    /// A fresh directory for a test's state file.
+    fn store_dir(test: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("qvoting-store-{}-{}", std::process::id(), test));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn a_save_started_later_is_never_overwritten_by_an_earlier_one() {
+        let dir = store_dir("ordering");
+        let store = Store::new(dir.join("state.json"));
+
+        // The first save is still gathering its state when the second one starts
+        let first = store.save(async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            snapshot(1)
+        });
+        let second = store.save(async { snapshot(2) });
+        let (first, second) = tokio::join!(first, second);
+        assert_eq!((first, second), (Ok(()), Ok(())));
+
+        let saved = store.load();
+        assert_eq!(saved.guilds.keys().copied().collect::<Vec<GuildId>>(), vec![GuildId::new(2)]);
+        assert!(!dir.join("state.json.tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_or_corrupt_state_files_start_from_scratch() {
+        let dir = store_dir("corrupt");
+        let store = Store::new(dir.join("state.json"));
+        assert!(store.load().guilds.is_empty());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("state.json"), "{\"guilds\": ").unwrap();
+        assert!(store.load().guilds.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
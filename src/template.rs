@@ -0,0 +1,63 @@
+//! Election prompts and `/start` options saved with `/template save`, so recurring
+//! elections can be relaunched with a single `/template start`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ElectionOptions;
+
+/// Most templates a guild can keep
+pub const MAX_TEMPLATES: usize = 25;
+
+/// Longest template name accepted by `/template save`
+pub const MAX_TEMPLATE_NAME_LEN: usize = 32;
+
+/// A saved election
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ElectionTemplate {
+    pub prompt: String,
+
+    #[serde(default)]
+    pub options: ElectionOptions,
+}
+
+impl ElectionTemplate {
+    /// A short summary of the options that differ from a plain `/start`, e.g. "48h, open voting".
+    pub fn describe(&self) -> String {
+        let options = &self.options;
+        let mut parts = Vec::new();
+        if let Some(hours) = options.duration_hours {
+            parts.push(format!("{}h", hours));
+        }
+        if let Some(credits) = options.credits {
+            parts.push(format!("{} credits", credits));
+        }
+        if let Some(seats) = options.seats {
+            parts.push(format!("{} seats", seats));
+        }
+        if options.reaction_voting {
+            parts.push("reaction voting".to_string());
+        }
+        if options.open_voting {
+            parts.push("open voting".to_string());
+        }
+        if options.anonymous_proposals {
+            parts.push("anonymous".to_string());
+        }
+
+        if parts.is_empty() {
+            "default settings".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Normalizes a template name so lookups ignore case and surrounding spaces. Names
+/// are limited to letters, digits, `-` and `_` so they're easy to type back.
+pub fn normalize_name(name: &str) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_TEMPLATE_NAME_LEN
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(name)
+}
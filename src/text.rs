@@ -0,0 +1,182 @@
+//! Helpers for fitting variable-length content into Discord messages.
+
+/// Discord rejects messages longer than this many characters
+pub const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// Splits content into messages that each fit within `limit` characters,
+/// breaking between lines where possible.
+pub fn split_message(content: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for line in content.split('\n') {
+        // Lines that can never fit on their own are broken up mid-line
+        let mut pieces = Vec::new();
+        let mut rest = line;
+        while rest.chars().count() > limit {
+            let split_at = rest.char_indices().nth(limit).map(|(i, _)| i).unwrap_or(rest.len());
+            pieces.push(&rest[..split_at]);
+            rest = &rest[split_at..];
+        }
+        pieces.push(rest);
+
+        for piece in pieces {
+            let needed = if current.is_empty() { 0 } else { 1 } + piece.chars().count();
+            if !current.is_empty() && current.chars().count() + needed > limit {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(piece);
+        }
+    }
+
+    if !current.is_empty() || chunks.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+/// Joins as many lines as fit after `prefix` within `limit` characters, ending
+/// with "…and N more" when some had to be left out.
+pub fn truncate_lines(prefix: &str, lines: &[String], limit: usize) -> String {
+    let mut content = prefix.to_string();
+    let mut used = content.chars().count();
+
+    for (i, line) in lines.iter().enumerate() {
+        let separator = if i == 0 { 0 } else { 1 };
+        let remaining = lines.len() - i - 1;
+
+        // Keep room for the "…and N more" line unless this is the last line
+        let reserve = if remaining > 0 { format!("\n…and {} more", remaining).chars().count() } else { 0 };
+        if used + separator + line.chars().count() + reserve > limit {
+            content.push_str(&format!("{}…and {} more", if i == 0 { "" } else { "\n" }, lines.len() - i));
+            return content;
+        }
+
+        if separator == 1 {
+            content.push('\n');
+        }
+        content.push_str(line);
+        used += separator + line.chars().count();
+    }
+
+    content
+}
+
+/// The first line of `text`, cut to at most `max` characters with a trailing "…"
+/// when anything was left out.
+pub fn excerpt(text: &str, max: usize) -> String {
+    let first_line = text.trim().lines().next().unwrap_or("");
+    let cut = first_line.chars().count() > max || text.trim().lines().nth(1).is_some();
+    if !cut {
+        return first_line.to_string();
+    }
+    let kept = first_line.chars().take(max.saturating_sub(1)).collect::<String>();
+    format!("{}…", kept.trim_end())
+}
+
+/// `text` with its first letter in upper case, e.g. "points" → "Points".
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Names of the `{name}` placeholders in a template, in order of appearance.
+pub fn placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start + 1..].find('}') else {
+            break;
+        };
+        names.push(&rest[start + 1..start + 1 + len]);
+        rest = &rest[start + 1 + len + 1..];
+    }
+    names
+}
+
+/// Replaces each `{name}` placeholder in a template with its text, in a single pass
+/// so text filled in is never taken for a placeholder. Unknown names are kept as is.
+pub fn fill_placeholders(template: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start + 1..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + len];
+        filled.push_str(&rest[..start]);
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => filled.push_str(value),
+            None => filled.push_str(&rest[start..start + 1 + len + 1]),
+        }
+        rest = &rest[start + 1 + len + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(n: usize) -> Vec<String> {
+        (1..=n).map(|i| format!("#{}: Candidate number {} with a reasonably long name: {} votes", i, i, i * 3)).collect()
+    }
+
+    #[test]
+    fn split_message_keeps_200_candidates_whole_and_within_the_limit() {
+        let lines = candidates(200);
+        let chunks = split_message(&lines.join("\n"), DISCORD_MESSAGE_LIMIT);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= DISCORD_MESSAGE_LIMIT));
+        // Every line arrives once, in order, and none is cut across two messages
+        let received = chunks.iter().flat_map(|chunk| chunk.split('\n')).collect::<Vec<&str>>();
+        assert_eq!(received, lines);
+    }
+
+    #[test]
+    fn split_message_breaks_up_a_line_too_long_for_one_message() {
+        let line = "x".repeat(DISCORD_MESSAGE_LIMIT + 10);
+        let chunks = split_message(&line, DISCORD_MESSAGE_LIMIT);
+
+        assert_eq!(chunks.iter().map(|chunk| chunk.chars().count()).collect::<Vec<usize>>(), vec![DISCORD_MESSAGE_LIMIT, 10]);
+        assert_eq!(chunks.concat(), line);
+    }
+
+    #[test]
+    fn truncate_lines_fits_200_candidates_with_a_count_of_the_rest() {
+        let lines = candidates(200);
+        let content = truncate_lines("**Candidates:**\n", &lines, DISCORD_MESSAGE_LIMIT);
+
+        assert!(content.chars().count() <= DISCORD_MESSAGE_LIMIT);
+        let shown = content.lines().skip(1).take_while(|line| !line.starts_with('…')).collect::<Vec<&str>>();
+        assert_eq!(shown, lines[..shown.len()]);
+        assert!(content.ends_with(&format!("…and {} more", lines.len() - shown.len())));
+    }
+
+    #[test]
+    fn templates_are_filled_in_once() {
+        let template = "🏆 Results for {prompt}!\n{winners}\n{participants} members took part. {unknown}";
+        let values = [("winners", "1. Bike racks {prompt}"), ("prompt", "Spring budget"), ("participants", "12")];
+        assert_eq!(
+            fill_placeholders(template, &values),
+            "🏆 Results for Spring budget!\n1. Bike racks {prompt}\n12 members took part. {unknown}"
+        );
+        assert_eq!(fill_placeholders("{winners}{winners} {", &values[..1]), "1. Bike racks {prompt}1. Bike racks {prompt} {");
+        assert_eq!(placeholders(template), vec!["prompt", "winners", "participants", "unknown"]);
+    }
+
+    #[test]
+    fn truncate_lines_keeps_everything_that_fits() {
+        let lines = candidates(3);
+        assert_eq!(truncate_lines("", &lines, DISCORD_MESSAGE_LIMIT), lines.join("\n"));
+    }
+}
@@ -0,0 +1,108 @@
+//! The emoji the bot decorates its messages with. Announcement emoji can be changed
+//! per guild with `/config emoji`; the status marks on replies are shared by every guild.
+
+use serde::{Deserialize, Serialize};
+
+/// Marks a command that succeeded
+pub const SUCCESS: &str = "✅";
+
+/// Marks a command that was refused
+pub const ERROR: &str = "❌";
+
+/// Marks a command that only partly succeeded, or something that needs attention
+pub const WARNING: &str = "⚠️";
+
+/// Marks a command that timed out or was rate limited
+pub const WAITING: &str = "⏱️";
+
+/// Marks votes refused during the freeze window
+pub const FROZEN: &str = "🧊";
+
+/// Longest emoji accepted by `/config emoji`, enough for an animated custom emoji
+pub const MAX_EMOJI_LEN: usize = 64;
+
+/// The announcement emoji a guild can replace
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Slot {
+    /// Elections, proposals and candidate lists
+    Ballot,
+
+    /// The results once an election ends
+    Winners,
+
+    /// The candidates of a tie-break runoff
+    Runoff,
+
+    /// The notice that an admin reset the election
+    Reset,
+}
+
+impl Slot {
+    /// Every slot, as accepted by `/config emoji`
+    pub const ALL: [Slot; 4] = [Slot::Ballot, Slot::Winners, Slot::Runoff, Slot::Reset];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Slot::Ballot => "ballot",
+            Slot::Winners => "winners",
+            Slot::Runoff => "runoff",
+            Slot::Reset => "reset",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|slot| slot.name() == name)
+    }
+}
+
+/// A guild's announcement emoji. Emoji missing from an older state file fall back
+/// to the defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub ballot: String,
+    pub winners: String,
+    pub runoff: String,
+    pub reset: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            ballot: "🗳️".to_string(),
+            winners: "🏆".to_string(),
+            runoff: "⚖️".to_string(),
+            reset: "🧹".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn get(&self, slot: Slot) -> &str {
+        match slot {
+            Slot::Ballot => &self.ballot,
+            Slot::Winners => &self.winners,
+            Slot::Runoff => &self.runoff,
+            Slot::Reset => &self.reset,
+        }
+    }
+
+    /// Replaces the emoji in a slot, or restores its default when given `None`.
+    pub fn set(&mut self, slot: Slot, emoji: Option<String>) {
+        let emoji = emoji.unwrap_or_else(|| Theme::default().get(slot).to_string());
+        match slot {
+            Slot::Ballot => self.ballot = emoji,
+            Slot::Winners => self.winners = emoji,
+            Slot::Runoff => self.runoff = emoji,
+            Slot::Reset => self.reset = emoji,
+        }
+    }
+}
+
+/// Checks an emoji given to `/config emoji`: a single token such as `🎉` or a custom
+/// emoji like `<:party:123>`, with no mentions that would ping members.
+pub fn valid_emoji(emoji: &str) -> bool {
+    !emoji.is_empty()
+        && emoji.chars().count() <= MAX_EMOJI_LEN
+        && !emoji.chars().any(|c| c.is_whitespace() || c == '@')
+}
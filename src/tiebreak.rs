@@ -0,0 +1,187 @@
+//! Picking an election's winners when candidates tie at the winner cutoff.
+
+use serde::{Deserialize, Serialize};
+
+/// A candidate's final tally: its ID, name and number of votes
+pub type Ranked = (usize, String, usize);
+
+/// What happens when the last winning place is shared by several candidates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TieBreak {
+    /// Every tied candidate wins, so there can be more winners than places
+    #[default]
+    CoWinners,
+
+    /// The tied candidates go to a second vote for the remaining places
+    Runoff,
+
+    /// The remaining places are drawn among the tied candidates. The draw is
+    /// seeded by the election, so recounting the same election gives the same result.
+    Random,
+
+    /// The remaining places go to the tied candidates proposed first
+    FirstProposed,
+}
+
+impl TieBreak {
+    /// Every mode, as accepted by `/config tie-break`
+    pub const ALL: [TieBreak; 4] = [TieBreak::CoWinners, TieBreak::Runoff, TieBreak::Random, TieBreak::FirstProposed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TieBreak::CoWinners => "co-winners",
+            TieBreak::Runoff => "runoff",
+            TieBreak::Random => "random",
+            TieBreak::FirstProposed => "first-proposed",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
+/// The winners of an election, or the candidates that need a runoff to decide them
+pub enum Outcome {
+    /// The final winners, best first
+    Decided(Vec<Ranked>),
+
+    /// `decided` won outright; the places left are contested between `tied`
+    Runoff { decided: Vec<Ranked>, tied: Vec<Ranked> },
+}
+
+/// Sorts candidates by votes, breaking equal tallies by proposal order.
+pub fn rank(mut candidates: Vec<Ranked>) -> Vec<Ranked> {
+    candidates.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+    candidates
+}
+
+/// Picks up to `places` winners from candidates sorted by `rank`, settling a tie
+/// for the last place according to `mode`. `seed` identifies the election for
+/// random draws. Candidates tied without any votes aren't elected, so their places
+/// stay empty.
+pub fn select_winners(standings: &[Ranked], places: usize, mode: TieBreak, seed: u64) -> Outcome {
+    // No tie unless the first loser has as many votes as the last winner
+    let tie_votes = match (places.checked_sub(1).and_then(|i| standings.get(i)), standings.get(places)) {
+        (Some(last), Some(next)) if last.2 == next.2 => last.2,
+        _ => return Outcome::Decided(standings.iter().take(places).cloned().collect()),
+    };
+    if tie_votes == 0 {
+        return Outcome::Decided(standings.iter().filter(|c| c.2 > 0).take(places).cloned().collect());
+    }
+
+    let decided: Vec<Ranked> = standings.iter().filter(|c| c.2 > tie_votes).cloned().collect();
+    let mut tied: Vec<Ranked> = standings.iter().filter(|c| c.2 == tie_votes).cloned().collect();
+    let open = places - decided.len();
+
+    let chosen = match mode {
+        TieBreak::CoWinners => tied,
+        TieBreak::Runoff => return Outcome::Runoff { decided, tied },
+        TieBreak::FirstProposed => tied.into_iter().take(open).collect(),
+        TieBreak::Random => {
+            shuffle(&mut tied, seed);
+            let mut chosen: Vec<Ranked> = tied.into_iter().take(open).collect();
+            chosen.sort_by_key(|c| c.0);
+            chosen
+        },
+    };
+
+    Outcome::Decided(decided.into_iter().chain(chosen).collect())
+}
+
+/// Fisher-Yates shuffle driven by splitmix64, which is plenty for drawing lots
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    for i in (1..items.len()).rev() {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        items.swap(i, (z % (i as u64 + 1)) as usize);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two winning places, with #2, #3 and #4 tied for the second one
+    fn tie() -> Vec<Ranked> {
+        rank(vec![
+            (1, "Park".to_string(), 9),
+            (2, "Library".to_string(), 5),
+            (3, "Pool".to_string(), 5),
+            (4, "Market".to_string(), 5),
+            (5, "Garden".to_string(), 2),
+        ])
+    }
+
+    fn ids(candidates: &[Ranked]) -> Vec<usize> {
+        candidates.iter().map(|c| c.0).collect()
+    }
+
+    fn decided(outcome: Outcome) -> Vec<usize> {
+        match outcome {
+            Outcome::Decided(winners) => ids(&winners),
+            Outcome::Runoff { .. } => panic!("expected the winners to be decided"),
+        }
+    }
+
+    #[test]
+    fn co_winners_share_the_tied_place() {
+        assert_eq!(decided(select_winners(&tie(), 2, TieBreak::CoWinners, 0)), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn runoff_sends_the_tied_candidates_to_another_vote() {
+        let Outcome::Runoff { decided, tied } = select_winners(&tie(), 2, TieBreak::Runoff, 0) else {
+            panic!("expected a runoff");
+        };
+        assert_eq!(ids(&decided), vec![1]);
+        assert_eq!(ids(&tied), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn random_draws_the_same_winner_for_the_same_seed() {
+        let first = decided(select_winners(&tie(), 2, TieBreak::Random, 42));
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0], 1);
+        assert!([2, 3, 4].contains(&first[1]));
+        for _ in 0..10 {
+            assert_eq!(decided(select_winners(&tie(), 2, TieBreak::Random, 42)), first);
+        }
+    }
+
+    #[test]
+    fn first_proposed_picks_the_earliest_tied_candidate() {
+        assert_eq!(decided(select_winners(&tie(), 2, TieBreak::FirstProposed, 0)), vec![1, 2]);
+        assert_eq!(decided(select_winners(&tie(), 3, TieBreak::FirstProposed, 0)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn candidates_tied_without_votes_are_never_elected() {
+        let standings = rank(vec![
+            (1, "Park".to_string(), 4),
+            (2, "Library".to_string(), 0),
+            (3, "Pool".to_string(), 0),
+        ]);
+        for mode in TieBreak::ALL {
+            assert_eq!(decided(select_winners(&standings, 2, mode, 0)), vec![1]);
+        }
+        // Nobody voted at all
+        let standings = rank(vec![(1, "Park".to_string(), 0), (2, "Library".to_string(), 0)]);
+        for mode in TieBreak::ALL {
+            assert!(decided(select_winners(&standings, 1, mode, 0)).is_empty());
+        }
+    }
+
+    #[test]
+    fn no_tie_at_the_cutoff_needs_no_tie_break() {
+        for mode in TieBreak::ALL {
+            assert_eq!(decided(select_winners(&tie(), 1, mode, 0)), vec![1]);
+            assert_eq!(decided(select_winners(&tie(), 4, mode, 0)), vec![1, 2, 3, 4]);
+        }
+    }
+}
@@ -0,0 +1,1108 @@
+//! The quadratic voting rules: what votes cost, when they can be cast, and how the
+//! phase of an election moves along. Nothing here touches Discord, so the handlers
+//! only lock a guild's state, apply one of these operations and report the result.
+
+use crate::error::CommandError;
+use crate::snapshot::CreditSnapshot;
+use crate::tiebreak::{self, Ranked};
+use serde::{Deserialize, Serialize};
+use serenity::all::UserId;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Total votes for an idea, and votes cast for it per user
+pub type CandidateVotes = (String, AtomicUsize, HashMap<UserId, AtomicUsize>);
+
+/// Where a guild's election is in its lifecycle
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Phase {
+    // No election is running
+    #[default]
+    Idle,
+
+    // Members are proposing candidates
+    Suggestion,
+
+    // Proposals are closed for review, but voting hasn't started yet
+    ProposalsLocked,
+
+    // Candidates are locked in and votes are being cast
+    Voting,
+}
+
+impl Phase {
+    /// Checks that a /prop can be accepted now. Proposals belong to an election, so
+    /// they're refused until /start; once voting opens, only open voting takes more.
+    pub fn check_proposal(self, open_voting: bool) -> Result<(), CommandError> {
+        match self {
+            Phase::Idle => Err(CommandError::NoElection),
+            Phase::ProposalsLocked => Err(CommandError::ProposalsLocked),
+            Phase::Voting if !open_voting => Err(CommandError::VoteInProgress { action: "Candidates cannot be proposed" }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a proposal can be reworded with /editprop in this phase. Votes may
+    /// already reference the old text, so edits stop once voting opens; once proposals
+    /// are locked for review, only admins may still reword them.
+    pub fn check_edit(self, is_admin: bool) -> Result<(), CommandError> {
+        match self {
+            Phase::Voting => Err(CommandError::VoteInProgress { action: "Proposals cannot be edited" }),
+            Phase::ProposalsLocked if !is_admin => Err(CommandError::ProposalsLocked),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that votes can be cast in this phase, telling an idle guild apart from
+    /// one still collecting proposals.
+    pub fn check_vote(self) -> Result<(), CommandError> {
+        match self {
+            Phase::Idle => Err(CommandError::NoElection),
+            Phase::Suggestion | Phase::ProposalsLocked => Err(CommandError::NoVoteInProgress),
+            Phase::Voting => Ok(()),
+        }
+    }
+}
+
+/// The end of a voting phase during which votes can no longer be changed
+#[derive(Clone, Copy)]
+pub struct FreezeWindow {
+    pub minutes: u64,
+    pub allows_new_votes: bool,
+}
+
+impl FreezeWindow {
+    /// Whether a voting phase ending at `deadline` is within the window at `now`, both
+    /// unix timestamps. The window includes its first second.
+    pub fn covers(self, deadline: i64, now: i64) -> bool {
+        deadline - now <= self.minutes as i64 * 60
+    }
+
+    /// Refuses a vote during the freeze if it isn't allowed. Changing or retracting
+    /// an existing vote is never allowed; new votes are when the guild says so.
+    pub fn check(self, changes_existing_vote: bool) -> Result<(), CommandError> {
+        if changes_existing_vote || !self.allows_new_votes {
+            Err(CommandError::VotesFrozen { minutes: self.minutes, changes_existing_vote })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// What `/vote` does when a member asks for more votes than they can afford
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverspendPolicy {
+    /// Refuse the vote
+    #[default]
+    Reject,
+
+    /// Cast as many of the votes as the member can afford
+    Clamp,
+}
+
+impl OverspendPolicy {
+    /// Every policy, as accepted by `/config overspend-policy`
+    pub const ALL: [OverspendPolicy; 2] = [OverspendPolicy::Reject, OverspendPolicy::Clamp];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OverspendPolicy::Reject => "reject",
+            OverspendPolicy::Clamp => "clamp",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|policy| policy.name() == name)
+    }
+}
+
+/// What happens to the votes of members who leave the server mid-election
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DepartedPolicy {
+    /// Keep their votes and credits, restored if they rejoin
+    #[default]
+    Keep,
+
+    /// Remove their votes from the tally
+    Purge,
+}
+
+impl DepartedPolicy {
+    /// Every policy, as accepted by `/config departed-members`
+    pub const ALL: [DepartedPolicy; 2] = [DepartedPolicy::Keep, DepartedPolicy::Purge];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DepartedPolicy::Keep => "keep",
+            DepartedPolicy::Purge => "purge",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|policy| policy.name() == name)
+    }
+}
+
+/// What happens to members' unused credits when the reset interval refills them.
+/// After a reset a member has their starting credits plus `carried(unused)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CarryOver {
+    /// Unused credits are lost, so everyone is back to their starting credits
+    #[default]
+    Reset,
+
+    /// Unused credits are all kept on top of the starting credits
+    Carry,
+
+    /// This percentage of the unused credits is kept, rounded down
+    Decay { percent: u8 },
+}
+
+impl CarryOver {
+    /// Names of the policies, as accepted by `/config credit-carryover`
+    pub const NAMES: [&'static str; 3] = ["reset", "carry", "decay"];
+
+    /// Reads a policy by name; `decay` needs the percentage of credits it keeps.
+    pub fn parse(name: &str, percent: Option<u8>) -> Option<Self> {
+        match name {
+            "reset" => Some(CarryOver::Reset),
+            "carry" => Some(CarryOver::Carry),
+            "decay" => percent.map(|percent| CarryOver::Decay { percent }),
+            _ => None,
+        }
+    }
+
+    /// How many of a member's unused credits they keep through a reset.
+    pub fn carried(self, unused: usize) -> usize {
+        match self {
+            CarryOver::Reset => 0,
+            CarryOver::Carry => unused,
+            CarryOver::Decay { percent } => unused * percent as usize / 100,
+        }
+    }
+}
+
+/// Credits it takes to cast `votes` votes for a single candidate
+pub fn cost(votes: usize) -> usize {
+    votes.pow(2)
+}
+
+/// Most votes, up to `votes`, that `credits` can pay for.
+pub fn affordable_votes(votes: usize, credits: usize) -> usize {
+    (1..=votes).rev().find(|&v| cost(v) <= credits).unwrap_or(0)
+}
+
+/// Sorts the candidates by their number of votes, then by proposal order.
+pub fn rank_candidates(votes: &HashMap<usize, CandidateVotes>) -> Vec<Ranked> {
+    tiebreak::rank(
+        votes
+            .iter()
+            .map(|(id, (c, votes, _))| (*id, c.clone(), votes.load(Ordering::Relaxed)))
+            .collect(),
+    )
+}
+
+/// Counts the members who voted for each candidate.
+pub fn voter_counts(votes: &HashMap<usize, CandidateVotes>) -> HashMap<usize, usize> {
+    votes.iter().map(|(id, (_, _, voters))| (*id, voters.len())).collect()
+}
+
+/// A guild's tally, credit balances and phase, borrowed for a single change along
+/// with the settings that govern it.
+pub struct VotingState<'a> {
+    pub phase: &'a mut Phase,
+    pub candidates: &'a mut HashMap<usize, CandidateVotes>,
+    pub points: &'a mut HashMap<UserId, AtomicUsize>,
+
+    // Credits each member received from others with /delegate this election
+    pub delegated: &'a mut HashMap<UserId, usize>,
+
+    // Unused credits each member kept through the last carry-over refill
+    pub carried: &'a mut HashMap<UserId, usize>,
+
+    // Balance of members who haven't voted yet in this election
+    pub starting_credits: usize,
+
+    // Starting balances of individual members, when the election uses a snapshot
+    pub snapshot: Option<&'a CreditSnapshot>,
+
+    // Most votes a member can give a single candidate
+    pub max_votes: usize,
+
+    // Set while the voting phase is in its freeze window
+    pub freeze: Option<FreezeWindow>,
+
+    // Most credits a member can receive from others in one election, if limited
+    pub delegation_cap: Option<usize>,
+
+    // Largest share of the starting budget, in percent, that votes for a single
+    // candidate may cost, if limited
+    pub max_concentration: Option<usize>,
+
+    // Most different candidates a member can vote for, if limited
+    pub max_candidates: Option<usize>,
+
+    // Whether /vote refuses votes a member can't afford or casts fewer of them
+    pub overspend: OverspendPolicy,
+}
+
+impl VotingState<'_> {
+    /// Credits a member starts the election with.
+    pub fn starting_balance(&self, user_id: UserId) -> usize {
+        self.snapshot.map_or(self.starting_credits, |snapshot| snapshot.starting_credits(user_id, self.starting_credits))
+    }
+
+    /// Credits a member has left to spend.
+    pub fn balance(&self, user_id: UserId) -> usize {
+        self.points
+            .get(&user_id)
+            .map_or_else(|| self.starting_balance(user_id), |points| points.load(Ordering::Relaxed))
+    }
+
+    /// Most credits a member can hold: the election's starting amount plus whatever
+    /// others delegated to them and they carried over from the last refill. Refunds are
+    /// clamped to it, so no sequence of votes and retractions can leave a member with
+    /// more credits than they were given.
+    pub fn credit_cap(&self, user_id: UserId) -> usize {
+        self.starting_balance(user_id)
+            + self.delegated.get(&user_id).copied().unwrap_or(0)
+            + self.carried.get(&user_id).copied().unwrap_or(0)
+    }
+
+    /// Refuses votes for a candidate that would cost more than the guild's share of
+    /// the member's starting budget.
+    fn check_concentration(&self, user_id: UserId, candidate_id: usize, votes: usize) -> Result<(), CommandError> {
+        let Some(percent) = self.max_concentration else {
+            return Ok(());
+        };
+        let cap = self.starting_balance(user_id) * percent / 100;
+        if cost(votes) > cap {
+            return Err(CommandError::OverConcentration { candidate_id, cost: cost(votes), cap, percent });
+        }
+        Ok(())
+    }
+
+    /// Refuses votes that would spread a member's votes over more candidates than the
+    /// guild allows. Votes for candidates they already back can always be changed.
+    fn check_spread(&self, user_id: UserId, candidate_ids: &[usize]) -> Result<(), CommandError> {
+        let Some(max) = self.max_candidates else {
+            return Ok(());
+        };
+        let backed = self
+            .candidates
+            .iter()
+            .filter(|(_, candidate)| candidate.2.contains_key(&user_id))
+            .map(|(id, _)| *id)
+            .collect::<HashSet<usize>>();
+        let added = candidate_ids.iter().filter(|id| !backed.contains(id)).collect::<HashSet<&usize>>().len();
+        if added > 0 && backed.len() + added > max {
+            return Err(CommandError::TooManyCandidates { max });
+        }
+        Ok(())
+    }
+
+    /// The candidates ranked by their current tally.
+    pub fn standings(&self) -> Vec<Ranked> {
+        rank_candidates(self.candidates)
+    }
+
+    /// Credits each member has spent on the current tally.
+    pub fn spending(&self) -> HashMap<UserId, usize> {
+        let mut spent = HashMap::new();
+        for (_, _, voters) in self.candidates.values() {
+            for (user_id, votes) in voters {
+                *spent.entry(*user_id).or_default() += cost(votes.load(Ordering::Relaxed));
+            }
+        }
+        spent
+    }
+
+    /// Sets a member's votes for a candidate, refunding whatever their previous votes
+    /// for it cost. Under the clamp policy, votes the member can't afford are cut down
+    /// to as many as they can. Returns the votes cast and the credits left.
+    pub fn cast(&mut self, user_id: UserId, candidate_id: usize, votes: usize) -> Result<(usize, usize), CommandError> {
+        // Checked first, as with nothing on the ballot every candidate ID looks wrong
+        self.phase.check_vote()?;
+        if candidate_id == 0 {
+            return Err(CommandError::CandidateIdZero);
+        }
+        if votes == 0 || votes > self.max_votes {
+            return Err(CommandError::OutOfRange { what: "Number of votes", min: 1, max: self.max_votes as i64, unit: "" });
+        }
+
+        let Some(candidate) = self.candidates.get(&candidate_id) else {
+            return Err(CommandError::NoSuchCandidate(candidate_id));
+        };
+        let previous = candidate.2.get(&user_id).map(|v| v.load(Ordering::Relaxed));
+        if let Some(window) = self.freeze {
+            window.check(previous.is_some())?;
+        }
+
+        // Votes already cast for this candidate are refunded before charging the new ones
+        let available = (self.balance(user_id) + previous.map_or(0, cost)).min(self.credit_cap(user_id));
+        let cast = match self.overspend {
+            OverspendPolicy::Reject => votes,
+            OverspendPolicy::Clamp => affordable_votes(votes, available),
+        };
+        if cast == 0 || available < cost(cast) {
+            return Err(CommandError::InsufficientCredits { cost: cost(votes), available });
+        }
+        self.check_concentration(user_id, candidate_id, cast)?;
+        self.check_spread(user_id, &[candidate_id])?;
+
+        let candidate = self.candidates.get_mut(&candidate_id).unwrap();
+        candidate.1.fetch_sub(previous.unwrap_or(0), Ordering::Relaxed);
+        candidate.1.fetch_add(cast, Ordering::Relaxed);
+        candidate.2.insert(user_id, AtomicUsize::new(cast));
+        let remaining = available - cost(cast);
+        self.points.insert(user_id, AtomicUsize::new(remaining));
+
+        Ok((cast, remaining))
+    }
+
+    /// Casts a /votemulti batch of (candidate ID, votes) pairs all-or-nothing: if any
+    /// pair is refused, no votes are cast. Returns the credits left afterwards.
+    pub fn cast_batch(&mut self, user_id: UserId, pairs: &[(usize, usize)]) -> Result<usize, CommandError> {
+        self.phase.check_vote()?;
+
+        if let Some(&(candidate_id, votes)) = pairs.iter().find(|(_, votes)| *votes > self.max_votes) {
+            return Err(CommandError::BatchRejected(Box::new(CommandError::OverVoteLimit { candidate_id, votes, max: self.max_votes })));
+        }
+        for &(candidate_id, votes) in pairs {
+            self.check_concentration(user_id, candidate_id, votes).map_err(|e| CommandError::BatchRejected(Box::new(e)))?;
+        }
+
+        if let Some((id, _)) = pairs.iter().find(|(id, _)| !self.candidates.contains_key(id)) {
+            return Err(CommandError::BatchRejected(Box::new(CommandError::NoSuchCandidate(*id))));
+        }
+        let candidate_ids = pairs.iter().map(|(id, _)| *id).collect::<Vec<usize>>();
+        self.check_spread(user_id, &candidate_ids).map_err(|e| CommandError::BatchRejected(Box::new(e)))?;
+
+        // Previous votes on the same candidates are refunded before charging the batch
+        let previous = |id: &usize| self.candidates.get(id).and_then(|c| c.2.get(&user_id)).map(|v| v.load(Ordering::Relaxed));
+        if let Some(window) = self.freeze {
+            window.check(pairs.iter().any(|(id, _)| previous(id).is_some()))?;
+        }
+        let refund: usize = pairs.iter().filter_map(|(id, _)| previous(id)).map(cost).sum();
+        let req_points: usize = pairs.iter().map(|(_, votes)| cost(*votes)).sum();
+        let can_spend = (self.balance(user_id) + refund).min(self.credit_cap(user_id));
+
+        if can_spend < req_points {
+            return Err(CommandError::BatchRejected(Box::new(CommandError::InsufficientCredits { cost: req_points, available: can_spend })));
+        }
+
+        for (id, votes) in pairs.iter() {
+            let candidate_entry = self.candidates.get_mut(id).unwrap();
+            let prev_votes = candidate_entry.2
+                .insert(user_id, AtomicUsize::new(*votes))
+                .map(|v| v.into_inner())
+                .unwrap_or(0);
+            candidate_entry.1.fetch_sub(prev_votes, Ordering::Relaxed);
+            candidate_entry.1.fetch_add(*votes, Ordering::Relaxed);
+        }
+
+        let remaining = can_spend - req_points;
+        self.points.insert(user_id, AtomicUsize::new(remaining));
+        Ok(remaining)
+    }
+
+    /// Works out the votes `/vote-all` casts: the same number of extra votes on every
+    /// candidate, as many rounds as the member's credits cover. Candidates stop gaining
+    /// votes at the guild's per-candidate limits, and credits too few for another round
+    /// are left unspent. Returns the new vote count for each candidate that gains votes,
+    /// ready for [`VotingState::cast_batch`], whose budget it never exceeds.
+    pub fn even_split(&self, user_id: UserId) -> Result<Vec<(usize, usize)>, CommandError> {
+        self.phase.check_vote()?;
+
+        let current = |id: &usize| self.candidates.get(id).and_then(|c| c.2.get(&user_id)).map_or(0, |v| v.load(Ordering::Relaxed));
+        let mut ids = self.candidates.keys().copied().collect::<Vec<usize>>();
+        ids.sort_unstable();
+        if let Some(max) = self.max_candidates {
+            // Candidates the member already backs are kept first
+            ids.sort_by_key(|id| current(id) == 0);
+            ids.truncate(max);
+        }
+
+        let limit = self
+            .max_concentration
+            .map_or(self.max_votes, |percent| affordable_votes(self.max_votes, self.starting_balance(user_id) * percent / 100));
+        let targets = |extra: usize| ids.iter().map(|id| (*id, (current(id) + extra).min(limit).max(current(id)))).collect::<Vec<(usize, usize)>>();
+        let spend = |pairs: &[(usize, usize)]| pairs.iter().map(|(_, votes)| cost(*votes)).sum::<usize>();
+
+        // The same budget cast_batch allows: the balance plus the votes being replaced
+        let budget = (self.balance(user_id) + spend(&targets(0))).min(self.credit_cap(user_id));
+        let mut best = targets(0);
+        for extra in 1..=self.max_votes {
+            let next = targets(extra);
+            if next == best || spend(&next) > budget {
+                break;
+            }
+            best = next;
+        }
+
+        let pairs = best.into_iter().filter(|(id, votes)| *votes > current(id)).collect::<Vec<(usize, usize)>>();
+        if pairs.is_empty() {
+            return Err(CommandError::NothingToSpread);
+        }
+        Ok(pairs)
+    }
+
+    /// Casts a single reaction vote for a candidate, costing 1 credit. Each member can
+    /// approve a candidate only once, so approving it again changes nothing.
+    pub fn approve(&mut self, user_id: UserId, candidate_id: usize) -> Result<(), CommandError> {
+        if *self.phase != Phase::Voting {
+            return Err(CommandError::VotingEnded);
+        }
+
+        let starting_credits = self.starting_balance(user_id);
+        let Some(candidate_entry) = self.candidates.get(&candidate_id) else {
+            return Err(CommandError::NoSuchCandidate(candidate_id));
+        };
+        // Already counted, e.g. a duplicate gateway event
+        if candidate_entry.2.contains_key(&user_id) {
+            return Ok(());
+        }
+        if let Some(window) = self.freeze {
+            window.check(false)?;
+        }
+        self.check_spread(user_id, &[candidate_id])?;
+
+        let balance = self.points.entry(user_id).or_insert_with(|| AtomicUsize::new(starting_credits));
+        if balance.load(Ordering::Relaxed) == 0 {
+            return Err(CommandError::InsufficientCredits { cost: 1, available: 0 });
+        }
+        balance.fetch_sub(1, Ordering::Relaxed);
+
+        let candidate_entry = self.candidates.get_mut(&candidate_id).unwrap();
+        candidate_entry.1.fetch_add(1, Ordering::Relaxed);
+        candidate_entry.2.insert(user_id, AtomicUsize::new(1));
+        Ok(())
+    }
+
+    /// Removes a member's votes for a candidate and refunds the credits they cost.
+    /// Returns the number of votes that were retracted.
+    pub fn retract(&mut self, user_id: UserId, candidate_id: usize) -> Result<usize, CommandError> {
+        self.phase.check_vote()?;
+        let Some(candidate_entry) = self.candidates.get_mut(&candidate_id) else {
+            return Err(CommandError::NoSuchCandidate(candidate_id));
+        };
+        if !candidate_entry.2.contains_key(&user_id) {
+            return Err(CommandError::NoVotesFor(candidate_id));
+        }
+        if let Some(window) = self.freeze {
+            window.check(true)?;
+        }
+
+        let prev_votes = candidate_entry.2.remove(&user_id).unwrap().into_inner();
+        candidate_entry.1.fetch_sub(prev_votes, Ordering::Relaxed);
+        let cap = self.credit_cap(user_id);
+        if let Some(points) = self.points.get(&user_id) {
+            let refunded = (points.load(Ordering::Relaxed) + cost(prev_votes)).min(cap);
+            points.store(refunded, Ordering::Relaxed);
+        }
+
+        Ok(prev_votes)
+    }
+
+    /// Moves credits from one member's balance to another's. Returns the credits the
+    /// giver has left.
+    pub fn delegate(&mut self, from: UserId, to: UserId, credits: usize) -> Result<usize, CommandError> {
+        if from == to {
+            return Err(CommandError::SelfDelegation);
+        }
+        if *self.phase == Phase::Idle {
+            return Err(CommandError::NoElection);
+        }
+
+        let available = self.balance(from);
+        if credits == 0 || credits > available {
+            return Err(CommandError::InsufficientCredits { cost: credits, available });
+        }
+
+        let received = self.delegated.get(&to).copied().unwrap_or(0);
+        if let Some(cap) = self.delegation_cap {
+            if received + credits > cap {
+                return Err(CommandError::DelegationCapReached { cap, room: cap.saturating_sub(received) });
+            }
+        }
+
+        let starting_credits = self.starting_balance(to);
+        self.points.insert(from, AtomicUsize::new(available - credits));
+        self.points
+            .entry(to)
+            .or_insert_with(|| AtomicUsize::new(starting_credits))
+            .fetch_add(credits, Ordering::Relaxed);
+        self.delegated.insert(to, received + credits);
+
+        Ok(available - credits)
+    }
+
+    /// Takes what proposing costs from a member's balance. Returns the credits they have left.
+    pub fn charge_proposal(&mut self, user_id: UserId, cost: usize) -> Result<usize, CommandError> {
+        let available = self.balance(user_id);
+        if cost > available {
+            return Err(CommandError::ProposalUnaffordable { cost, available });
+        }
+        self.points.insert(user_id, AtomicUsize::new(available - cost));
+        Ok(available - cost)
+    }
+
+    /// Gives back what a member paid for a proposal that didn't make the ballot, clamped
+    /// to their credit cap like any other refund.
+    pub fn refund_proposal(&mut self, user_id: UserId, cost: usize) {
+        let refunded = (self.balance(user_id) + cost).min(self.credit_cap(user_id));
+        self.points.insert(user_id, AtomicUsize::new(refunded));
+    }
+
+    /// Removes every vote a member cast, along with their credit balance and what was
+    /// delegated to or carried over by them, so they start over if they rejoin. Returns
+    /// whether any votes were removed.
+    pub fn purge(&mut self, user_id: UserId) -> bool {
+        let mut removed = false;
+        for (_, total, voters) in self.candidates.values_mut() {
+            if let Some(votes) = voters.remove(&user_id) {
+                total.fetch_sub(votes.into_inner(), Ordering::Relaxed);
+                removed = true;
+            }
+        }
+        self.points.remove(&user_id);
+        self.delegated.remove(&user_id);
+        self.carried.remove(&user_id);
+        removed
+    }
+
+    /// Adds a proposal to the ballot while voting is open, as in open voting.
+    /// `next_id` hands out its ID once the proposal is accepted.
+    pub fn add_candidate(&mut self, idea: String, next_id: impl FnOnce() -> usize) -> Result<usize, CommandError> {
+        if *self.phase != Phase::Voting {
+            return Err(CommandError::NoElection);
+        }
+        if self.candidates.values().any(|(name, _, _)| *name == idea) {
+            return Err(CommandError::DuplicateProposal(idea));
+        }
+
+        let id = next_id();
+        self.candidates.insert(id, (idea, AtomicUsize::new(0), HashMap::new()));
+        Ok(id)
+    }
+
+    /// Moves an idle election into its suggestion phase.
+    pub fn begin_suggestions(&mut self) {
+        if *self.phase == Phase::Idle {
+            *self.phase = Phase::Suggestion;
+        }
+    }
+
+    /// Closes the suggestion phase to new proposals, leaving them to be reviewed until
+    /// voting opens.
+    pub fn lock_proposals(&mut self) -> Result<(), CommandError> {
+        match *self.phase {
+            Phase::Idle => Err(CommandError::NoElection),
+            Phase::Suggestion => {
+                *self.phase = Phase::ProposalsLocked;
+                Ok(())
+            },
+            Phase::ProposalsLocked => Err(CommandError::ProposalsLocked),
+            Phase::Voting => Err(CommandError::VoteInProgress { action: "Proposals cannot be locked" }),
+        }
+    }
+
+    /// Opens voting on the given candidates, each starting without votes.
+    pub fn open(&mut self, candidates: impl IntoIterator<Item = (usize, String)>) {
+        for (id, name) in candidates {
+            self.candidates.insert(id, (name, AtomicUsize::new(0), HashMap::new()));
+        }
+        *self.phase = Phase::Voting;
+    }
+
+    /// Keeps only the tied candidates on the ballot, with fresh tallies, for a runoff vote.
+    pub fn start_runoff(&mut self, tied: &[Ranked]) {
+        self.candidates.retain(|id, _| tied.iter().any(|c| c.0 == *id));
+        for (_, votes, voters) in self.candidates.values_mut() {
+            votes.store(0, Ordering::Relaxed);
+            voters.clear();
+        }
+    }
+
+    /// Ends the election, clearing the ballot.
+    pub fn close(&mut self) {
+        *self.phase = Phase::Idle;
+        self.candidates.clear();
+    }
+
+    /// Ends the election and forgets everyone's credits, as /reset does.
+    pub fn reset(&mut self) {
+        self.close();
+        self.points.clear();
+        self.delegated.clear();
+        self.carried.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: u64) -> UserId {
+        UserId::new(id)
+    }
+
+    /// A guild's voting state, owned so tests can borrow it as a `VotingState`
+    struct Guild {
+        phase: Phase,
+        candidates: HashMap<usize, CandidateVotes>,
+        points: HashMap<UserId, AtomicUsize>,
+        delegated: HashMap<UserId, usize>,
+        carried: HashMap<UserId, usize>,
+    }
+
+    impl Guild {
+        /// A guild with no election running.
+        fn idle() -> Self {
+            Guild { phase: Phase::Idle, candidates: HashMap::new(), points: HashMap::new(), delegated: HashMap::new(), carried: HashMap::new() }
+        }
+
+        /// A guild voting on `candidates` candidates, numbered from 1.
+        fn voting(candidates: usize) -> Self {
+            let mut guild = Guild::idle();
+            guild.state().open((1..=candidates).map(|id| (id, format!("Candidate {}", id))));
+            guild
+        }
+
+        /// The state with the default settings: 100 credits and at most 10 votes per candidate.
+        fn state(&mut self) -> VotingState<'_> {
+            VotingState {
+                phase: &mut self.phase,
+                candidates: &mut self.candidates,
+                points: &mut self.points,
+                delegated: &mut self.delegated,
+                carried: &mut self.carried,
+                starting_credits: 100,
+                snapshot: None,
+                max_votes: 10,
+                freeze: None,
+                delegation_cap: None,
+                max_concentration: None,
+                max_candidates: None,
+                overspend: OverspendPolicy::Reject,
+            }
+        }
+
+        fn votes(&self, candidate_id: usize) -> usize {
+            self.candidates[&candidate_id].1.load(Ordering::Relaxed)
+        }
+    }
+
+    const FREEZE: FreezeWindow = FreezeWindow { minutes: 10, allows_new_votes: true };
+
+    #[test]
+    fn freeze_window_starts_exactly_at_its_length_before_the_deadline() {
+        let deadline = 1_000_000;
+        assert!(!FREEZE.covers(deadline, deadline - 10 * 60 - 1));
+        assert!(FREEZE.covers(deadline, deadline - 10 * 60));
+        assert!(FREEZE.covers(deadline, deadline));
+        // Past the deadline, until an admin stops the vote
+        assert!(FREEZE.covers(deadline, deadline + 60));
+    }
+
+    #[test]
+    fn freeze_window_refuses_changes_but_can_allow_first_votes() {
+        assert!(FREEZE.check(false).is_ok());
+        assert!(matches!(FREEZE.check(true), Err(CommandError::VotesFrozen { changes_existing_vote: true, .. })));
+
+        let strict = FreezeWindow { allows_new_votes: false, ..FREEZE };
+        assert!(matches!(strict.check(false), Err(CommandError::VotesFrozen { changes_existing_vote: false, .. })));
+        assert!(matches!(strict.check(true), Err(CommandError::VotesFrozen { changes_existing_vote: true, .. })));
+    }
+
+    #[test]
+    fn frozen_votes_can_be_cast_once_but_not_changed_or_retracted() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+        voting.freeze = Some(FREEZE);
+
+        assert_eq!(voting.cast(user(1), 1, 2), Ok((2, 96)));
+        assert!(matches!(voting.cast(user(1), 1, 3), Err(CommandError::VotesFrozen { changes_existing_vote: true, .. })));
+        assert!(matches!(voting.retract(user(1), 1), Err(CommandError::VotesFrozen { .. })));
+        assert_eq!(voting.balance(user(1)), 96);
+        assert_eq!(guild.votes(1), 2);
+
+        let mut voting = guild.state();
+        voting.freeze = Some(FreezeWindow { allows_new_votes: false, ..FREEZE });
+        assert!(matches!(voting.cast(user(2), 2, 1), Err(CommandError::VotesFrozen { changes_existing_vote: false, .. })));
+    }
+
+    #[test]
+    fn vote_limit_follows_the_guild_setting_beyond_ten() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+        voting.starting_credits = 1000;
+        voting.max_votes = 20;
+
+        assert_eq!(voting.cast(user(1), 1, 15), Ok((15, 775)));
+        assert_eq!(voting.cast(user(1), 1, 20), Ok((20, 600)));
+        assert!(matches!(voting.cast(user(1), 1, 21), Err(CommandError::OutOfRange { max: 20, .. })));
+        assert_eq!(
+            voting.cast_batch(user(1), &[(2, 21)]),
+            Err(CommandError::BatchRejected(Box::new(CommandError::OverVoteLimit { candidate_id: 2, votes: 21, max: 20 })))
+        );
+        assert_eq!(guild.votes(1), 20);
+    }
+
+    #[test]
+    fn votes_cost_their_square_and_credits_buy_the_most_they_cover() {
+        assert_eq!(cost(0), 0);
+        assert_eq!(cost(1), 1);
+        assert_eq!(cost(10), 100);
+
+        assert_eq!(affordable_votes(10, 100), 10);
+        assert_eq!(affordable_votes(10, 99), 9);
+        assert_eq!(affordable_votes(5, 100), 5);
+        assert_eq!(affordable_votes(3, 0), 0);
+    }
+
+    #[test]
+    fn votes_can_only_be_cast_while_voting() {
+        assert_eq!(Phase::Idle.check_vote(), Err(CommandError::NoElection));
+        assert_eq!(Phase::Suggestion.check_vote(), Err(CommandError::NoVoteInProgress));
+        assert_eq!(Phase::ProposalsLocked.check_vote(), Err(CommandError::NoVoteInProgress));
+        assert_eq!(Phase::Voting.check_vote(), Ok(()));
+    }
+
+    #[test]
+    fn votes_in_an_idle_guild_say_no_election_is_running() {
+        let mut guild = Guild::idle();
+        assert_eq!(guild.state().cast(user(1), 3, 1), Err(CommandError::NoElection));
+        assert_eq!(guild.state().cast_batch(user(1), &[(3, 1)]), Err(CommandError::NoElection));
+        assert_eq!(guild.state().retract(user(1), 3), Err(CommandError::NoElection));
+
+        guild.phase = Phase::Suggestion;
+        assert_eq!(guild.state().cast(user(1), 3, 1), Err(CommandError::NoVoteInProgress));
+        assert_eq!(guild.state().retract(user(1), 3), Err(CommandError::NoVoteInProgress));
+        assert!(guild.points.is_empty());
+    }
+
+    #[test]
+    fn proposals_are_taken_during_suggestions_and_open_voting() {
+        for open_voting in [false, true] {
+            assert_eq!(Phase::Idle.check_proposal(open_voting), Err(CommandError::NoElection));
+            assert_eq!(Phase::Suggestion.check_proposal(open_voting), Ok(()));
+            assert_eq!(Phase::ProposalsLocked.check_proposal(open_voting), Err(CommandError::ProposalsLocked));
+        }
+        assert_eq!(
+            Phase::Voting.check_proposal(false),
+            Err(CommandError::VoteInProgress { action: "Candidates cannot be proposed" })
+        );
+        assert_eq!(Phase::Voting.check_proposal(true), Ok(()));
+    }
+
+    #[test]
+    fn locked_proposals_take_no_new_or_reworded_ideas_and_no_votes_yet() {
+        let phase = Phase::ProposalsLocked;
+        for open_voting in [false, true] {
+            assert_eq!(phase.check_proposal(open_voting), Err(CommandError::ProposalsLocked));
+        }
+        assert_eq!(phase.check_edit(false), Err(CommandError::ProposalsLocked));
+        // Admins can still tidy up proposals during the review
+        assert_eq!(phase.check_edit(true), Ok(()));
+        assert_eq!(phase.check_vote(), Err(CommandError::NoVoteInProgress));
+
+        assert_eq!(Phase::Suggestion.check_edit(false), Ok(()));
+        assert_eq!(Phase::Voting.check_edit(true), Err(CommandError::VoteInProgress { action: "Proposals cannot be edited" }));
+
+        let mut guild = Guild::idle();
+        guild.state().begin_suggestions();
+        guild.state().lock_proposals().unwrap();
+        assert_eq!(guild.state().cast(user(1), 1, 1), Err(CommandError::NoVoteInProgress));
+    }
+
+    #[test]
+    fn proposals_lock_only_during_suggestions() {
+        let mut guild = Guild::idle();
+        assert_eq!(guild.state().lock_proposals(), Err(CommandError::NoElection));
+
+        guild.state().begin_suggestions();
+        assert_eq!(guild.state().lock_proposals(), Ok(()));
+        assert_eq!(guild.phase, Phase::ProposalsLocked);
+        assert_eq!(guild.state().lock_proposals(), Err(CommandError::ProposalsLocked));
+
+        // Voting opens from the locked phase as it does from suggestions
+        guild.state().open([(1, "Candidate 1".to_string())]);
+        assert_eq!(
+            guild.state().lock_proposals(),
+            Err(CommandError::VoteInProgress { action: "Proposals cannot be locked" })
+        );
+        assert_eq!(guild.phase, Phase::Voting);
+    }
+
+    #[test]
+    fn casting_again_refunds_the_previous_votes_for_the_candidate() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+
+        assert_eq!(voting.cast(user(1), 1, 5), Ok((5, 75)));
+        assert_eq!(voting.cast(user(1), 1, 3), Ok((3, 91)));
+        // The refund counts towards what the new votes can cost
+        assert_eq!(voting.cast(user(1), 1, 9), Ok((9, 19)));
+        assert_eq!(
+            voting.cast(user(1), 2, 5),
+            Err(CommandError::InsufficientCredits { cost: 25, available: 19 })
+        );
+        assert_eq!(guild.votes(1), 9);
+        assert_eq!(guild.votes(2), 0);
+    }
+
+    #[test]
+    fn both_overspend_policies_cast_votes_costing_exactly_the_balance() {
+        for overspend in OverspendPolicy::ALL {
+            let mut guild = Guild::voting(2);
+            let mut voting = guild.state();
+            voting.overspend = overspend;
+            voting.cast(user(1), 1, 6).unwrap();
+
+            assert_eq!(voting.cast(user(1), 2, 8), Ok((8, 0)), "{:?}", overspend);
+        }
+    }
+
+    #[test]
+    fn votes_one_credit_over_the_balance_are_rejected_or_clamped() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+        voting.starting_credits = 99;
+        voting.cast(user(1), 1, 6).unwrap();
+        assert_eq!(voting.cast(user(1), 2, 8), Err(CommandError::InsufficientCredits { cost: 64, available: 63 }));
+        assert_eq!(voting.balance(user(1)), 63);
+
+        voting.overspend = OverspendPolicy::Clamp;
+        assert_eq!(voting.cast(user(1), 2, 8), Ok((7, 14)));
+        assert_eq!(guild.votes(2), 7);
+
+        // Clamping can't help when not even one vote is affordable
+        let mut voting = guild.state();
+        voting.overspend = OverspendPolicy::Clamp;
+        voting.starting_credits = 0;
+        assert_eq!(voting.cast(user(2), 1, 1), Err(CommandError::InsufficientCredits { cost: 1, available: 0 }));
+    }
+
+    #[test]
+    fn retracting_refunds_the_votes_and_clears_the_tally() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+        voting.cast(user(1), 1, 4).unwrap();
+        voting.cast(user(1), 2, 2).unwrap();
+        voting.cast(user(2), 1, 1).unwrap();
+
+        assert_eq!(voting.retract(user(1), 1), Ok(4));
+        assert_eq!(voting.balance(user(1)), 96);
+        assert_eq!(voting.retract(user(1), 1), Err(CommandError::NoVotesFor(1)));
+        assert_eq!(voting.retract(user(1), 3), Err(CommandError::NoSuchCandidate(3)));
+        assert_eq!(guild.votes(1), 1);
+        assert_eq!(guild.votes(2), 2);
+    }
+
+    #[test]
+    fn delegated_credits_move_between_balances_and_can_be_spent() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+
+        assert_eq!(voting.delegate(user(1), user(2), 30), Ok(70));
+        assert_eq!(voting.balance(user(1)), 70);
+        assert_eq!(voting.balance(user(2)), 130);
+        assert_eq!(voting.credit_cap(user(2)), 130);
+        assert_eq!(voting.cast(user(2), 1, 10), Ok((10, 30)));
+        assert_eq!(voting.cast(user(2), 2, 5), Ok((5, 5)));
+        // Retracting refunds up to the cap, which includes the delegated credits
+        assert_eq!(voting.retract(user(2), 1), Ok(10));
+        assert_eq!(voting.balance(user(2)), 105);
+    }
+
+    #[test]
+    fn delegation_is_refused_to_oneself_beyond_the_balance_or_over_the_cap() {
+        let mut guild = Guild::idle();
+        assert_eq!(guild.state().delegate(user(1), user(2), 10), Err(CommandError::NoElection));
+
+        let mut guild = Guild::voting(1);
+        let mut voting = guild.state();
+        voting.delegation_cap = Some(50);
+        assert_eq!(voting.delegate(user(1), user(1), 10), Err(CommandError::SelfDelegation));
+        assert_eq!(voting.delegate(user(1), user(2), 0), Err(CommandError::InsufficientCredits { cost: 0, available: 100 }));
+        assert_eq!(voting.delegate(user(1), user(2), 101), Err(CommandError::InsufficientCredits { cost: 101, available: 100 }));
+
+        assert_eq!(voting.delegate(user(1), user(2), 40), Ok(60));
+        assert_eq!(voting.delegate(user(3), user(2), 20), Err(CommandError::DelegationCapReached { cap: 50, room: 10 }));
+        assert_eq!(voting.delegate(user(3), user(2), 10), Ok(90));
+        assert_eq!(voting.balance(user(2)), 150);
+    }
+
+    #[test]
+    fn proposals_are_charged_only_when_affordable() {
+        let mut guild = Guild::idle();
+        guild.state().begin_suggestions();
+        let mut voting = guild.state();
+
+        assert_eq!(voting.charge_proposal(user(1), 30), Ok(70));
+        assert_eq!(voting.charge_proposal(user(1), 70), Ok(0));
+        assert_eq!(voting.charge_proposal(user(1), 1), Err(CommandError::ProposalUnaffordable { cost: 1, available: 0 }));
+        assert_eq!(voting.charge_proposal(user(2), 101), Err(CommandError::ProposalUnaffordable { cost: 101, available: 100 }));
+        // A refused proposal costs nothing
+        assert_eq!(voting.balance(user(2)), 100);
+    }
+
+    #[test]
+    fn rejected_proposals_are_refunded_up_to_the_credit_cap() {
+        let mut guild = Guild::idle();
+        guild.state().begin_suggestions();
+        let mut voting = guild.state();
+
+        // Charged on /prop, then given back when a moderator rejects it
+        voting.charge_proposal(user(1), 20).unwrap();
+        voting.refund_proposal(user(1), 20);
+        assert_eq!(voting.balance(user(1)), 100);
+
+        // A refund never lifts a member above what they were given
+        voting.refund_proposal(user(1), 20);
+        assert_eq!(voting.balance(user(1)), 100);
+    }
+
+    #[test]
+    fn even_splits_leave_over_what_another_round_would_overspend() {
+        let mut guild = Guild::voting(3);
+        let mut voting = guild.state();
+
+        // A sixth vote each would cost 108 credits
+        let pairs = voting.even_split(user(1)).unwrap();
+        assert_eq!(pairs, vec![(1, 5), (2, 5), (3, 5)]);
+        assert_eq!(voting.cast_batch(user(1), &pairs), Ok(25));
+
+        // Votes already cast are built on, and their refund counts towards the budget
+        let mut guild = Guild::voting(3);
+        let mut voting = guild.state();
+        voting.cast(user(1), 1, 3).unwrap();
+        let pairs = voting.even_split(user(1)).unwrap();
+        assert_eq!(pairs, vec![(1, 7), (2, 4), (3, 4)]);
+        assert_eq!(voting.cast_batch(user(1), &pairs), Ok(19));
+    }
+
+    #[test]
+    fn even_splits_stop_at_the_vote_limit() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+        voting.starting_credits = 1000;
+
+        let pairs = voting.even_split(user(1)).unwrap();
+        assert_eq!(pairs, vec![(1, 10), (2, 10)]);
+        assert_eq!(voting.cast_batch(user(1), &pairs), Ok(800));
+        assert_eq!(voting.even_split(user(1)), Err(CommandError::NothingToSpread));
+    }
+
+    #[test]
+    fn votes_spread_over_one_candidate_too_many_are_refused() {
+        let mut guild = Guild::voting(4);
+        let mut voting = guild.state();
+        voting.max_candidates = Some(2);
+        voting.cast(user(1), 1, 1).unwrap();
+        voting.cast(user(1), 2, 1).unwrap();
+
+        assert_eq!(voting.cast(user(1), 3, 1), Err(CommandError::TooManyCandidates { max: 2 }));
+        assert_eq!(voting.cast_batch(user(1), &[(1, 2), (4, 1)]), Err(CommandError::BatchRejected(Box::new(CommandError::TooManyCandidates { max: 2 }))));
+        assert_eq!(voting.approve(user(1), 3), Err(CommandError::TooManyCandidates { max: 2 }));
+        // Candidates already backed can still be changed at the cap
+        assert_eq!(voting.cast(user(1), 2, 3), Ok((3, 90)));
+        assert_eq!(voting.cast_batch(user(1), &[(1, 4), (2, 2)]), Ok(80));
+        // Other members have their own allowance
+        assert_eq!(voting.cast(user(2), 3, 1), Ok((1, 99)));
+
+        // Backing fewer candidates frees a place
+        voting.retract(user(1), 1).unwrap();
+        assert_eq!(voting.cast(user(1), 3, 1), Ok((1, 95)));
+    }
+
+    #[test]
+    fn even_splits_keep_to_the_candidate_and_concentration_limits() {
+        // Candidates already backed are kept first
+        let mut guild = Guild::voting(3);
+        let mut voting = guild.state();
+        voting.max_candidates = Some(2);
+        voting.cast(user(1), 3, 1).unwrap();
+        let pairs = voting.even_split(user(1)).unwrap();
+        assert_eq!(pairs, vec![(3, 7), (1, 6)]);
+        assert_eq!(voting.cast_batch(user(1), &pairs), Ok(15));
+
+        // 25% of 100 credits buys at most 5 votes per candidate
+        let mut guild = Guild::voting(3);
+        let mut voting = guild.state();
+        voting.max_concentration = Some(25);
+        let pairs = voting.even_split(user(1)).unwrap();
+        assert_eq!(pairs, vec![(1, 5), (2, 5), (3, 5)]);
+        assert_eq!(voting.cast_batch(user(1), &pairs), Ok(25));
+    }
+
+    #[test]
+    fn even_splits_never_spend_more_than_the_balance() {
+        for starting_credits in 0..=300 {
+            let mut guild = Guild::voting(4);
+            let mut voting = guild.state();
+            voting.starting_credits = starting_credits;
+            if starting_credits % 3 == 0 && starting_credits >= 4 {
+                voting.cast(user(1), 2, 2).unwrap();
+            }
+
+            match voting.even_split(user(1)) {
+                Ok(pairs) => assert!(voting.cast_batch(user(1), &pairs).is_ok(), "{} credits: {:?}", starting_credits, pairs),
+                Err(why) => assert_eq!(why, CommandError::NothingToSpread, "{} credits", starting_credits),
+            }
+        }
+    }
+
+    /// Credits the given members hold, spent on votes or not.
+    fn total_credits(voting: &VotingState, members: &[UserId]) -> usize {
+        let spending = voting.spending();
+        members.iter().map(|id| voting.balance(*id) + spending.get(id).copied().unwrap_or(0)).sum()
+    }
+
+    #[test]
+    fn voting_and_retracting_never_creates_credits() {
+        let mut guild = Guild::voting(3);
+        let mut voting = guild.state();
+        let members = [user(1), user(2)];
+        assert_eq!(voting.delegate(user(2), user(1), 40), Ok(60));
+        let total = total_credits(&voting, &members);
+
+        for round in 0..20 {
+            let votes = round % 10 + 1;
+            let _ = voting.cast(user(1), 1, votes);
+            let _ = voting.cast_batch(user(1), &[(2, votes), (3, 10 - round % 10)]);
+            let _ = voting.cast(user(2), 2, 11 - votes);
+            let _ = voting.retract(user(1), 1 + round % 3);
+            let _ = voting.retract(user(2), 2);
+            assert_eq!(total_credits(&voting, &members), total, "round {}", round);
+            assert!(voting.balance(user(1)) <= voting.credit_cap(user(1)));
+        }
+
+        for candidate_id in 1..=3 {
+            let _ = voting.retract(user(1), candidate_id);
+        }
+        assert_eq!(voting.balance(user(1)), 140);
+    }
+
+    #[test]
+    fn votes_may_cost_exactly_the_concentration_cap_but_no_more() {
+        let mut guild = Guild::voting(2);
+        let mut voting = guild.state();
+        voting.max_concentration = Some(25);
+
+        assert_eq!(voting.cast(user(1), 1, 5), Ok((5, 75)));
+        assert_eq!(
+            voting.cast(user(1), 2, 6),
+            Err(CommandError::OverConcentration { candidate_id: 2, cost: 36, cap: 25, percent: 25 })
+        );
+        assert_eq!(
+            voting.cast_batch(user(1), &[(2, 5), (1, 6)]),
+            Err(CommandError::BatchRejected(Box::new(CommandError::OverConcentration { candidate_id: 1, cost: 36, cap: 25, percent: 25 })))
+        );
+        assert_eq!(voting.cast_batch(user(1), &[(2, 5)]), Ok(50));
+    }
+}
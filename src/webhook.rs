@@ -0,0 +1,120 @@
+//! Best-effort delivery of final election results to an external webhook,
+//! so downstream automation can react when an election ends.
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Environment variable holding the URL results are POSTed to
+pub const RESULTS_WEBHOOK_URL_KEY: &str = "RESULTS_WEBHOOK_URL";
+
+/// Environment variable holding the shared secret used to sign payloads
+pub const RESULTS_WEBHOOK_SECRET_KEY: &str = "RESULTS_WEBHOOK_SECRET";
+
+/// Header carrying the hex HMAC-SHA256 of the request body, as `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-QVoting-Signature";
+
+/// How many times delivery is attempted before giving up
+const MAX_ATTEMPTS: u32 = 3;
+
+/// How long a single delivery attempt may take
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A candidate's final position in the election
+#[derive(Serialize)]
+pub struct RankedCandidate {
+    pub rank: usize,
+    pub name: String,
+    pub votes: usize,
+}
+
+/// The JSON payload POSTed when an election ends
+#[derive(Serialize)]
+pub struct ElectionResults {
+    pub guild_id: String,
+    pub prompt: Option<String>,
+    /// Unix timestamps (in seconds)
+    pub started_at: Option<i64>,
+    pub ended_at: i64,
+    pub total_votes: usize,
+    pub candidates: Vec<RankedCandidate>,
+}
+
+/// Where finished elections are reported
+pub struct ResultsWebhook {
+    url: String,
+    secret: Option<String>,
+    client: reqwest::Client,
+}
+
+impl ResultsWebhook {
+    /// Reads the webhook settings from the environment. Returns `None` when no URL is configured.
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var(RESULTS_WEBHOOK_URL_KEY).ok().filter(|u| !u.trim().is_empty())?;
+        let secret = std::env::var(RESULTS_WEBHOOK_SECRET_KEY).ok().filter(|s| !s.is_empty());
+        if secret.is_none() {
+            println!("{} is not set: results webhook payloads will be unsigned", RESULTS_WEBHOOK_SECRET_KEY);
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(ATTEMPT_TIMEOUT)
+            .build()
+            .ok()?;
+
+        Some(Self { url: url.trim().to_string(), secret, client })
+    }
+
+    /// Sends the results, retrying with a growing delay. Failures are only logged.
+    pub async fn deliver(&self, results: &ElectionResults) {
+        let body = match serde_json::to_vec(results) {
+            Ok(body) => body,
+            Err(e) => {
+                eprintln!("Failed to serialize election results for webhook: {}", e);
+                return;
+            }
+        };
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let mut request = self.client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            if let Some(secret) = &self.secret {
+                request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+            }
+
+            match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    println!("Delivered election results webhook for guild {}", results.guild_id);
+                    return;
+                },
+                Ok(response) => {
+                    eprintln!("Results webhook returned {} (attempt {})", response.status(), attempt);
+                },
+                Err(e) => {
+                    eprintln!("Results webhook failed (attempt {}): {}", attempt, e);
+                }
+            }
+
+            if attempt < MAX_ATTEMPTS {
+                tokio::time::sleep(Duration::from_secs(2u64.pow(attempt - 1))).await;
+            }
+        }
+
+        eprintln!("Giving up on results webhook for guild {} after {} attempts", results.guild_id, MAX_ATTEMPTS);
+    }
+}
+
+/// Hex-encoded HMAC-SHA256 of the body, keyed with the shared secret.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}